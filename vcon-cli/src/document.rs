@@ -0,0 +1,66 @@
+use crate::CliResult;
+use std::io::{Read as _, Write as _};
+use std::path::Path;
+use vcon_types::{ParseOptions, Vcon};
+
+/// Serialization of a document
+#[derive(Debug, Copy, Clone, Eq, PartialEq, clap::ValueEnum)]
+pub enum Format {
+    Json,
+    Cbor,
+}
+
+/// Reads the document at `path` as JSON, "-" being stdin.
+///
+/// JSON documents are objects, so anything not starting with `{` is read as CBOR, which is
+/// transcoded to JSON within the default [ParseOptions].
+pub fn read(path: &Path) -> CliResult<(Format, Vec<u8>)> {
+    let mut bytes = vec![];
+    if path == Path::new("-") {
+        std::io::stdin().read_to_end(&mut bytes)?;
+    } else {
+        bytes = std::fs::read(path)?;
+    }
+    if bytes.trim_ascii_start().starts_with(b"{") {
+        Ok((Format::Json, bytes))
+    } else {
+        Ok((
+            Format::Cbor,
+            ParseOptions::default().transcode_cbor(&bytes)?,
+        ))
+    }
+}
+
+/// Reads the vCon at `path`, "-" being stdin, within the default [ParseOptions]
+pub fn read_vcon(path: &Path) -> CliResult<Vcon> {
+    parse_vcon(&read(path)?.1)
+}
+
+/// Parses the JSON vCon `json` within the default [ParseOptions]
+pub fn parse_vcon(json: &[u8]) -> CliResult<Vcon> {
+    Ok(Vcon::parse_json(json, &ParseOptions::default())?)
+}
+
+/// Writes `value` to `path`, "-" being stdout. `pretty` only applies to JSON.
+pub fn write(
+    path: &Path,
+    value: &serde_json::Value,
+    format: Format,
+    pretty: bool,
+) -> CliResult<()> {
+    let mut bytes = vec![];
+    match format {
+        Format::Json if pretty => serde_json::to_writer_pretty(&mut bytes, value)?,
+        Format::Json => serde_json::to_writer(&mut bytes, value)?,
+        Format::Cbor => ciborium::into_writer(value, &mut bytes)?,
+    }
+    if format == Format::Json {
+        bytes.push(b'\n');
+    }
+    if path == Path::new("-") {
+        std::io::stdout().write_all(&bytes)?;
+    } else {
+        std::fs::write(path, bytes)?;
+    }
+    Ok(())
+}
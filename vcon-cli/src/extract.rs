@@ -0,0 +1,82 @@
+use crate::CliResult;
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use vcon_types::{Content, ContentParameters, Dialog, Url, Uuid, Vcon, VconError, VconResult};
+
+/// Fetches externally referenced bodies from the local file system
+pub struct LocalStore {
+    /// Directory holding bodies by last segment of their url, `file://` urls being read as is
+    pub blobs: Option<PathBuf>,
+}
+
+impl vcon_types::VconStore for LocalStore {
+    fn load(&self, uuid: &Uuid) -> VconResult<Vcon> {
+        Err(VconError::UnresolvedReference(format!(
+            "cannot load vCon {uuid}"
+        )))
+    }
+
+    fn fetch(&self, url: &Url) -> VconResult<Vec<u8>> {
+        let unresolved = || VconError::UnresolvedReference(url.as_str().to_string());
+        let path = match url.as_str().strip_prefix("file://") {
+            Some(path) => PathBuf::from(path),
+            None => {
+                let name = url.as_str().rsplit('/').next().filter(|n| !n.is_empty());
+                self.blobs
+                    .as_ref()
+                    .zip(name)
+                    .map(|(b, n)| b.join(n))
+                    .ok_or_else(unresolved)?
+            }
+        };
+        Ok(std::fs::read(path)?)
+    }
+}
+
+/// Writes the body of every recording and text dialog then of every attachment of `vcon` to
+/// `dir`, named after their `filename` when set. Externally referenced bodies are fetched from
+/// `store` and fail to extract when they do not match their digest.
+///
+/// Returns the paths written.
+pub fn extract(vcon: &Vcon, store: &LocalStore, dir: &Path) -> CliResult<Vec<PathBuf>> {
+    std::fs::create_dir_all(dir)?;
+    let mut written = vec![];
+    let mut write = |default: String, parameters: &ContentParameters, content: &Content| {
+        let name = parameters
+            .filename
+            .as_deref()
+            .and_then(|f| Path::new(f).file_name())
+            .map(PathBuf::from)
+            .unwrap_or_else(|| default.into());
+        let path = dir.join(name);
+        let mut file = std::io::BufWriter::new(std::fs::File::create(&path)?);
+        content.write_to(store, &mut file)?;
+        file.flush()?;
+        written.push(path);
+        CliResult::Ok(())
+    };
+
+    for (i, object) in vcon.dialog.iter().flatten().enumerate() {
+        if let Dialog::Recording {
+            content_parameters,
+            content,
+            ..
+        }
+        | Dialog::Text {
+            content_parameters,
+            content,
+            ..
+        } = &object.dialog
+        {
+            write(format!("dialog-{i}"), content_parameters, content)?;
+        }
+    }
+    for (i, attachment) in vcon.attachments.iter().flatten().enumerate() {
+        write(
+            format!("attachment-{i}"),
+            &attachment.content_parameters,
+            &attachment.content,
+        )?;
+    }
+    Ok(written)
+}
@@ -0,0 +1,89 @@
+use std::fmt::Write as _;
+use vcon_types::{Date, Dialog, Vcon};
+
+/// Human readable summary of `vcon`
+pub fn summary(vcon: &Vcon) -> String {
+    let mut out = String::new();
+    let mut line = |s: String| {
+        out.push_str(&s);
+        out.push('\n');
+    };
+
+    line(format!("uuid: {}", vcon.uuid));
+    line(format!("version: {}", vcon.version.as_str()));
+    if let Some(subject) = &vcon.subject {
+        line(format!("subject: {subject}"));
+    }
+    if let Some(created_at) = &vcon.created_at {
+        line(format!("created at: {}", date(created_at)));
+    }
+
+    let parties = vcon.parties.as_deref().unwrap_or_default();
+    line(format!("parties: {}", parties.len()));
+    for (i, party) in parties.iter().enumerate() {
+        let mut description = party.display_name().unwrap_or("unknown").to_string();
+        if let Some(role) = &party.role {
+            let _ = write!(description, " ({role})");
+        }
+        line(format!("  [{i}] {description}"));
+    }
+
+    let dialogs = vcon.dialog.as_deref().unwrap_or_default();
+    let mut total = 0.0;
+    line(format!("dialogs: {}", dialogs.len()));
+    for (i, object) in dialogs.iter().enumerate() {
+        let mut description = format!("{} at {}", object.dialog.typ(), date(&object.start));
+        match &object.dialog {
+            Dialog::Recording {
+                duration, parties, ..
+            }
+            | Dialog::Text {
+                duration, parties, ..
+            } => {
+                if let Some(duration) = duration {
+                    total += duration.as_secs_f64();
+                    let _ = write!(description, ", {:.1}s", duration.as_secs_f64());
+                }
+                let parties = parties.indexes().into_iter().map(|p| p.to_string());
+                let _ = write!(
+                    description,
+                    ", parties {}",
+                    parties.collect::<Vec<_>>().join(", ")
+                );
+            }
+            Dialog::Incomplete { disposition } => {
+                let _ = write!(description, ", {disposition}");
+            }
+            Dialog::Transfer { .. } => {}
+        }
+        line(format!("  [{i}] {description}"));
+    }
+    line(format!("total duration: {total:.1}s"));
+
+    let analysis = vcon.analysis.as_deref().unwrap_or_default();
+    line(format!("analysis: {}", analysis.len()));
+    for (i, analysis) in analysis.iter().enumerate() {
+        let mut description = format!("{} of dialog {}", analysis.typ, analysis.dialog);
+        if let Some(vendor) = &analysis.vendor {
+            let _ = write!(description, " by {vendor}");
+        }
+        line(format!("  [{i}] {description}"));
+    }
+
+    let attachments = vcon.attachments.as_deref().unwrap_or_default();
+    line(format!("attachments: {}", attachments.len()));
+    for (i, attachment) in attachments.iter().enumerate() {
+        line(format!(
+            "  [{i}] {} from party {}",
+            attachment.typ, attachment.party
+        ));
+    }
+    out
+}
+
+fn date(date: &Date) -> String {
+    match serde_json::to_value(date) {
+        Ok(serde_json::Value::String(date)) => date,
+        _ => "invalid date".to_string(),
+    }
+}
@@ -0,0 +1,192 @@
+//! `vcon` command-line tool
+
+mod document;
+mod extract;
+mod inspect;
+mod jws;
+
+use clap::{Parser, Subcommand};
+use document::Format;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use vcon_types::Vcon;
+
+type CliResult<T> = Result<T, Box<dyn std::error::Error>>;
+
+/// Inspect, validate, convert, sign and verify vCons
+///
+/// Documents are read as JSON or CBOR, detected from their content. A path of "-" (the default)
+/// stands for stdin or stdout.
+#[derive(Debug, Parser)]
+#[command(name = "vcon", version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Summary of the parties, dialogs and analysis of a vCon
+    Inspect {
+        #[arg(default_value = "-")]
+        input: PathBuf,
+    },
+    /// Checks a vCon is well typed and its indexes reference existing parties and dialogs
+    Validate {
+        #[arg(default_value = "-")]
+        input: PathBuf,
+    },
+    /// Converts a vCon between JSON and CBOR
+    Convert {
+        #[arg(default_value = "-")]
+        input: PathBuf,
+        #[arg(short, long, default_value = "-")]
+        output: PathBuf,
+        #[arg(long, value_enum, default_value_t = Format::Json)]
+        to: Format,
+        /// Indents JSON output
+        #[arg(long)]
+        pretty: bool,
+    },
+    /// Signs a vCon into a JWS in general JSON serialization
+    Sign {
+        #[arg(default_value = "-")]
+        input: PathBuf,
+        #[arg(short, long, default_value = "-")]
+        output: PathBuf,
+        /// PEM private key
+        #[arg(short, long)]
+        key: PathBuf,
+        #[arg(long, default_value = "EdDSA")]
+        alg: jsonwebtoken::Algorithm,
+    },
+    /// Verifies a signed vCon, writing the vCon it contains
+    Verify {
+        #[arg(default_value = "-")]
+        input: PathBuf,
+        #[arg(short, long, default_value = "-")]
+        output: PathBuf,
+        /// PEM public key, or secret for HMAC algorithms
+        #[arg(short, long)]
+        key: PathBuf,
+        #[arg(long, default_value = "EdDSA")]
+        alg: jsonwebtoken::Algorithm,
+    },
+    /// Writes a redacted copy of a vCon, referencing the original one
+    Redact {
+        #[arg(default_value = "-")]
+        input: PathBuf,
+        #[arg(short, long, default_value = "-")]
+        output: PathBuf,
+        /// Kind of redaction
+        #[arg(long = "type", default_value = "redacted")]
+        typ: String,
+        /// Index of a party whose identity is removed
+        #[arg(long)]
+        party: Vec<u32>,
+        /// Index of a dialog whose body and analysis are removed
+        #[arg(long)]
+        dialog: Vec<u32>,
+        /// Index of an attachment to remove
+        #[arg(long)]
+        attachment: Vec<u32>,
+    },
+    /// Writes the bodies of dialogs and attachments to files, verifying digests
+    Extract {
+        #[arg(default_value = "-")]
+        input: PathBuf,
+        /// Directory the bodies are written to
+        #[arg(short, long)]
+        dir: PathBuf,
+        /// Directory holding externally referenced bodies, by last segment of their url
+        #[arg(long)]
+        blobs: Option<PathBuf>,
+    },
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(cli: Cli) -> CliResult<()> {
+    match cli.command {
+        Command::Inspect { input } => {
+            let vcon = document::read_vcon(&input)?;
+            print!("{}", inspect::summary(&vcon));
+        }
+        Command::Validate { input } => {
+            document::read_vcon(&input)?.validate()?;
+            println!("valid");
+        }
+        Command::Convert {
+            input,
+            output,
+            to,
+            pretty,
+        } => {
+            let (_, json) = document::read(&input)?;
+            // only convert what is a vCon
+            document::parse_vcon(&json)?;
+            document::write(&output, &serde_json::from_slice(&json)?, to, pretty)?;
+        }
+        Command::Sign {
+            input,
+            output,
+            key,
+            alg,
+        } => {
+            let vcon = document::read_vcon(&input)?;
+            let signed = jws::sign(&vcon, &std::fs::read(key)?, alg)?;
+            document::write(&output, &signed, Format::Json, true)?;
+        }
+        Command::Verify {
+            input,
+            output,
+            key,
+            alg,
+        } => {
+            let (_, signed) = document::read(&input)?;
+            let vcon = jws::verify(&serde_json::from_slice(&signed)?, &std::fs::read(key)?, alg)?;
+            document::write(&output, &serde_json::to_value(&vcon)?, Format::Json, true)?;
+        }
+        Command::Redact {
+            input,
+            output,
+            typ,
+            party,
+            dialog,
+            mut attachment,
+        } => {
+            let (format, json) = document::read(&input)?;
+            let original = document::parse_vcon(&json)?;
+            let mut redacted = Vcon::redact(&original, typ);
+            for index in party {
+                redacted.redact_party(index)?;
+            }
+            for index in dialog {
+                redacted.redact_dialog(index)?;
+            }
+            attachment.sort_unstable();
+            attachment.dedup();
+            // from the last so that indexes still designate the original attachments
+            for index in attachment.into_iter().rev() {
+                redacted.redact_attachment(index)?;
+            }
+            document::write(&output, &serde_json::to_value(&redacted)?, format, true)?;
+        }
+        Command::Extract { input, dir, blobs } => {
+            let vcon = document::read_vcon(&input)?;
+            let store = extract::LocalStore { blobs };
+            for path in extract::extract(&vcon, &store, &dir)? {
+                println!("{}", path.display());
+            }
+        }
+    }
+    Ok(())
+}
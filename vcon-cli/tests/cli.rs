@@ -0,0 +1,284 @@
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use vcon_types::Vcon;
+
+const CALL: &str = "../vcon-types/examples/json/two-party-call-with-analysis.json";
+const EXTERNAL: &str =
+    "../vcon-types/examples/json/two-party-call-with-external-reference-recording.json";
+
+fn vcon(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_vcon"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn stdout(output: Output) -> String {
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn tmp(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("vcon-cli-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir.join(name)
+}
+
+fn read_json(path: &Path) -> serde_json::Value {
+    serde_json::from_slice(&std::fs::read(path).unwrap()).unwrap()
+}
+
+#[test]
+fn inspect_and_validate() {
+    let summary = stdout(vcon(&["inspect", CALL]));
+    assert!(summary.contains("[1] Bob"));
+    assert!(summary.contains("[0] recording at 2022-06-21T17:53:26Z, 33.1s, parties 0, 1"));
+    assert!(summary.contains("[0] transcript of dialog 0 by deepgram"));
+
+    assert_eq!(stdout(vcon(&["validate", CALL])), "valid\n");
+    let mut invalid = read_json(Path::new(CALL));
+    invalid["dialog"][0]["parties"] = json!([0, 5]);
+    let path = tmp("invalid.json");
+    std::fs::write(&path, invalid.to_string()).unwrap();
+    let output = vcon(&["validate", path.to_str().unwrap()]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains("dialog[0].parties references unknown party 5"));
+}
+
+#[test]
+fn convert_should_round_trip_through_cbor() {
+    let (cbor, json) = (tmp("call.cbor"), tmp("call.json"));
+    stdout(vcon(&[
+        "convert",
+        CALL,
+        "--to",
+        "cbor",
+        "-o",
+        cbor.to_str().unwrap(),
+    ]));
+    assert_ne!(std::fs::read(&cbor).unwrap().first(), Some(&b'{'));
+    stdout(vcon(&[
+        "convert",
+        cbor.to_str().unwrap(),
+        "-o",
+        json.to_str().unwrap(),
+    ]));
+    assert_eq!(read_json(&json), read_json(Path::new(CALL)));
+
+    let compact = stdout(vcon(&["convert", CALL]));
+    assert_eq!(compact.lines().count(), 1);
+    let pretty = stdout(vcon(&["convert", CALL, "--pretty"]));
+    assert!(pretty.lines().count() > 1);
+}
+
+#[test]
+fn cbor_bodies_should_be_read_as_base64url() {
+    // bodies written by the `cbor` feature: tagged byte strings holding base64url text
+    let json = read_json(Path::new(
+        "../vcon-types/examples/json/two-party-call-with-inline-recording.json",
+    ));
+    let mut cbor = ciborium::Value::serialized(&json).unwrap();
+    let (_, dialog) = cbor
+        .as_map_mut()
+        .unwrap()
+        .iter_mut()
+        .find(|(k, _)| k.as_text() == Some("dialog"))
+        .unwrap();
+    let recording = dialog.as_array_mut().unwrap()[0].as_map_mut().unwrap();
+    let (_, body) = recording
+        .iter_mut()
+        .find(|(k, _)| k.as_text() == Some("body"))
+        .unwrap();
+    let text = body.as_text().unwrap().as_bytes().to_vec();
+    *body = ciborium::Value::Tag(vcon_types::BASE_64_URL_TAG, Box::new(text.into()));
+    let path = tmp("tagged.cbor");
+    let mut bytes = vec![];
+    ciborium::into_writer(&cbor, &mut bytes).unwrap();
+    std::fs::write(&path, bytes).unwrap();
+
+    assert_eq!(
+        stdout(vcon(&["validate", path.to_str().unwrap()])),
+        "valid\n"
+    );
+    let converted = stdout(vcon(&["convert", path.to_str().unwrap()]));
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(&converted).unwrap(),
+        json
+    );
+}
+
+#[test]
+fn validate_should_report_out_of_range_durations() {
+    let mut call = read_json(Path::new(CALL));
+    call["dialog"][0]["duration"] = json!(1e30);
+    let path = tmp("duration.json");
+    std::fs::write(&path, call.to_string()).unwrap();
+    let output = vcon(&["validate", path.to_str().unwrap()]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("dialog[0].duration"));
+}
+
+#[test]
+fn validate_should_reject_unsupported_versions() {
+    let mut call = read_json(Path::new(CALL));
+    call["vcon"] = json!("9.9.9");
+    let path = tmp("version.json");
+    std::fs::write(&path, call.to_string()).unwrap();
+    let output = vcon(&["validate", path.to_str().unwrap()]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Unsupported vCon version '9.9.9'"));
+}
+
+#[test]
+fn convert_redact_and_verify_should_reject_unsupported_versions() {
+    let mut call = read_json(Path::new(CALL));
+    call["vcon"] = json!("9.9.9");
+    let path = tmp("convert-version.json");
+    std::fs::write(&path, call.to_string()).unwrap();
+    let key = jsonwebtoken::EncodingKey::from_ed_pem(include_bytes!("keys/ed25519.pem")).unwrap();
+    let header = jsonwebtoken::Header::new(jsonwebtoken::Algorithm::EdDSA);
+    let token = jsonwebtoken::encode(&header, &call, &key).unwrap();
+    let [protected, payload, signature] = token.split('.').collect::<Vec<_>>()[..] else {
+        unreachable!()
+    };
+    let signed = tmp("signed-version.json");
+    let jws = json!({
+        "payload": payload,
+        "signatures": [{ "protected": protected, "signature": signature }]
+    });
+    std::fs::write(&signed, jws.to_string()).unwrap();
+
+    for args in [
+        ["convert", path.to_str().unwrap(), "--to", "cbor"],
+        ["redact", path.to_str().unwrap(), "--type", "PII Masked"],
+        [
+            "verify",
+            signed.to_str().unwrap(),
+            "--key",
+            "tests/keys/ed25519.pub.pem",
+        ],
+    ] {
+        let output = vcon(&args);
+        assert_eq!(output.status.code(), Some(1));
+        assert!(
+            String::from_utf8_lossy(&output.stderr).contains("Unsupported vCon version '9.9.9'")
+        );
+    }
+}
+
+#[test]
+fn sign_then_verify() {
+    let (signed, verified) = (tmp("signed.json"), tmp("verified.json"));
+    stdout(vcon(&[
+        "sign",
+        CALL,
+        "--key",
+        "tests/keys/ed25519.pem",
+        "-o",
+        signed.to_str().unwrap(),
+    ]));
+    let jws = read_json(&signed);
+    assert!(jws["payload"].is_string());
+    assert_eq!(jws["signatures"].as_array().unwrap().len(), 1);
+
+    let signed = signed.to_str().unwrap();
+    stdout(vcon(&[
+        "verify",
+        signed,
+        "--key",
+        "tests/keys/ed25519.pub.pem",
+        "-o",
+        verified.to_str().unwrap(),
+    ]));
+    let vcon_at = |path: &Path| serde_json::from_value::<Vcon>(read_json(path)).unwrap();
+    assert_eq!(vcon_at(&verified), vcon_at(Path::new(CALL)));
+
+    let output = vcon(&["verify", signed, "--key", "tests/keys/other.pub.pem"]);
+    assert!(!output.status.success());
+}
+
+#[test]
+fn redact_then_extract() {
+    let redacted = tmp("redacted.json");
+    stdout(vcon(&[
+        "redact",
+        CALL,
+        "--type",
+        "PII Masked",
+        "--party",
+        "0",
+        "--dialog",
+        "0",
+        "-o",
+        redacted.to_str().unwrap(),
+    ]));
+    let mut with_attachments = read_json(Path::new(CALL));
+    with_attachments["attachments"] = json!(["contract", "invoice", "receipt"].map(|typ| json!({
+        "type": typ, "start": "2022-06-21T17:53:26Z", "party": 0, "encoding": "none", "body": typ
+    })));
+    let path = tmp("attachments.json");
+    std::fs::write(&path, with_attachments.to_string()).unwrap();
+    let output = stdout(vcon(&[
+        "redact",
+        path.to_str().unwrap(),
+        "--attachment",
+        "0",
+        "--attachment",
+        "2",
+    ]));
+    let attachments = &serde_json::from_str::<serde_json::Value>(&output).unwrap()["attachments"];
+    assert_eq!(attachments.as_array().unwrap().len(), 1);
+    assert_eq!(attachments[0]["type"], "invoice");
+    let output = vcon(&["redact", path.to_str().unwrap(), "--attachment", "3"]);
+    assert!(!output.status.success());
+    let original = read_json(Path::new(CALL));
+    let redacted = read_json(&redacted);
+    assert_ne!(redacted["uuid"], original["uuid"]);
+    assert_eq!(redacted["redacted"]["type"], "PII Masked");
+    assert!(redacted["redacted"]
+        .to_string()
+        .contains(original["uuid"].as_str().unwrap()));
+    assert_eq!(redacted["parties"][0], json!({}));
+    assert_eq!(redacted["parties"][1], original["parties"][1]);
+    assert_eq!(redacted["dialog"][0]["body"], "");
+    assert_eq!(redacted["analysis"], json!([]));
+
+    // external body is checked against its digest
+    let blobs = tmp("blobs");
+    std::fs::create_dir_all(&blobs).unwrap();
+    std::fs::write(blobs.join("ab_call.mp3"), b"not the recording").unwrap();
+    let dir = tmp("extracted");
+    let output = vcon(&[
+        "extract",
+        EXTERNAL,
+        "--dir",
+        dir.to_str().unwrap(),
+        "--blobs",
+        blobs.to_str().unwrap(),
+    ]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("does not match its signature"));
+
+    let text = read_json(Path::new(
+        "../vcon-types/examples/json/email-thread-text.json",
+    ));
+    let path = tmp("email.json");
+    std::fs::write(&path, text.to_string()).unwrap();
+    let written = stdout(vcon(&[
+        "extract",
+        path.to_str().unwrap(),
+        "--dir",
+        dir.to_str().unwrap(),
+    ]));
+    let first = written.lines().next().unwrap();
+    assert_eq!(
+        std::fs::read_to_string(first).unwrap(),
+        text["dialog"][0]["body"].as_str().unwrap()
+    );
+}
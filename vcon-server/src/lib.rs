@@ -0,0 +1,310 @@
+//! HTTP service exposing a [VconRepository]
+//!
+//! - `POST /vcon` stores a vCon (JSON or CBOR, within the [ParseOptions] of the service, hence
+//!   only marking its `supported_extensions` as `critical`, and after [Vcon::validate]), replacing
+//!   any with the same uuid
+//! - `GET /vcon?tel=..&mailto=..` lists the uuids of the stored vCons, of those with a party
+//!   matching every given address
+//! - `GET /vcon/{uuid}` returns a vCon, in CBOR when accepted and JSON otherwise
+//! - `DELETE /vcon/{uuid}` removes a vCon
+//! - `GET /vcon/{uuid}/dialog/{n}/body` returns the decoded body of a dialog
+//!
+//! Everything is kept on the local file system so that it can be stood up in tests. The repository
+//! is only used on blocking threads.
+
+use axum::body::{Body, Bytes};
+use axum::extract::{DefaultBodyLimit, FromRef, Path, Query, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use std::sync::{Arc, RwLock};
+use vcon_types::{
+    Dialog, ParseOptions, Url, Uuid, Vcon, VconError, VconRepository, VconResult, VconStore,
+};
+
+const CBOR: &str = "application/cbor";
+
+type Repository = Arc<RwLock<VconRepository>>;
+
+#[derive(Clone)]
+struct AppState {
+    repository: Repository,
+    options: Arc<ParseOptions>,
+}
+
+impl FromRef<AppState> for Repository {
+    fn from_ref(state: &AppState) -> Self {
+        state.repository.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<ParseOptions> {
+    fn from_ref(state: &AppState) -> Self {
+        state.options.clone()
+    }
+}
+
+/// Routes of the service over `repository`, vCons being posted parsed within `options`, whose
+/// `max_size` also bounds request bodies
+pub fn router(repository: VconRepository, options: ParseOptions) -> Router {
+    Router::new()
+        .route("/vcon", post(create).get(search))
+        .route("/vcon/{uuid}", get(read).delete(remove))
+        .route("/vcon/{uuid}/dialog/{dialog}/body", get(dialog_body))
+        .layer(DefaultBodyLimit::max(options.max_size))
+        .with_state(AppState {
+            repository: Arc::new(RwLock::new(repository)),
+            options: Arc::new(options),
+        })
+}
+
+/// Serves [router] on `listener` until the process stops
+pub async fn serve(
+    listener: tokio::net::TcpListener,
+    repository: VconRepository,
+    options: ParseOptions,
+) -> std::io::Result<()> {
+    axum::serve(listener, router(repository, options)).await
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ApiError {
+    #[error("{0}")]
+    NotFound(String),
+    #[error("Malformed vCon: {0}")]
+    Malformed(String),
+    #[error(transparent)]
+    TooLarge(VconError),
+    #[error(transparent)]
+    Invalid(VconError),
+    #[error(transparent)]
+    Storage(#[from] VconError),
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = match &self {
+            Self::NotFound(_) => StatusCode::NOT_FOUND,
+            Self::Malformed(_) => StatusCode::BAD_REQUEST,
+            Self::TooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            Self::Invalid(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            Self::Storage(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        let body = serde_json::json!({ "error": self.to_string() });
+        (status, Json(body)).into_response()
+    }
+}
+
+async fn create(
+    State(repository): State<Repository>,
+    State(options): State<Arc<ParseOptions>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<impl IntoResponse, ApiError> {
+    let parsed = if header_contains(&headers, header::CONTENT_TYPE, CBOR) {
+        options
+            .transcode_cbor(&body)
+            .and_then(|json| Vcon::parse_json(&json, &options))
+    } else {
+        Vcon::parse_json(&body, &options)
+    };
+    let vcon = parsed.map_err(|e| match e {
+        VconError::LimitExceeded { .. } => ApiError::TooLarge(e),
+        VconError::InvalidVcon(reason) => ApiError::Malformed(reason),
+        VconError::UnsupportedCriticalExtensions(_) => ApiError::Invalid(e),
+        e => ApiError::Malformed(e.to_string()),
+    })?;
+    vcon.validate().map_err(ApiError::Invalid)?;
+
+    let stored = vcon.clone();
+    blocking(&repository, move |repository| {
+        write(repository).put(&stored)
+    })
+    .await?;
+    let location = format!("/vcon/{}", vcon.uuid);
+    Ok((
+        StatusCode::CREATED,
+        [(header::LOCATION, location)],
+        Json(serde_json::json!({ "uuid": vcon.uuid })),
+    ))
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct PartyQuery {
+    tel: Option<String>,
+    mailto: Option<String>,
+}
+
+async fn search(
+    State(repository): State<Repository>,
+    Query(query): Query<PartyQuery>,
+) -> Json<Vec<Uuid>> {
+    let uuids = blocking(&repository, move |repository| {
+        let repository = read_lock(repository);
+        let mut uuids = match (&query.tel, &query.mailto) {
+            (Some(tel), _) => repository.with_tel(tel),
+            (None, Some(mailto)) => repository.with_mailto(mailto),
+            (None, None) => repository.list(),
+        };
+        if let (Some(_), Some(mailto)) = (&query.tel, &query.mailto) {
+            let with_mailto = repository.with_mailto(mailto);
+            uuids.retain(|uuid| with_mailto.contains(uuid));
+        }
+        uuids
+    })
+    .await;
+    Json(uuids)
+}
+
+async fn read(
+    State(repository): State<Repository>,
+    Path(uuid): Path<Uuid>,
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
+    let vcon = find(&repository, uuid.clone()).await?;
+    if !header_contains(&headers, header::ACCEPT, CBOR) {
+        return Ok(Json(vcon).into_response());
+    }
+    let value = serde_json::to_value(&vcon).map_err(|e| VconError::Storage(e.to_string()))?;
+    let mut body = vec![];
+    ciborium::into_writer(&value, &mut body).map_err(|e| VconError::Storage(e.to_string()))?;
+    Ok(([(header::CONTENT_TYPE, CBOR)], body).into_response())
+}
+
+async fn remove(
+    State(repository): State<Repository>,
+    Path(uuid): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
+    let deleted = uuid.clone();
+    if blocking(&repository, move |repository| {
+        write(repository).delete(&deleted)
+    })
+    .await?
+    {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(ApiError::NotFound(format!("unknown vCon {uuid}")))
+    }
+}
+
+/// Streams the decoded body, written by [vcon_types::Content::write_to] on a blocking thread
+async fn dialog_body(
+    State(repository): State<Repository>,
+    Path((uuid, dialog)): Path<(Uuid, usize)>,
+) -> Result<Response, ApiError> {
+    let vcon = find(&repository, uuid.clone()).await?;
+    let object = vcon
+        .dialog
+        .into_iter()
+        .flatten()
+        .nth(dialog)
+        .ok_or_else(|| ApiError::NotFound(format!("unknown dialog {dialog} in vCon {uuid}")))?;
+    let (Dialog::Recording {
+        content_parameters,
+        content,
+        ..
+    }
+    | Dialog::Text {
+        content_parameters,
+        content,
+        ..
+    }) = object.dialog
+    else {
+        return Err(ApiError::NotFound(format!(
+            "dialog {dialog} in vCon {uuid} has no body"
+        )));
+    };
+    let mime = content_parameters
+        .mime
+        .as_ref()
+        .map(|m| m.as_str().to_string())
+        .unwrap_or_else(|| "application/octet-stream".to_string());
+
+    let (sender, receiver) = tokio::sync::mpsc::channel(BODY_CHUNKS);
+    let store = Locking(repository.clone());
+    tokio::task::spawn_blocking(move || {
+        let mut writer = std::io::BufWriter::with_capacity(BODY_CHUNK, ChunkWriter(sender.clone()));
+        let written = content
+            .write_to(&store, &mut writer)
+            .and_then(|()| Ok(std::io::Write::flush(&mut writer)?));
+        if let Err(e) = written {
+            // ends the response, which is already under way, with an error
+            let _ = sender.blocking_send(Err(std::io::Error::other(e.to_string())));
+        }
+    });
+    let chunks = futures_util::stream::unfold(receiver, |mut receiver| async move {
+        receiver.recv().await.map(|chunk| (chunk, receiver))
+    });
+    Ok(([(header::CONTENT_TYPE, mime)], Body::from_stream(chunks)).into_response())
+}
+
+/// Size of the chunks bodies are streamed by
+const BODY_CHUNK: usize = 64 * 1024;
+/// Chunks written ahead of the client
+const BODY_CHUNKS: usize = 4;
+
+/// Sends what is written to a streamed response body
+struct ChunkWriter(tokio::sync::mpsc::Sender<std::io::Result<Bytes>>);
+
+impl std::io::Write for ChunkWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0
+            .blocking_send(Ok(Bytes::copy_from_slice(buf)))
+            .map_err(|_| std::io::Error::from(std::io::ErrorKind::BrokenPipe))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Repository locked only while loading or fetching, so that a client slowly reading a body does
+/// not hold it
+struct Locking(Repository);
+
+impl VconStore for Locking {
+    fn load(&self, uuid: &Uuid) -> VconResult<Vcon> {
+        read_lock(&self.0).load(uuid)
+    }
+
+    fn fetch(&self, url: &Url) -> VconResult<Vec<u8>> {
+        read_lock(&self.0).fetch(url)
+    }
+}
+
+/// Runs `f` on a blocking thread, as the repository reads and writes files under a lock
+async fn blocking<T: Send + 'static>(
+    repository: &Repository,
+    f: impl FnOnce(&Repository) -> T + Send + 'static,
+) -> T {
+    let repository = repository.clone();
+    tokio::task::spawn_blocking(move || f(&repository))
+        .await
+        .unwrap_or_else(|e| std::panic::resume_unwind(e.into_panic()))
+}
+
+async fn find(repository: &Repository, uuid: Uuid) -> Result<Vcon, ApiError> {
+    blocking(repository, move |repository| {
+        read_lock(repository)
+            .get(&uuid)?
+            .ok_or_else(|| ApiError::NotFound(format!("unknown vCon {uuid}")))
+    })
+    .await
+}
+
+fn header_contains(headers: &HeaderMap, name: header::HeaderName, value: &str) -> bool {
+    headers
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.contains(value))
+}
+
+fn read_lock(repository: &Repository) -> std::sync::RwLockReadGuard<'_, VconRepository> {
+    repository.read().unwrap_or_else(|e| e.into_inner())
+}
+
+fn write(repository: &Repository) -> std::sync::RwLockWriteGuard<'_, VconRepository> {
+    repository.write().unwrap_or_else(|e| e.into_inner())
+}
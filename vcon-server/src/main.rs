@@ -0,0 +1,36 @@
+use clap::Parser;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use vcon_types::{ParseOptions, StorageFormat, VconRepository};
+
+/// Serves a file-system vCon repository over HTTP
+#[derive(Debug, Parser)]
+#[command(name = "vcon-server", version)]
+struct Args {
+    /// Directory of the repository, created when missing
+    #[arg(long, default_value = "vcons")]
+    root: PathBuf,
+    #[arg(long, default_value = "127.0.0.1:8080")]
+    addr: SocketAddr,
+    /// Extension the consumers of the repository implement, vCons marking others as critical
+    /// being rejected
+    #[arg(long = "supported-extension")]
+    supported_extensions: Vec<String>,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::parse();
+    let options = ParseOptions {
+        supported_extensions: args.supported_extensions.into_iter().collect(),
+        ..Default::default()
+    };
+    let repository = VconRepository::open_with(args.root, StorageFormat::Json, options.clone())?;
+    for (path, error) in repository.skipped() {
+        eprintln!("skipped {}: {error}", path.display());
+    }
+    let listener = tokio::net::TcpListener::bind(args.addr).await?;
+    println!("listening on {}", listener.local_addr()?);
+    vcon_server::serve(listener, repository, options).await?;
+    Ok(())
+}
@@ -0,0 +1,205 @@
+use axum::body::Body;
+use axum::http::{header, Request, StatusCode};
+use axum::Router;
+use http_body_util::BodyExt as _;
+use serde_json::json;
+use tower::ServiceExt as _;
+use vcon_types::{ParseOptions, StorageFormat, VconRepository};
+
+const UUID: &str = "01928e10-193e-8231-b9a2-279e0d16bc46";
+
+fn vcon() -> serde_json::Value {
+    json!({
+        "vcon": "0.0.1",
+        "uuid": UUID,
+        "parties": [{ "tel": "+12025550123" }, { "mailto": "agent@example.com" }],
+        "dialog": [{
+            "type": "text",
+            "start": "2023-02-01T00:00:00Z",
+            "parties": [0, 1],
+            "body": "hello, I would like to cancel my subscription",
+            "encoding": "none",
+            "mimetype": "text/plain"
+        }]
+    })
+}
+
+fn app(name: &str) -> (std::path::PathBuf, Router) {
+    app_with(name, ParseOptions::default())
+}
+
+fn app_with(name: &str, options: ParseOptions) -> (std::path::PathBuf, Router) {
+    let root = std::env::temp_dir().join(format!("vcon-server-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    let repository =
+        VconRepository::open_with(&root, StorageFormat::Json, options.clone()).unwrap();
+    (root, vcon_server::router(repository, options))
+}
+
+async fn send(app: &Router, request: Request<Body>) -> (StatusCode, Vec<u8>) {
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (status, body.to_vec())
+}
+
+fn post(body: &serde_json::Value) -> Request<Body> {
+    Request::post("/vcon")
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+fn get(uri: &str) -> Request<Body> {
+    Request::get(uri).body(Body::empty()).unwrap()
+}
+
+#[tokio::test]
+async fn server_should_store_and_serve() {
+    let (root, app) = app("crud");
+    let (status, _) = send(&app, post(&vcon())).await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let (status, body) = send(&app, get(&format!("/vcon/{UUID}"))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        serde_json::from_slice::<serde_json::Value>(&body).unwrap(),
+        vcon()
+    );
+
+    let request = Request::get(format!("/vcon/{UUID}"))
+        .header(header::ACCEPT, "application/cbor")
+        .body(Body::empty())
+        .unwrap();
+    let (_, body) = send(&app, request).await;
+    assert_eq!(
+        ciborium::from_reader::<serde_json::Value, _>(body.as_slice()).unwrap(),
+        vcon()
+    );
+
+    let (status, body) = send(&app, get(&format!("/vcon/{UUID}/dialog/0/body"))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, b"hello, I would like to cancel my subscription");
+    let (status, _) = send(&app, get(&format!("/vcon/{UUID}/dialog/1/body"))).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // bodies are streamed by chunks
+    let long = "hello ".repeat(50_000);
+    let mut with_long_body = vcon();
+    with_long_body["dialog"][0]["body"] = json!(long);
+    assert_eq!(
+        send(&app, post(&with_long_body)).await.0,
+        StatusCode::CREATED
+    );
+    let (status, body) = send(&app, get(&format!("/vcon/{UUID}/dialog/0/body"))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, long.as_bytes());
+    assert_eq!(send(&app, post(&vcon())).await.0, StatusCode::CREATED);
+
+    let (_, body) = send(&app, get("/vcon?tel=%2B12025550123")).await;
+    assert_eq!(
+        serde_json::from_slice::<serde_json::Value>(&body).unwrap(),
+        json!([UUID])
+    );
+    let (_, body) = send(
+        &app,
+        get("/vcon?tel=%2B12025550123&mailto=other@example.com"),
+    )
+    .await;
+    assert_eq!(
+        serde_json::from_slice::<serde_json::Value>(&body).unwrap(),
+        json!([])
+    );
+
+    let delete = Request::delete(format!("/vcon/{UUID}"))
+        .body(Body::empty())
+        .unwrap();
+    assert_eq!(send(&app, delete).await.0, StatusCode::NO_CONTENT);
+    let (status, _) = send(&app, get(&format!("/vcon/{UUID}"))).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+#[tokio::test]
+async fn server_should_reject_invalid_vcons() {
+    let (root, app) = app("invalid");
+    let (status, _) = send(&app, post(&json!({ "vcon": "0.0.1" }))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let mut invalid = vcon();
+    invalid["dialog"][0]["parties"] = json!([0, 2]);
+    let (status, body) = send(&app, post(&invalid)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    let error: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(
+        error["error"],
+        "dialog[0].parties references unknown party 2"
+    );
+    let mut invalid = vcon();
+    invalid["dialog"][0]["duration"] = json!(1e30);
+    let (status, body) = send(&app, post(&invalid)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    let error: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert!(error["error"]
+        .as_str()
+        .unwrap()
+        .starts_with("Invalid vCon: dialog[0].duration of "));
+
+    let mut cbor = vec![];
+    ciborium::into_writer(&vcon(), &mut cbor).unwrap();
+    let request = Request::post("/vcon")
+        .header(header::CONTENT_TYPE, "application/cbor")
+        .body(Body::from(cbor))
+        .unwrap();
+    assert_eq!(send(&app, request).await.0, StatusCode::CREATED);
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+#[tokio::test]
+async fn server_should_accept_supported_critical_extensions() {
+    let mut critical = vcon();
+    critical["vcon"] = json!("0.3.0");
+    critical["critical"] = json!(["consent"]);
+
+    let (root, app) = app("critical");
+    assert_eq!(
+        send(&app, post(&critical)).await.0,
+        StatusCode::UNPROCESSABLE_ENTITY
+    );
+    std::fs::remove_dir_all(root).unwrap();
+
+    let options = ParseOptions {
+        supported_extensions: ["consent".to_string()].into(),
+        ..Default::default()
+    };
+    let (root, app) = app_with("supported", options.clone());
+    assert_eq!(send(&app, post(&critical)).await.0, StatusCode::CREATED);
+    // read back when reopened with the same options
+    let repository = VconRepository::open_with(&root, StorageFormat::Json, options).unwrap();
+    assert_eq!(repository.list().len(), 1);
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+#[tokio::test]
+async fn server_should_accept_bodies_up_to_max_size() {
+    let (root, app) = app("large");
+    let mut large = vcon();
+    large["dialog"][0]["body"] = json!("a".repeat(3 * 1024 * 1024));
+    assert_eq!(send(&app, post(&large)).await.0, StatusCode::CREATED);
+    let (status, body) = send(&app, get(&format!("/vcon/{UUID}/dialog/0/body"))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body.len(), 3 * 1024 * 1024);
+
+    let (_, app) = app_with(
+        "small",
+        ParseOptions {
+            max_size: 1024,
+            ..Default::default()
+        },
+    );
+    assert_eq!(
+        send(&app, post(&large)).await.0,
+        StatusCode::PAYLOAD_TOO_LARGE
+    );
+    std::fs::remove_dir_all(root).unwrap();
+}
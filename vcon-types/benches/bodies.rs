@@ -0,0 +1,69 @@
+//! Cost of inline bodies, kept encoded and decoded on demand, against decoding them when parsed
+//! as they used to be
+//!
+//! Run with `cargo bench --bench bodies`, and `cargo bench --bench memory` for the peak heap usage.
+
+mod common;
+
+use common::{parse_as_before, with_recording_of, INLINE_RECORDING};
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use std::hint::black_box;
+use vcon_types::{Content, Dialog, Vcon};
+
+/// Parses then decodes every body
+fn parse_and_decode(json: &str) -> (Vcon, Vec<Vec<u8>>) {
+    let vcon: Vcon = serde_json::from_str(json).unwrap();
+    let bodies = vcon
+        .dialog
+        .iter()
+        .flatten()
+        .filter_map(|d| match &d.dialog {
+            Dialog::Recording { content, .. } | Dialog::Text { content, .. } => {
+                content.inline_bytes().map(|b| b.into_owned())
+            }
+            _ => None,
+        })
+        .collect();
+    (vcon, bodies)
+}
+
+fn bodies(c: &mut Criterion) {
+    let large = with_recording_of(8 * 1024 * 1024);
+    for (name, json) in [
+        ("inline recording", INLINE_RECORDING),
+        ("8MiB recording", &large),
+    ] {
+        let mut group = c.benchmark_group(name);
+        group.sample_size(20);
+        group.bench_function("parse", |b| {
+            b.iter(|| serde_json::from_str::<Vcon>(black_box(json)).unwrap())
+        });
+        group.bench_function("parse as before", |b| {
+            b.iter(|| parse_as_before(black_box(json)))
+        });
+        group.bench_function("parse and decode", |b| {
+            b.iter(|| parse_and_decode(black_box(json)))
+        });
+
+        let vcon: Vcon = serde_json::from_str(json).unwrap();
+        group.bench_function("serialize", |b| {
+            b.iter(|| serde_json::to_string(black_box(&vcon)).unwrap())
+        });
+        group.bench_function("clone", |b| {
+            b.iter_batched(|| (), |_| black_box(&vcon).clone(), BatchSize::SmallInput)
+        });
+        let Dialog::Recording { content, .. } = &vcon.dialog.as_ref().unwrap()[0].dialog else {
+            unreachable!()
+        };
+        let Content::Inline(inline) = content else {
+            unreachable!()
+        };
+        group.bench_function("stream decoded body", |b| {
+            b.iter(|| inline.write_to(std::io::sink()).unwrap())
+        });
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bodies);
+criterion_main!(benches);
@@ -0,0 +1,35 @@
+//! Inputs shared by the benches, and the former way of parsing bodies to compare against
+
+use base64::Engine as _;
+use vcon_types::{Base64Body, InlineContent};
+
+pub const INLINE_RECORDING: &str =
+    include_str!("../../examples/json/two-party-call-with-inline-recording.json");
+
+/// The example with its recording repeated to reach `size` decoded bytes
+pub fn with_recording_of(size: usize) -> String {
+    let mut vcon: serde_json::Value = serde_json::from_str(INLINE_RECORDING).unwrap();
+    let body = Base64Body::from_encoded(vcon["dialog"][0]["body"].as_str().unwrap().to_string())
+        .unwrap()
+        .decode();
+    let recording = body.iter().copied().cycle().take(size).collect::<Vec<_>>();
+    vcon["dialog"][0]["body"] = Base64Body::encode(recording).as_str().into();
+    vcon.to_string()
+}
+
+/// Dialogs parsed as bodies used to be: their fields read as [serde_json::Value]s, then base64url
+/// bodies decoded into a `Vec<u8>`
+pub fn parse_as_before(json: &str) -> Vec<(serde_json::Value, Option<Vec<u8>>)> {
+    let mut vcon: serde_json::Value = serde_json::from_str(json).unwrap();
+    let dialogs = std::mem::take(vcon["dialog"].as_array_mut().unwrap());
+    dialogs
+        .into_iter()
+        .map(|mut dialog| {
+            let body = (dialog["encoding"] == "base64url")
+                .then(|| dialog.as_object_mut()?.remove("body"))
+                .flatten()
+                .map(|body| InlineContent::B64.decode(body.as_str().unwrap()).unwrap());
+            (dialog, body)
+        })
+        .collect()
+}
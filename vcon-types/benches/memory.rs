@@ -0,0 +1,32 @@
+//! Peak heap usage of parsing inline bodies, kept encoded, against decoding them when parsed as
+//! they used to be
+//!
+//! Run with `cargo bench --bench memory`.
+
+mod common;
+
+use common::{parse_as_before, with_recording_of, INLINE_RECORDING};
+use std::hint::black_box;
+use vcon_types::Vcon;
+
+#[global_allocator]
+static ALLOCATOR: dhat::Alloc = dhat::Alloc;
+
+/// Most bytes allocated at once while running `f`
+fn peak_heap(f: impl FnOnce()) -> usize {
+    let _profiler = dhat::Profiler::builder().testing().build();
+    f();
+    dhat::HeapStats::get().max_bytes
+}
+
+fn main() {
+    let large = with_recording_of(8 * 1024 * 1024);
+    for (name, json) in [
+        ("inline recording", INLINE_RECORDING),
+        ("8MiB recording", &large),
+    ] {
+        let parsed = peak_heap(|| drop(black_box(serde_json::from_str::<Vcon>(json).unwrap())));
+        let before = peak_heap(|| drop(black_box(parse_as_before(json))));
+        println!("{name}: peak heap of {parsed} bytes when parsing, {before} bytes as before");
+    }
+}
@@ -0,0 +1,143 @@
+use crate::{
+    Date, OrEmpty, RedactedReference, Uuid, Vcon, VconError, VconReference, VconResolver,
+    VconResult,
+};
+use std::collections::HashSet;
+
+/// Amendments
+///
+/// An amended vCon is a copy of a prior version to which data (e.g. late analysis) is appended.
+/// The prior version is left untouched and referenced through `amended` so that its signature
+/// stays valid.
+///
+/// See https://ietf-wg-vcon.github.io/draft-ietf-vcon-vcon-container/draft-ietf-vcon-vcon-container.html#name-amended
+impl Vcon {
+    /// Creates a new version of `original` referencing it through `amended`.
+    ///
+    /// The new vCon gets a fresh [Uuid] and `updated_at` is set to now. Data should then only be
+    /// appended to it for the amendment to stay valid (see [Vcon::verify_amendments]).
+    pub fn amend(original: &Vcon) -> Vcon {
+        let updated_at = Date::now();
+
+        let mut seed = original.uuid.as_bytes().to_vec();
+        seed.extend_from_slice(&updated_at.unix_timestamp_nanos().to_be_bytes());
+
+        let mut amended = original.clone();
+        amended.uuid = Uuid::derive(&seed);
+        amended.updated_at = Some(updated_at);
+        amended.amended = Some(OrEmpty::Some(RedactedReference {
+            typ: None,
+            vcon_reference: VconReference::Uuid {
+                uuid: original.uuid.clone(),
+            },
+            #[cfg(json)]
+            extension_object: Default::default(),
+        }));
+        amended
+    }
+
+    /// Prior version this vCon amends, if any
+    pub fn amended_reference(&self) -> Option<&VconReference> {
+        match &self.amended {
+            Some(OrEmpty::Some(RedactedReference { vcon_reference, .. })) => Some(vcon_reference),
+            _ => None,
+        }
+    }
+
+    /// Walks the chain of amendments down to the original vCon, verifying that each version only
+    /// appends data to the one it amends.
+    ///
+    /// Returns the prior versions, most recent first.
+    pub fn verify_amendments(&self, resolver: &impl VconResolver) -> VconResult<Vec<Vcon>> {
+        let mut chain = Vec::new();
+        let mut seen = HashSet::from([self.uuid.clone()]);
+        let mut current = self;
+
+        while let Some(reference) = current.amended_reference() {
+            let prior = resolver.resolve(reference)?;
+            if !seen.insert(prior.uuid.clone()) {
+                return Err(VconError::AmendmentCycle(prior.uuid));
+            }
+            current.verify_amends(&prior)?;
+            chain.push(prior);
+            current = chain.last().expect("just pushed");
+        }
+        Ok(chain)
+    }
+
+    /// Verifies this vCon only adds data to `prior`
+    fn verify_amends(&self, prior: &Vcon) -> VconResult<()> {
+        let invalid = |field| VconError::InvalidAmendment {
+            original: prior.uuid.clone(),
+            amended: self.uuid.clone(),
+            field,
+        };
+
+        fn kept<T: PartialEq>(prior: &Option<T>, current: &Option<T>) -> bool {
+            prior.is_none() || prior == current
+        }
+
+        fn appended<T: PartialEq>(prior: &Option<Vec<T>>, current: &Option<Vec<T>>) -> bool {
+            let prior = prior.as_deref().unwrap_or_default();
+            let current = current.as_deref().unwrap_or_default();
+            current.starts_with(prior)
+        }
+
+        if matches!(self.amended_reference(), Some(VconReference::Uuid { uuid }) if *uuid != prior.uuid)
+        {
+            return Err(invalid("amended"));
+        }
+        if !kept(&prior.subject, &self.subject) {
+            return Err(invalid("subject"));
+        }
+        if !kept(&prior.created_at, &self.created_at) {
+            return Err(invalid("created_at"));
+        }
+        if prior
+            .updated_at
+            .as_ref()
+            .zip(self.updated_at.as_ref())
+            .is_some_and(|(p, c)| c < p)
+        {
+            return Err(invalid("updated_at"));
+        }
+        if !kept(&prior.redacted, &self.redacted) {
+            return Err(invalid("redacted"));
+        }
+        if !appended(&prior.extensions, &self.extensions) {
+            return Err(invalid("extensions"));
+        }
+        if !appended(&prior.critical, &self.critical) {
+            return Err(invalid("critical"));
+        }
+        if !appended(&prior.group, &self.group) {
+            return Err(invalid("group"));
+        }
+        if !appended(&prior.parties, &self.parties) {
+            return Err(invalid("parties"));
+        }
+        if !appended(&prior.dialog, &self.dialog) {
+            return Err(invalid("dialog"));
+        }
+        if !appended(&prior.attachments, &self.attachments) {
+            return Err(invalid("attachments"));
+        }
+        if !appended(&prior.analysis, &self.analysis) {
+            return Err(invalid("analysis"));
+        }
+        #[cfg(feature = "json")]
+        {
+            let extension_kept = match (&prior.extension_object.0, &self.extension_object.0) {
+                (serde_json::Value::Object(prior), serde_json::Value::Object(current)) => prior
+                    .iter()
+                    .all(|(k, v)| current.get(k).is_some_and(|c| c == v)),
+                (serde_json::Value::Null, _) => true,
+                (prior, current) => prior == current,
+            };
+            if !extension_kept {
+                return Err(invalid("extension_object"));
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,336 @@
+use crate::{
+    analysis::{Transcript, TranscriptSegment, TranscriptWord},
+    Party, PartyIndex, VconError, VconResult,
+};
+use std::fmt::Write as _;
+
+/// Caption and script exports
+///
+/// One cue is emitted per segment. Speakers are displayed using the name of the [Party] at the
+/// index of their label.
+impl Transcript {
+    /// WebVTT captions with voice spans e.g. `<v Alice>Hello.</v>`
+    ///
+    /// See https://www.w3.org/TR/webvtt1/
+    pub fn to_webvtt(&self, parties: &[Party]) -> String {
+        let escape = |s: &str| {
+            s.replace('&', "&amp;")
+                .replace('<', "&lt;")
+                .replace('>', "&gt;")
+        };
+        let mut vtt = String::from("WEBVTT\n");
+        for (i, segment) in self.segments.iter().enumerate() {
+            let _ = write!(
+                vtt,
+                "\n{}\n{} --> {}\n",
+                i + 1,
+                timestamp(segment.start, '.'),
+                timestamp(segment.end, '.')
+            );
+            match segment.speaker {
+                Some(speaker) => {
+                    let name = escape(&speaker_name(parties, speaker));
+                    let _ = writeln!(vtt, "<v {name}>{}</v>", escape(segment.text.trim()));
+                }
+                None => {
+                    let _ = writeln!(vtt, "{}", escape(segment.text.trim()));
+                }
+            }
+        }
+        vtt
+    }
+
+    /// SubRip captions, the speaker name prefixing the text
+    pub fn to_srt(&self, parties: &[Party]) -> String {
+        let mut srt = String::new();
+        for (i, segment) in self.segments.iter().enumerate() {
+            if i > 0 {
+                srt.push('\n');
+            }
+            let _ = writeln!(
+                srt,
+                "{}\n{} --> {}",
+                i + 1,
+                timestamp(segment.start, ','),
+                timestamp(segment.end, ',')
+            );
+            if let Some(speaker) = segment.speaker {
+                let _ = write!(srt, "{}: ", speaker_name(parties, speaker));
+            }
+            let _ = writeln!(srt, "{}", segment.text.trim());
+        }
+        srt
+    }
+
+    /// Plain text dialogue, one `Speaker: text` line per turn
+    pub fn to_script(&self, parties: &[Party]) -> String {
+        let mut turns: Vec<(Option<u32>, Vec<&str>)> = vec![];
+        for segment in &self.segments {
+            match turns.last_mut() {
+                Some((speaker, texts)) if *speaker == segment.speaker => {
+                    texts.push(segment.text.trim())
+                }
+                _ => turns.push((segment.speaker, vec![segment.text.trim()])),
+            }
+        }
+        turns
+            .into_iter()
+            .map(|(speaker, texts)| match speaker {
+                Some(speaker) => {
+                    format!("{}: {}\n", speaker_name(parties, speaker), texts.join(" "))
+                }
+                None => format!("{}\n", texts.join(" ")),
+            })
+            .collect()
+    }
+}
+
+/// Caption imports
+///
+/// Each cue becomes a segment (or several when the voice changes within it), its words being
+/// timed by interpolation over the cue. Voice names are matched against [Party] names to get the
+/// speaker.
+///
+/// Only the `language` (from the WebVTT header) and the `duration` (end of the last cue) of the
+/// [Transcript] are filled. Cue identifiers, cue settings and the captions format are not kept,
+/// nor are voice names matching no party: their segments have no speaker.
+impl Transcript {
+    /// From WebVTT captions, voices being read from `<v Name>` spans
+    ///
+    /// See https://www.w3.org/TR/webvtt1/
+    pub fn from_webvtt(vtt: &str, parties: &[Party]) -> VconResult<Self> {
+        let mut lines = vtt.lines().enumerate().map(|(i, l)| (i + 1, l)).peekable();
+        let header = lines.next().map(|(_, l)| l.trim_start_matches('\u{feff}'));
+        if !header.is_some_and(|h| h.starts_with("WEBVTT")) {
+            return Err(invalid(1, "missing WEBVTT header"));
+        }
+        let mut transcript = Self::default();
+        while let Some((_, line)) = lines.next_if(|(_, l)| !l.trim().is_empty()) {
+            if let Some(language) = line.strip_prefix("Language:") {
+                transcript.language = Some(language.trim().to_string());
+            }
+        }
+
+        for block in blocks(lines) {
+            let first = block[0].1;
+            if first.starts_with("NOTE") || first == "STYLE" || first == "REGION" {
+                continue;
+            }
+            let Some(timing) = block.iter().position(|(_, l)| l.contains("-->")) else {
+                return Err(invalid(block[0].0, "missing cue timings"));
+            };
+            let (start, end) = timings(block[timing], '.')?;
+            let payload = block[timing + 1..].iter().map(|(_, l)| *l);
+            transcript.push_cue(start, end, voices(payload, false), parties);
+        }
+        Ok(transcript)
+    }
+
+    /// From SubRip captions, voices being read from a `Name: ` prefix matching a party name or
+    /// from `<v Name>` spans
+    pub fn from_srt(srt: &str, parties: &[Party]) -> VconResult<Self> {
+        let lines = srt
+            .trim_start_matches('\u{feff}')
+            .lines()
+            .enumerate()
+            .map(|(i, l)| (i + 1, l));
+
+        let mut transcript = Self::default();
+        for block in blocks(lines) {
+            let Some(timing) = block.iter().position(|(_, l)| l.contains("-->")) else {
+                return Err(invalid(block[0].0, "missing cue timings"));
+            };
+            let (start, end) = timings(block[timing], ',')?;
+            let payload = block[timing + 1..].iter().map(|(_, l)| *l);
+            let mut runs = voices(payload, true);
+            for (voice, text) in &mut runs {
+                if voice.is_none() {
+                    if let Some((name, rest)) = text.split_once(": ") {
+                        if party_index(parties, name).is_some() {
+                            (*voice, *text) = (Some(name.to_string()), rest.to_string());
+                        }
+                    }
+                }
+            }
+            transcript.push_cue(start, end, runs, parties);
+        }
+        Ok(transcript)
+    }
+
+    fn push_cue(
+        &mut self,
+        start: f64,
+        end: f64,
+        runs: Vec<(Option<String>, String)>,
+        parties: &[Party],
+    ) {
+        // time is spread according to the length of the text
+        let total = runs
+            .iter()
+            .map(|(_, t)| t.chars().count() + 1)
+            .sum::<usize>();
+        let per_char = (end - start) / total.max(1) as f64;
+
+        let mut cursor = start;
+        for (voice, text) in runs {
+            let speaker = voice.and_then(|v| party_index(parties, &v));
+            let mut segment = TranscriptSegment {
+                start: cursor,
+                speaker,
+                ..Default::default()
+            };
+            for word in text.split_whitespace() {
+                let word_end = cursor + per_char * word.chars().count() as f64;
+                segment.words.push(TranscriptWord {
+                    text: word.to_string(),
+                    start: cursor,
+                    end: word_end,
+                    speaker,
+                    confidence: None,
+                });
+                cursor = word_end + per_char;
+            }
+            segment.end = cursor.min(end);
+            segment.text = text;
+            self.segments.push(segment);
+        }
+        self.duration = Some(self.duration.unwrap_or_default().max(end));
+    }
+}
+
+fn invalid(line: usize, reason: &str) -> VconError {
+    VconError::InvalidCaptions {
+        line,
+        reason: reason.to_string(),
+    }
+}
+
+/// Groups of consecutive non blank lines
+fn blocks<'a>(lines: impl Iterator<Item = (usize, &'a str)>) -> Vec<Vec<(usize, &'a str)>> {
+    let mut blocks = vec![vec![]];
+    for (i, line) in lines {
+        match (line.trim().is_empty(), blocks.last_mut()) {
+            (true, Some(last)) if !last.is_empty() => blocks.push(vec![]),
+            (true, _) => {}
+            (false, Some(last)) => last.push((i, line.trim_end())),
+            (false, None) => unreachable!(),
+        }
+    }
+    blocks.retain(|b| !b.is_empty());
+    blocks
+}
+
+/// `start --> end [settings]`
+fn timings((line, timing): (usize, &str), separator: char) -> VconResult<(f64, f64)> {
+    let mut parts = timing.split("-->");
+    let (Some(start), Some(end)) = (parts.next(), parts.next()) else {
+        return Err(invalid(line, "invalid cue timings"));
+    };
+    let end = end.split_whitespace().next().unwrap_or_default();
+    let start =
+        timestamp_seconds(start.trim(), separator).ok_or_else(|| invalid(line, "invalid start"))?;
+    let end = timestamp_seconds(end, separator).ok_or_else(|| invalid(line, "invalid end"))?;
+    Ok((start, end))
+}
+
+/// `[HH:]MM:SS<separator>mmm`, None when it does not fit in a `u64` of seconds
+fn timestamp_seconds(timestamp: &str, separator: char) -> Option<f64> {
+    let (time, millis) = timestamp.split_once(separator)?;
+    let seconds = time.split(':').try_fold(0u64, |acc, part| {
+        acc.checked_mul(60)?.checked_add(part.parse().ok()?)
+    })?;
+    let millis = Some(millis).filter(|m| m.len() == 3)?.parse::<u64>().ok()?;
+    Some(seconds as f64 + millis as f64 / 1000.0)
+}
+
+/// Splits a cue payload into `(voice, text)` runs, stripping markup. In SRT each line can hold a
+/// different speaker.
+fn voices<'a>(
+    payload: impl Iterator<Item = &'a str>,
+    split_lines: bool,
+) -> Vec<(Option<String>, String)> {
+    let mut runs: Vec<(Option<String>, String)> = vec![];
+    let mut voice = None;
+    let mut push = |voice: &Option<String>, text: &str, new_run: bool| {
+        let text = text
+            .replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .replace("&nbsp;", " ")
+            .replace("&amp;", "&");
+        match runs.last_mut() {
+            Some((v, t)) if v == voice && !new_run => {
+                if !t.is_empty() && !text.is_empty() && !t.ends_with(' ') && !text.starts_with(' ')
+                {
+                    t.push(' ');
+                }
+                t.push_str(&text);
+            }
+            _ if text.trim().is_empty() => {}
+            _ => runs.push((voice.clone(), text)),
+        }
+    };
+
+    for line in payload {
+        let mut rest = line;
+        let mut new_run = split_lines;
+        while let Some(open) = rest.find('<') {
+            push(&voice, &rest[..open], new_run);
+            new_run = false;
+            let Some(close) = rest[open..].find('>') else {
+                rest = "";
+                break;
+            };
+            let tag = &rest[open + 1..open + close];
+            if let Some(annotation) = tag.strip_prefix('v').filter(|t| t.starts_with([' ', '.'])) {
+                // `<v.class Name>`
+                let name = annotation
+                    .split_once(' ')
+                    .map(|(_, n)| n)
+                    .unwrap_or_default();
+                voice = Some(name.trim().to_string()).filter(|n| !n.is_empty());
+                new_run = true;
+            } else if tag == "/v" {
+                voice = None;
+                new_run = true;
+            }
+            rest = &rest[open + close + 1..];
+        }
+        push(&voice, rest, new_run);
+    }
+    for (_, text) in &mut runs {
+        *text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    }
+    runs.retain(|(_, text)| !text.is_empty());
+    runs
+}
+
+fn party_index(parties: &[Party], name: &str) -> Option<PartyIndex> {
+    parties
+        .iter()
+        .position(|p| {
+            p.name
+                .as_deref()
+                .is_some_and(|n| n.eq_ignore_ascii_case(name.trim()))
+        })
+        .map(|i| i as PartyIndex)
+}
+
+fn speaker_name(parties: &[Party], speaker: u32) -> String {
+    parties
+        .get(speaker as usize)
+        .and_then(Party::display_name)
+        .map(ToString::to_string)
+        .unwrap_or_else(|| format!("Speaker {speaker}"))
+}
+
+/// `HH:MM:SS.mmm` with the given fraction separator
+fn timestamp(seconds: f64, separator: char) -> String {
+    let millis = (seconds.max(0.0) * 1000.0).round() as u64;
+    format!(
+        "{:02}:{:02}:{:02}{separator}{:03}",
+        millis / 3_600_000,
+        millis / 60_000 % 60,
+        millis / 1000 % 60,
+        millis % 1000
+    )
+}
@@ -0,0 +1,142 @@
+//! Deepgram's transcription response
+//!
+//! See https://developers.deepgram.com/reference/listen-file
+
+use crate::{analysis::AnalysisDecoder, dialog::Duration, Date, Uuid, VconError, VconResult};
+use derive_more::{From, Into};
+use std::collections::HashMap;
+
+/// Decodes `transcript` analyses from the `deepgram` vendor into a [PrerecordedResponse]
+pub struct Deepgram;
+
+impl AnalysisDecoder for Deepgram {
+    const TYPE: &'static str = "transcript";
+    const VENDOR: Option<&'static str> = Some("deepgram");
+    type Output = PrerecordedResponse;
+
+    fn decode(body: &[u8]) -> VconResult<Self::Output> {
+        #[cfg(feature = "json")]
+        {
+            serde_json::from_slice(body).map_err(|e| VconError::InvalidAnalysis(e.to_string()))
+        }
+        #[cfg(not(feature = "json"))]
+        {
+            let _ = body;
+            Err(VconError::InvalidAnalysis(
+                "decoding Deepgram responses requires the 'json' feature".to_string(),
+            ))
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, From, Into)]
+#[cfg_attr(ser, derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "builder", derive(derive_builder::Builder))]
+pub struct PrerecordedResponse {
+    pub metadata: Metadata,
+    pub results: Results,
+}
+
+#[derive(Debug, Clone, PartialEq, From, Into)]
+#[cfg_attr(ser, derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "builder", derive(derive_builder::Builder))]
+pub struct Metadata {
+    pub transaction_key: String,
+    pub request_id: Uuid,
+    pub sha256: String, // TODO: type depending alg
+    pub created: Date,
+    pub duration: Duration,
+    pub channels: u32,
+    pub models: Vec<Uuid>,
+    pub model_info: HashMap<Uuid, ModelInfo>,
+}
+
+impl std::hash::Hash for Metadata {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.transaction_key.hash(state);
+        self.request_id.hash(state);
+        self.sha256.hash(state);
+        self.created.hash(state);
+        self.channels.hash(state);
+        self.models.hash(state);
+        // TODO: handle duration & model info
+        todo!()
+    }
+}
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq, From, Into)]
+#[cfg_attr(ser, derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "builder", derive(derive_builder::Builder))]
+pub struct ModelInfo {
+    pub name: String,
+    pub version: String,
+    pub arch: String,
+}
+
+#[derive(Debug, Clone, PartialEq, From, Into)]
+#[cfg_attr(ser, derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "builder", derive(derive_builder::Builder))]
+pub struct Results {
+    pub channels: Vec<Channel>,
+}
+
+#[derive(Debug, Clone, PartialEq, From, Into)]
+#[cfg_attr(ser, derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "builder", derive(derive_builder::Builder))]
+pub struct Channel {
+    pub alternatives: Vec<Alternative>,
+}
+
+#[derive(Debug, Clone, PartialEq, From, Into)]
+#[cfg_attr(ser, derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "builder", derive(derive_builder::Builder))]
+pub struct Alternative {
+    pub transcript: String,
+    // #[cfg_attr(ser, serde(with = "crate::serde_float"))]
+    pub confidence: f32,
+    pub words: Vec<Word>,
+    #[cfg_attr(ser, serde(rename = "paragraphs"))]
+    pub paragraph: Paragraph,
+}
+
+#[derive(Debug, Clone, PartialEq, From, Into)]
+#[cfg_attr(ser, derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "builder", derive(derive_builder::Builder))]
+pub struct Word {
+    pub word: String,
+    pub start: f32,
+    pub end: f32,
+    pub confidence: f32,
+    /// Diarization label, see [crate::SpeakerMapping]
+    pub speaker: u32,
+    pub speaker_confidence: f32,
+    pub punctuated_word: String,
+}
+
+#[derive(Debug, Clone, PartialEq, From, Into)]
+#[cfg_attr(ser, derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "builder", derive(derive_builder::Builder))]
+pub struct Paragraph {
+    pub transcript: String,
+    pub paragraphs: Vec<InnerParagraph>,
+}
+
+#[derive(Debug, Clone, PartialEq, From, Into)]
+#[cfg_attr(ser, derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "builder", derive(derive_builder::Builder))]
+pub struct InnerParagraph {
+    pub sentences: Vec<Sentence>,
+    pub speaker: u32,
+    pub num_words: u32,
+    pub start: f32,
+    pub end: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, From, Into)]
+#[cfg_attr(ser, derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "builder", derive(derive_builder::Builder))]
+pub struct Sentence {
+    pub text: String,
+    pub start: f32,
+    pub end: f32,
+}
@@ -0,0 +1,227 @@
+use crate::{
+    analysis::{AnalysisDecoder, Transcript},
+    Dialog, DialogIndex, DialogObject, PartyIndex, Vcon, VconResult,
+};
+use std::collections::BTreeMap;
+
+/// Talk time, silence, overlaps and turn taking of a recording dialog, derived from a transcript
+///
+/// Times are in seconds. Transcript speakers are expected to be party indexes (see
+/// [crate::SpeakerMapping]), words without a speaker being ignored.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(ser, derive(serde::Serialize, serde::Deserialize))]
+pub struct ConversationMetrics {
+    /// Index of the recording dialog
+    pub dialog: DialogIndex,
+    /// Length of the recording, or of the transcript when the dialog has no `duration`
+    pub duration: f64,
+    /// Time nobody talks
+    pub silence: f64,
+    /// Time at least two parties talk at once
+    pub overlap: f64,
+    /// Number of times the floor changed hands, the first speaker included
+    pub turns: usize,
+    /// Every party of the dialog, plus the ones only found in the transcript
+    pub parties: BTreeMap<PartyIndex, PartyMetrics>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(ser, derive(serde::Serialize, serde::Deserialize))]
+pub struct PartyMetrics {
+    /// Time this party talks, overlaps included
+    pub talk_time: f64,
+    pub words: usize,
+    /// Words per minute of talk time
+    pub words_per_minute: f64,
+    pub turns: usize,
+    /// Longest turn of this party
+    pub longest_monologue: f64,
+    /// Turns started while another party was still talking
+    pub interruptions: usize,
+}
+
+/// Speech of a party: a word, or a whole segment when it has no word timings
+struct Span {
+    party: PartyIndex,
+    start: f64,
+    end: f64,
+    words: usize,
+}
+
+impl ConversationMetrics {
+    /// `schema` of analyses holding serialized [ConversationMetrics]
+    pub const SCHEMA: &'static str = "vcon-types/conversation-metrics";
+
+    /// Metrics of the dialog at index `dialog` from its transcript
+    pub fn new(dialog: DialogIndex, object: &DialogObject, transcript: &Transcript) -> Self {
+        let dialog_parties = object
+            .dialog
+            .parties()
+            .map(|p| p.indexes())
+            .unwrap_or_default();
+
+        let mut spans = vec![];
+        for segment in &transcript.segments {
+            if segment.words.is_empty() {
+                spans.extend(segment.speaker.map(|speaker| Span {
+                    party: speaker,
+                    start: segment.start,
+                    end: segment.end,
+                    words: segment.text.split_whitespace().count(),
+                }));
+            }
+            spans.extend(segment.words.iter().filter_map(|w| {
+                Some(Span {
+                    party: w.speaker.or(segment.speaker)?,
+                    start: w.start,
+                    end: w.end.max(w.start),
+                    words: 1,
+                })
+            }));
+        }
+        spans.sort_by(|a, b| a.start.total_cmp(&b.start));
+
+        let duration = match &object.dialog {
+            Dialog::Recording {
+                duration: Some(duration),
+                ..
+            } => duration.as_secs_f64(),
+            _ => transcript
+                .duration
+                .unwrap_or_else(|| spans.iter().map(|s| s.end).fold(0.0, f64::max)),
+        };
+
+        let mut metrics = Self {
+            dialog,
+            duration,
+            ..Default::default()
+        };
+        for party in &dialog_parties {
+            metrics.parties.insert(*party, PartyMetrics::default());
+        }
+
+        // turn taking
+        let mut talking_until = BTreeMap::<PartyIndex, f64>::new();
+        let mut turn: Option<(PartyIndex, f64, f64)> = None;
+        for span in &spans {
+            match &mut turn {
+                Some((party, _, end)) if *party == span.party => *end = end.max(span.end),
+                _ => {
+                    if let Some(previous) = turn.replace((span.party, span.start, span.end)) {
+                        metrics.end_turn(previous);
+                    }
+                    let interrupts = talking_until
+                        .iter()
+                        .any(|(party, until)| *party != span.party && *until > span.start);
+                    let current = metrics.parties.entry(span.party).or_default();
+                    current.turns += 1;
+                    current.interruptions += interrupts as usize;
+                    metrics.turns += 1;
+                }
+            }
+            metrics.parties.entry(span.party).or_default().words += span.words;
+            let until = talking_until.entry(span.party).or_default();
+            *until = until.max(span.end);
+        }
+        if let Some(last) = turn {
+            metrics.end_turn(last);
+        }
+
+        // talk time, silence and overlaps by sweeping over the speech of every party
+        let mut changes = vec![];
+        for (party, party_metrics) in metrics.parties.iter_mut() {
+            let speech = merge(spans.iter().filter(|s| s.party == *party));
+            party_metrics.talk_time = speech.iter().map(|(start, end)| end - start).sum();
+            if party_metrics.talk_time > 0.0 {
+                party_metrics.words_per_minute =
+                    party_metrics.words as f64 * 60.0 / party_metrics.talk_time;
+            }
+            changes.extend(
+                speech
+                    .into_iter()
+                    .flat_map(|(start, end)| [(start, 1), (end, -1)]),
+            );
+        }
+        changes.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+
+        let (mut talking, mut last, mut speech) = (0, 0.0, 0.0);
+        for (time, change) in changes {
+            let elapsed = time - last;
+            if talking > 0 {
+                speech += elapsed;
+            }
+            if talking > 1 {
+                metrics.overlap += elapsed;
+            }
+            (talking, last) = (talking + change, time);
+        }
+        metrics.silence = (metrics.duration - speech).max(0.0);
+        metrics
+    }
+
+    fn end_turn(&mut self, (party, start, end): (PartyIndex, f64, f64)) {
+        let metrics = self.parties.entry(party).or_default();
+        metrics.longest_monologue = metrics.longest_monologue.max(end - start);
+    }
+}
+
+/// Union of the time intervals of `spans`, sorted by start
+fn merge<'a>(spans: impl Iterator<Item = &'a Span>) -> Vec<(f64, f64)> {
+    let mut merged: Vec<(f64, f64)> = vec![];
+    for span in spans {
+        match merged.last_mut() {
+            Some((_, end)) if span.start <= *end => *end = end.max(span.end),
+            _ => merged.push((span.start, span.end)),
+        }
+    }
+    merged
+}
+
+#[cfg(feature = "json")]
+impl ConversationMetrics {
+    /// Wraps these metrics into a `conversation-metrics` analysis of their dialog (see
+    /// [ConversationMetrics::SCHEMA])
+    pub fn to_analysis(&self) -> VconResult<crate::Analysis> {
+        super::json_analysis(Self::TYPE, Self::SCHEMA, self.dialog, self)
+    }
+}
+
+/// Analyses holding [ConversationMetrics] as is (see [ConversationMetrics::SCHEMA])
+impl AnalysisDecoder for ConversationMetrics {
+    const TYPE: &'static str = "conversation-metrics";
+    const SCHEMA: Option<&'static str> = Some(ConversationMetrics::SCHEMA);
+    type Output = ConversationMetrics;
+
+    fn decode(body: &[u8]) -> VconResult<Self::Output> {
+        super::json_body(body)
+    }
+}
+
+impl Vcon {
+    /// [ConversationMetrics] of every recording dialog having a transcript analysis (see
+    /// [Vcon::transcript]), the first transcript of a dialog being used.
+    ///
+    /// Fails when a transcript references a dialog which does not exist or cannot be decoded.
+    pub fn conversation_metrics(&self) -> VconResult<Vec<ConversationMetrics>> {
+        let dialogs = self.dialog.as_deref().unwrap_or_default();
+        let mut metrics: Vec<ConversationMetrics> = vec![];
+        for (index, analysis) in self.analysis.iter().flatten().enumerate() {
+            if metrics.iter().any(|m| m.dialog == analysis.dialog) {
+                continue;
+            }
+            let Some(transcript) = self.transcript(index)? else {
+                continue;
+            };
+            let object = &dialogs[analysis.dialog as usize];
+            if matches!(object.dialog, Dialog::Recording { .. }) {
+                metrics.push(ConversationMetrics::new(
+                    analysis.dialog,
+                    object,
+                    &transcript,
+                ));
+            }
+        }
+        metrics.sort_by_key(|m| m.dialog);
+        Ok(metrics)
+    }
+}
@@ -1,16 +1,34 @@
-use crate::{body::BodyEncoding, dialog::Duration, Date, DialogIndex, PartyIndex, Uuid};
+mod captions;
+pub mod deepgram;
+mod metrics;
+mod speakers;
+mod transcript;
+
+pub use metrics::{ConversationMetrics, PartyMetrics};
+pub use speakers::SpeakerMapping;
+pub use transcript::{Transcript, TranscriptSegment, TranscriptWord};
+
+use crate::{Content, ContentParameters, DialogIndex, VconError, VconResult, VconStore};
 use derive_more::{From, Into};
-use std::collections::HashMap;
+use std::any::Any;
 
-#[derive(Debug, Clone, PartialEq, From, Into)]
+/// Analysis
+///
+/// The body is kept as raw [Content] whatever its `type`, `vendor` or `schema`. Use an
+/// [AnalysisDecoder] or an [AnalysisRegistry] to get a typed representation of it.
+///
+/// See https://ietf-wg-vcon.github.io/draft-ietf-vcon-vcon-container/draft-ietf-vcon-vcon-container.html#name-analysis-object
+#[derive(Debug, Clone, Hash, Eq, PartialEq, From, Into)]
 #[cfg_attr(ser, derive(serde::Serialize, serde::Deserialize))]
 #[cfg_attr(feature = "builder", derive(derive_builder::Builder))]
 pub struct Analysis {
     #[cfg_attr(ser, serde(rename = "type"))]
     pub typ: String,
     pub dialog: DialogIndex,
-    pub body: AnalysisBody,
-    pub encoding: BodyEncoding,
+    #[cfg_attr(ser, serde(flatten))]
+    pub content_parameters: ContentParameters,
+    #[cfg_attr(ser, serde(flatten))]
+    pub content: Content,
     #[cfg_attr(ser, serde(skip_serializing_if = "Option::is_none"))]
     pub vendor: Option<String>,
     #[cfg_attr(ser, serde(skip_serializing_if = "Option::is_none"))]
@@ -22,113 +40,178 @@ pub struct Analysis {
     pub extension_object: crate::JsonAnyValue,
 }
 
-#[derive(Debug, Clone, PartialEq, From, Into)]
-#[cfg_attr(ser, derive(serde::Serialize, serde::Deserialize))]
-#[cfg_attr(feature = "builder", derive(derive_builder::Builder))]
-pub struct AnalysisBody {
-    pub metadata: Metadata,
-    pub results: Results,
-}
+/// Typed decoding of the body of analyses with a given `type`, `vendor` and `schema`
+pub trait AnalysisDecoder {
+    /// `type` of the analyses this decoder understands
+    const TYPE: &'static str;
+    /// `vendor` of the analyses this decoder understands, any when None
+    const VENDOR: Option<&'static str> = None;
+    /// `schema` of the analyses this decoder understands, any when None
+    const SCHEMA: Option<&'static str> = None;
 
-#[derive(Debug, Clone, PartialEq, From, Into)]
-#[cfg_attr(ser, derive(serde::Serialize, serde::Deserialize))]
-#[cfg_attr(feature = "builder", derive(derive_builder::Builder))]
-pub struct Metadata {
-    pub transaction_key: String,
-    pub request_id: Uuid,
-    pub sha256: String, // TODO: type depending alg
-    pub created: Date,
-    pub duration: Duration,
-    pub channels: u32,
-    pub models: Vec<Uuid>,
-    pub model_info: HashMap<Uuid, ModelInfo>,
+    type Output: 'static;
+
+    fn decode(body: &[u8]) -> VconResult<Self::Output>;
 }
 
-impl std::hash::Hash for Metadata {
-    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-        self.transaction_key.hash(state);
-        self.request_id.hash(state);
-        self.sha256.hash(state);
-        self.created.hash(state);
-        self.channels.hash(state);
-        self.models.hash(state);
-        // TODO: handle duration & model info
-        todo!()
+impl Analysis {
+    /// Whether `D` is able to decode this analysis
+    pub fn is<D: AnalysisDecoder>(&self) -> bool {
+        matches(self, D::TYPE, D::VENDOR, D::SCHEMA)
+    }
+
+    /// Decodes an inline body with `D`
+    pub fn decode<D: AnalysisDecoder>(&self) -> VconResult<D::Output> {
+        let body = self.content.inline_bytes().ok_or_else(|| {
+            VconError::UnresolvedReference("analysis body is url referenced".to_string())
+        })?;
+        self.decode_bytes::<D>(&body)
+    }
+
+    /// Decodes the body with `D`, url referenced bodies being fetched from `store`
+    pub fn decode_with<D: AnalysisDecoder>(&self, store: &impl VconStore) -> VconResult<D::Output> {
+        self.decode_bytes::<D>(&self.content.bytes(store)?)
+    }
+
+    fn decode_bytes<D: AnalysisDecoder>(&self, body: &[u8]) -> VconResult<D::Output> {
+        if !self.is::<D>() {
+            return Err(VconError::InvalidAnalysis(format!(
+                "decoder for '{}' analyses cannot decode a '{}' one",
+                D::TYPE,
+                self.typ
+            )));
+        }
+        D::decode(body)
     }
 }
 
-#[derive(Debug, Clone, Hash, Eq, PartialEq, From, Into)]
-#[cfg_attr(ser, derive(serde::Serialize, serde::Deserialize))]
-#[cfg_attr(feature = "builder", derive(derive_builder::Builder))]
-pub struct ModelInfo {
-    pub name: String,
-    pub version: String,
-    pub arch: String,
+/// Analysis of `dialog` whose body is `value` serialized as JSON
+#[cfg(feature = "json")]
+fn json_analysis(
+    typ: &str,
+    schema: &str,
+    dialog: DialogIndex,
+    value: &impl serde::Serialize,
+) -> VconResult<Analysis> {
+    let body =
+        serde_json::to_value(value).map_err(|e| VconError::InvalidAnalysis(e.to_string()))?;
+    Ok(Analysis {
+        typ: typ.to_string(),
+        dialog,
+        content_parameters: ContentParameters {
+            mime: Some("application/json".to_string().into()),
+            filename: None,
+        },
+        content: Content::Inline(crate::InlineContent::Json(crate::JsonAnyValue(body))),
+        vendor: None,
+        schema: Some(schema.to_string()),
+        product: None,
+        extension_object: Default::default(),
+    })
 }
 
-#[derive(Debug, Clone, PartialEq, From, Into)]
-#[cfg_attr(ser, derive(serde::Serialize, serde::Deserialize))]
-#[cfg_attr(feature = "builder", derive(derive_builder::Builder))]
-pub struct Results {
-    pub channels: Vec<Channel>,
+/// Decodes a JSON body into `T`
+#[cfg(feature = "json")]
+fn json_body<T: serde::de::DeserializeOwned>(body: &[u8]) -> VconResult<T> {
+    serde_json::from_slice(body).map_err(|e| VconError::InvalidAnalysis(e.to_string()))
 }
 
-#[derive(Debug, Clone, PartialEq, From, Into)]
-#[cfg_attr(ser, derive(serde::Serialize, serde::Deserialize))]
-#[cfg_attr(feature = "builder", derive(derive_builder::Builder))]
-pub struct Channel {
-    pub alternatives: Vec<Alternative>,
+#[cfg(not(feature = "json"))]
+fn json_body<T>(_body: &[u8]) -> VconResult<T> {
+    Err(VconError::InvalidAnalysis(
+        "decoding JSON analyses requires the 'json' feature".to_string(),
+    ))
 }
 
-#[derive(Debug, Clone, PartialEq, From, Into)]
-#[cfg_attr(ser, derive(serde::Serialize, serde::Deserialize))]
-#[cfg_attr(feature = "builder", derive(derive_builder::Builder))]
-pub struct Alternative {
-    pub transcript: String,
-    // #[cfg_attr(ser, serde(with = "crate::serde_float"))]
-    pub confidence: f32,
-    pub words: Vec<Word>,
-    #[cfg_attr(ser, serde(rename = "paragraphs"))]
-    pub paragraph: Paragraph,
+fn matches(analysis: &Analysis, typ: &str, vendor: Option<&str>, schema: Option<&str>) -> bool {
+    analysis.typ == typ
+        && vendor.is_none_or(|v| analysis.vendor.as_deref() == Some(v))
+        && schema.is_none_or(|s| analysis.schema.as_deref() == Some(s))
 }
 
-#[derive(Debug, Clone, PartialEq, From, Into)]
-#[cfg_attr(ser, derive(serde::Serialize, serde::Deserialize))]
-#[cfg_attr(feature = "builder", derive(derive_builder::Builder))]
-pub struct Word {
-    pub word: String,
-    pub start: f32,
-    pub end: f32,
-    pub confidence: f32,
-    pub speaker: PartyIndex,
-    pub speaker_confidence: f32,
-    pub punctuated_word: String,
+/// Set of [AnalysisDecoder]s picked according to the `type`, `vendor` and `schema` of an analysis
+///
+/// The default registry knows [deepgram::Deepgram] and [Transcript] transcripts as well as
+/// [ConversationMetrics].
+pub struct AnalysisRegistry {
+    decoders: Vec<RegisteredDecoder>,
 }
 
-#[derive(Debug, Clone, PartialEq, From, Into)]
-#[cfg_attr(ser, derive(serde::Serialize, serde::Deserialize))]
-#[cfg_attr(feature = "builder", derive(derive_builder::Builder))]
-pub struct Paragraph {
-    pub transcript: String,
-    pub paragraphs: Vec<InnerParagraph>,
+struct RegisteredDecoder {
+    typ: &'static str,
+    vendor: Option<&'static str>,
+    schema: Option<&'static str>,
+    decode: fn(&[u8]) -> VconResult<Box<dyn Any>>,
 }
 
-#[derive(Debug, Clone, PartialEq, From, Into)]
-#[cfg_attr(ser, derive(serde::Serialize, serde::Deserialize))]
-#[cfg_attr(feature = "builder", derive(derive_builder::Builder))]
-pub struct InnerParagraph {
-    pub sentences: Vec<Sentence>,
-    pub speaker: u32,
-    pub num_words: u32,
-    pub start: f32,
-    pub end: f32,
+impl RegisteredDecoder {
+    fn specificity(&self) -> usize {
+        self.vendor.is_some() as usize + self.schema.is_some() as usize
+    }
 }
 
-#[derive(Debug, Clone, PartialEq, From, Into)]
-#[cfg_attr(ser, derive(serde::Serialize, serde::Deserialize))]
-#[cfg_attr(feature = "builder", derive(derive_builder::Builder))]
-pub struct Sentence {
-    pub text: String,
-    pub start: f32,
-    pub end: f32,
+impl Default for AnalysisRegistry {
+    fn default() -> Self {
+        let mut registry = Self::empty();
+        registry.register::<deepgram::Deepgram>();
+        registry.register::<Transcript>();
+        registry.register::<ConversationMetrics>();
+        registry
+    }
+}
+
+impl AnalysisRegistry {
+    /// Registry without any decoder
+    pub fn empty() -> Self {
+        Self { decoders: vec![] }
+    }
+
+    /// Registers `D`, taking precedence over previously registered ones as specific
+    pub fn register<D: AnalysisDecoder>(&mut self) -> &mut Self {
+        self.decoders.push(RegisteredDecoder {
+            typ: D::TYPE,
+            vendor: D::VENDOR,
+            schema: D::SCHEMA,
+            decode: |body| D::decode(body).map(|output| Box::new(output) as Box<dyn Any>),
+        });
+        self
+    }
+
+    /// Most specific decoder matching the analysis
+    fn find(&self, analysis: &Analysis) -> Option<&RegisteredDecoder> {
+        self.decoders
+            .iter()
+            .filter(|d| matches(analysis, d.typ, d.vendor, d.schema))
+            .max_by_key(|d| d.specificity())
+    }
+
+    /// Whether a registered decoder is able to decode this analysis
+    pub fn supports(&self, analysis: &Analysis) -> bool {
+        self.find(analysis).is_some()
+    }
+
+    /// Decodes an inline body with the most specific registered decoder, None when there is none.
+    ///
+    /// The output is the [AnalysisDecoder::Output] of the selected decoder.
+    pub fn decode(&self, analysis: &Analysis) -> VconResult<Option<Box<dyn Any>>> {
+        let Some(decoder) = self.find(analysis) else {
+            return Ok(None);
+        };
+        let body = analysis.content.inline_bytes().ok_or_else(|| {
+            VconError::UnresolvedReference("analysis body is url referenced".to_string())
+        })?;
+        (decoder.decode)(&body).map(Some)
+    }
+
+    /// Same as [AnalysisRegistry::decode] with url referenced bodies being fetched from `store`
+    pub fn decode_with(
+        &self,
+        analysis: &Analysis,
+        store: &impl VconStore,
+    ) -> VconResult<Option<Box<dyn Any>>> {
+        let Some(decoder) = self.find(analysis) else {
+            return Ok(None);
+        };
+        (decoder.decode)(&analysis.content.bytes(store)?).map(Some)
+    }
 }
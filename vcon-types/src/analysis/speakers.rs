@@ -0,0 +1,147 @@
+use crate::{analysis::Transcript, Dialog, DialogObject, PartyIndex, Vcon, VconError, VconResult};
+use std::collections::BTreeMap;
+
+/// Which party each speaker label of a transcript stands for
+///
+/// Transcription engines label speakers 0, 1, 2… by order of appearance (diarization) or by
+/// channel, regardless of the `parties` of the vCon. The mapping of an analysis is persisted in its
+/// extension object under [SpeakerMapping::EXTENSION].
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+#[cfg_attr(ser, derive(serde::Serialize, serde::Deserialize), serde(transparent))]
+pub struct SpeakerMapping(BTreeMap<u32, PartyIndex>);
+
+impl SpeakerMapping {
+    /// Key of the analysis extension holding the mapping
+    pub const EXTENSION: &'static str = "speaker_mapping";
+
+    pub fn insert(&mut self, label: u32, party: PartyIndex) -> &mut Self {
+        self.0.insert(label, party);
+        self
+    }
+
+    /// Party the speaker `label` stands for, None when unknown
+    pub fn get(&self, label: u32) -> Option<PartyIndex> {
+        self.0.get(&label).copied()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (u32, PartyIndex)> + '_ {
+        self.0.iter().map(|(label, party)| (*label, *party))
+    }
+
+    /// Guesses the mapping of a transcript of `dialog`.
+    ///
+    /// A multichannel transcript (see [Transcript::channels]) labels speakers by channel, channels
+    /// following the order of the dialog's `parties`. Otherwise labels are taken by order of first
+    /// appearance and assigned to the dialog's `parties` in order, the `originator` (if any) being
+    /// the first one to speak. Labels left over are not mapped.
+    pub fn infer(dialog: &DialogObject, transcript: &Transcript) -> Self {
+        let mut parties = dialog
+            .dialog
+            .parties()
+            .map(|p| p.indexes())
+            .unwrap_or_default();
+
+        if transcript.channels.is_some_and(|c| c > 1) {
+            return Self((0..).zip(parties).collect());
+        }
+
+        let originator = match &dialog.dialog {
+            Dialog::Recording { originator, .. } | Dialog::Text { originator, .. } => *originator,
+            _ => None,
+        };
+        if let Some(position) = originator.and_then(|o| parties.iter().position(|p| *p == o)) {
+            let originator = parties.remove(position);
+            parties.insert(0, originator);
+        }
+
+        let mut appearances = transcript
+            .segments
+            .iter()
+            .filter_map(|s| Some((s.start, s.speaker?)))
+            .chain(
+                transcript
+                    .words()
+                    .filter_map(|w| Some((w.start, w.speaker?))),
+            )
+            .collect::<Vec<_>>();
+        appearances.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let mut labels = vec![];
+        for (_, label) in appearances {
+            if !labels.contains(&label) {
+                labels.push(label);
+            }
+        }
+        Self(labels.into_iter().zip(parties).collect())
+    }
+
+    /// Rewrites the speaker labels of `transcript` into party indexes, unmapped ones being removed
+    pub fn apply(&self, transcript: &mut Transcript) {
+        for segment in &mut transcript.segments {
+            segment.speaker = segment.speaker.and_then(|s| self.get(s));
+            for word in &mut segment.words {
+                word.speaker = word.speaker.and_then(|s| self.get(s));
+            }
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+impl crate::VconExtension for SpeakerMapping {
+    const NAME: &'static str = Self::EXTENSION;
+    type Value = Self;
+}
+
+#[cfg(feature = "json")]
+impl crate::Analysis {
+    /// [SpeakerMapping] persisted in the extension object, if any
+    pub fn speaker_mapping(&self) -> VconResult<Option<SpeakerMapping>> {
+        use crate::Extensible as _;
+        self.extension::<SpeakerMapping>()
+    }
+
+    /// Persists `mapping` in the extension object
+    pub fn set_speaker_mapping(&mut self, mapping: &SpeakerMapping) -> VconResult<()> {
+        use crate::Extensible as _;
+        self.set_extension::<SpeakerMapping>(mapping)
+    }
+}
+
+impl Vcon {
+    /// Transcript of the analysis at index `analysis` whose speakers are party indexes, None when
+    /// it is not a transcript (see [Transcript::from_analysis]).
+    ///
+    /// Speakers are mapped with the [SpeakerMapping] persisted in the analysis or, when there is
+    /// none, with the one inferred by [SpeakerMapping::infer].
+    ///
+    /// Fails when the analysis does not exist, references a dialog which does not exist or cannot
+    /// be decoded.
+    pub fn transcript(&self, analysis: usize) -> VconResult<Option<Transcript>> {
+        let analysis = self
+            .analysis
+            .as_deref()
+            .and_then(|a| a.get(analysis))
+            .ok_or_else(|| VconError::InvalidAnalysis(format!("unknown analysis {analysis}")))?;
+        let Some(mut transcript) = Transcript::from_analysis(analysis)? else {
+            return Ok(None);
+        };
+        let dialog = self
+            .dialog
+            .as_deref()
+            .and_then(|d| d.get(analysis.dialog as usize))
+            .ok_or_else(|| {
+                VconError::InvalidAnalysis(format!(
+                    "transcript of unknown dialog {}",
+                    analysis.dialog
+                ))
+            })?;
+
+        #[cfg(feature = "json")]
+        let persisted = analysis.speaker_mapping()?;
+        #[cfg(not(feature = "json"))]
+        let persisted = None;
+        persisted
+            .unwrap_or_else(|| SpeakerMapping::infer(dialog, &transcript))
+            .apply(&mut transcript);
+        Ok(Some(transcript))
+    }
+}
@@ -0,0 +1,590 @@
+use crate::{
+    analysis::{deepgram, AnalysisDecoder},
+    Analysis, VconError, VconResult, VconStore,
+};
+use std::borrow::Cow;
+use std::collections::BTreeSet;
+
+/// Vendor neutral transcript
+///
+/// Times are offsets in seconds from the start of the transcribed recording. Speakers are the
+/// labels emitted by the transcription engine (e.g. diarization), not party indexes: see
+/// [crate::SpeakerMapping] and [crate::Vcon::transcript].
+///
+/// Built from [deepgram::PrerecordedResponse] or the JSON output of Whisper, AWS Transcribe and
+/// Google Speech-to-Text. Use [Transcript::from_analysis] to get one whatever the vendor.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(ser, derive(serde::Serialize, serde::Deserialize))]
+pub struct Transcript {
+    /// BCP-47 language tag
+    #[cfg_attr(ser, serde(skip_serializing_if = "Option::is_none"))]
+    pub language: Option<String>,
+    /// Length of the transcribed recording
+    #[cfg_attr(ser, serde(skip_serializing_if = "Option::is_none"))]
+    pub duration: Option<f64>,
+    #[cfg_attr(ser, serde(skip_serializing_if = "Option::is_none"))]
+    pub confidence: Option<f64>,
+    /// Number of audio channels transcribed separately, speakers being labelled by channel when
+    /// more than one
+    #[cfg_attr(ser, serde(skip_serializing_if = "Option::is_none"))]
+    pub channels: Option<u32>,
+    /// Utterances (usually sentences) in chronological order
+    pub segments: Vec<TranscriptSegment>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(ser, derive(serde::Serialize, serde::Deserialize))]
+pub struct TranscriptSegment {
+    pub text: String,
+    pub start: f64,
+    pub end: f64,
+    #[cfg_attr(ser, serde(skip_serializing_if = "Option::is_none"))]
+    pub speaker: Option<u32>,
+    #[cfg_attr(ser, serde(skip_serializing_if = "Option::is_none"))]
+    pub confidence: Option<f64>,
+    #[cfg_attr(ser, serde(default, skip_serializing_if = "Vec::is_empty"))]
+    pub words: Vec<TranscriptWord>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(ser, derive(serde::Serialize, serde::Deserialize))]
+pub struct TranscriptWord {
+    /// Word including its punctuation when available
+    pub text: String,
+    pub start: f64,
+    pub end: f64,
+    #[cfg_attr(ser, serde(skip_serializing_if = "Option::is_none"))]
+    pub speaker: Option<u32>,
+    #[cfg_attr(ser, serde(skip_serializing_if = "Option::is_none"))]
+    pub confidence: Option<f64>,
+}
+
+impl Transcript {
+    /// `schema` of analyses holding a serialized [Transcript]
+    pub const SCHEMA: &'static str = "vcon-types/transcript";
+
+    /// Every word of every segment, in chronological order
+    pub fn words(&self) -> impl Iterator<Item = &TranscriptWord> {
+        self.segments.iter().flat_map(|s| s.words.iter())
+    }
+
+    /// Distinct speaker labels
+    pub fn speakers(&self) -> BTreeSet<u32> {
+        self.segments
+            .iter()
+            .filter_map(|s| s.speaker)
+            .chain(self.words().filter_map(|w| w.speaker))
+            .collect()
+    }
+
+    /// Full text, segments separated by a space
+    pub fn text(&self) -> String {
+        self.segments
+            .iter()
+            .map(|s| s.text.trim())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Normalizes a `transcript` analysis according to its `vendor` (or `schema`), None when
+    /// the vendor is not supported or the body is url referenced (see
+    /// [Transcript::from_analysis_with]).
+    pub fn from_analysis(analysis: &Analysis) -> VconResult<Option<Self>> {
+        Self::normalize(analysis, || Ok(analysis.content.inline_bytes()))
+    }
+
+    /// Same as [Transcript::from_analysis], url referenced bodies of supported vendors being
+    /// fetched from `store`
+    pub fn from_analysis_with(
+        analysis: &Analysis,
+        store: &impl VconStore,
+    ) -> VconResult<Option<Self>> {
+        Self::normalize(analysis, || analysis.content.bytes(store).map(Some))
+    }
+
+    /// Normalizes `analysis` whose body is only read once its vendor is known to be supported
+    fn normalize<'a>(
+        analysis: &'a Analysis,
+        body: impl FnOnce() -> VconResult<Option<Cow<'a, [u8]>>>,
+    ) -> VconResult<Option<Self>> {
+        if analysis.typ != "transcript" {
+            return Ok(None);
+        }
+        let vendor = analysis.vendor.as_deref().map(str::to_lowercase);
+        let vendor = match vendor.as_deref() {
+            _ if analysis.schema.as_deref() == Some(Self::SCHEMA) => Vendor::Neutral,
+            Some("deepgram") => Vendor::Deepgram,
+            #[cfg(feature = "json")]
+            Some("openai" | "whisper") => Vendor::Whisper,
+            #[cfg(feature = "json")]
+            Some("aws" | "amazon") => Vendor::Aws,
+            #[cfg(feature = "json")]
+            Some("google") => Vendor::Google,
+            _ => return Ok(None),
+        };
+        let Some(body) = body()? else {
+            return Ok(None);
+        };
+        Ok(Some(match vendor {
+            Vendor::Neutral => analysis.decode_bytes::<Self>(&body)?,
+            Vendor::Deepgram => (&analysis.decode_bytes::<deepgram::Deepgram>(&body)?).into(),
+            #[cfg(feature = "json")]
+            Vendor::Whisper => Self::from_whisper_json(&body)?,
+            #[cfg(feature = "json")]
+            Vendor::Aws => Self::from_aws_transcribe_json(&body)?,
+            #[cfg(feature = "json")]
+            Vendor::Google => Self::from_google_stt_json(&body)?,
+        }))
+    }
+}
+
+/// Formats [Transcript::from_analysis] understands
+enum Vendor {
+    /// [Transcript] as is
+    Neutral,
+    Deepgram,
+    #[cfg(feature = "json")]
+    Whisper,
+    #[cfg(feature = "json")]
+    Aws,
+    #[cfg(feature = "json")]
+    Google,
+}
+
+#[cfg(feature = "json")]
+impl Transcript {
+    /// Wraps this transcript into a `transcript` analysis of `dialog` (see [Transcript::SCHEMA])
+    pub fn to_analysis(&self, dialog: crate::DialogIndex) -> VconResult<Analysis> {
+        super::json_analysis(Self::TYPE, Self::SCHEMA, dialog, self)
+    }
+}
+
+/// Analyses holding a [Transcript] as is (see [Transcript::SCHEMA])
+impl AnalysisDecoder for Transcript {
+    const TYPE: &'static str = "transcript";
+    const SCHEMA: Option<&'static str> = Some(Transcript::SCHEMA);
+    type Output = Transcript;
+
+    fn decode(body: &[u8]) -> VconResult<Self::Output> {
+        super::json_body(body)
+    }
+}
+
+/// Segments are the sentences of each paragraph, words being attributed to the sentence they
+/// fall in. Every channel is kept, speakers being labelled by channel index when there are several.
+impl From<&deepgram::PrerecordedResponse> for Transcript {
+    fn from(response: &deepgram::PrerecordedResponse) -> Self {
+        const EPSILON: f64 = 0.01;
+
+        let channels = &response.results.channels;
+        let multichannel = channels.len() > 1;
+
+        let mut segments = vec![];
+        let mut confidences = vec![];
+        for (channel, alternative) in (0..).zip(channels.iter().map(|c| c.alternatives.first())) {
+            let Some(alternative) = alternative else {
+                continue;
+            };
+            let speaker = |label| if multichannel { channel } else { label };
+            confidences.push(alternative.confidence as f64);
+            let mut words = alternative.words.iter().peekable();
+            for paragraph in &alternative.paragraph.paragraphs {
+                for sentence in &paragraph.sentences {
+                    let (start, end) = (sentence.start as f64, sentence.end as f64);
+                    let mut segment = TranscriptSegment {
+                        text: sentence.text.clone(),
+                        start,
+                        end,
+                        speaker: Some(speaker(paragraph.speaker)),
+                        ..Default::default()
+                    };
+                    // skip words in between sentences, if any
+                    while words.next_if(|w| (w.end as f64) <= start).is_some() {}
+                    while let Some(word) = words.next_if(|w| (w.start as f64) < end - EPSILON) {
+                        segment.words.push(TranscriptWord {
+                            text: word.punctuated_word.clone(),
+                            start: word.start as f64,
+                            end: word.end as f64,
+                            speaker: Some(speaker(word.speaker)),
+                            confidence: Some(word.confidence as f64),
+                        });
+                    }
+                    segment.confidence = mean(segment.words.iter().filter_map(|w| w.confidence));
+                    segments.push(segment);
+                }
+            }
+        }
+        segments.sort_by(|a, b| a.start.total_cmp(&b.start));
+
+        Self {
+            language: None,
+            duration: Some(response.metadata.duration.as_secs_f64()),
+            confidence: mean(confidences),
+            channels: Some(channels.len() as u32),
+            segments,
+        }
+    }
+}
+
+fn mean(values: impl IntoIterator<Item = f64>) -> Option<f64> {
+    let (sum, count) = values
+        .into_iter()
+        .fold((0.0, 0usize), |(sum, count), v| (sum + v, count + 1));
+    (count > 0).then(|| sum / count as f64)
+}
+
+#[cfg(feature = "json")]
+impl Transcript {
+    /// From the `verbose_json` output of OpenAI Whisper
+    ///
+    /// See https://platform.openai.com/docs/api-reference/audio/verbose-json-object
+    pub fn from_whisper_json(json: &[u8]) -> VconResult<Self> {
+        #[derive(serde::Deserialize)]
+        struct WhisperTranscript {
+            language: Option<String>,
+            duration: Option<f64>,
+            #[serde(default)]
+            segments: Vec<WhisperSegment>,
+            #[serde(default)]
+            words: Vec<WhisperWord>,
+        }
+        #[derive(serde::Deserialize)]
+        struct WhisperSegment {
+            text: String,
+            start: f64,
+            end: f64,
+            avg_logprob: Option<f64>,
+            #[serde(default)]
+            words: Vec<WhisperWord>,
+        }
+        #[derive(serde::Deserialize)]
+        struct WhisperWord {
+            word: String,
+            start: f64,
+            end: f64,
+            probability: Option<f64>,
+        }
+
+        let whisper = serde_json::from_slice::<WhisperTranscript>(json)
+            .map_err(|e| VconError::InvalidAnalysis(e.to_string()))?;
+
+        let word = |w: WhisperWord| TranscriptWord {
+            text: w.word.trim().to_string(),
+            start: w.start,
+            end: w.end,
+            speaker: None,
+            confidence: w.probability,
+        };
+        // word timestamps are either given per segment or once for the whole transcript
+        let mut words = whisper.words.into_iter().map(word).peekable();
+        let segments = whisper
+            .segments
+            .into_iter()
+            .map(|s| {
+                let mut segment_words = s.words.into_iter().map(word).collect::<Vec<_>>();
+                while let Some(w) = words.next_if(|w| w.start < s.end) {
+                    segment_words.push(w);
+                }
+                TranscriptSegment {
+                    text: s.text.trim().to_string(),
+                    start: s.start,
+                    end: s.end,
+                    speaker: None,
+                    confidence: s.avg_logprob.map(f64::exp),
+                    words: segment_words,
+                }
+            })
+            .collect();
+
+        Ok(Self {
+            language: whisper.language,
+            duration: whisper.duration,
+            confidence: None,
+            channels: None,
+            segments,
+        })
+    }
+
+    /// From the output of an AWS Transcribe job
+    ///
+    /// See https://docs.aws.amazon.com/transcribe/latest/dg/how-input.html#how-it-works-output
+    pub fn from_aws_transcribe_json(json: &[u8]) -> VconResult<Self> {
+        #[derive(serde::Deserialize)]
+        struct AwsTranscript {
+            results: AwsResults,
+        }
+        #[derive(serde::Deserialize)]
+        struct AwsResults {
+            #[serde(default)]
+            transcripts: Vec<AwsText>,
+            #[serde(default)]
+            items: Vec<AwsItem>,
+            speaker_labels: Option<AwsSpeakerLabels>,
+            language_code: Option<String>,
+        }
+        #[derive(serde::Deserialize)]
+        struct AwsText {
+            transcript: String,
+        }
+        #[derive(serde::Deserialize)]
+        struct AwsItem {
+            #[serde(rename = "type")]
+            typ: String,
+            start_time: Option<String>,
+            end_time: Option<String>,
+            alternatives: Vec<AwsAlternative>,
+            speaker_label: Option<String>,
+        }
+        #[derive(serde::Deserialize)]
+        struct AwsAlternative {
+            content: String,
+            confidence: Option<String>,
+        }
+        #[derive(serde::Deserialize)]
+        struct AwsSpeakerLabels {
+            segments: Vec<AwsSpeakerSegment>,
+        }
+        #[derive(serde::Deserialize)]
+        struct AwsSpeakerSegment {
+            start_time: String,
+            end_time: String,
+            speaker_label: String,
+        }
+
+        let invalid = |e: &dyn std::fmt::Display| VconError::InvalidAnalysis(e.to_string());
+        let seconds = |s: &str| s.parse::<f64>().map_err(|e| invalid(&e));
+        // e.g. "spk_0"
+        let speaker = |label: &str| label.rsplit('_').next().and_then(|n| n.parse::<u32>().ok());
+
+        let aws = serde_json::from_slice::<AwsTranscript>(json)
+            .map_err(|e| invalid(&e))?
+            .results;
+
+        let mut words = Vec::<TranscriptWord>::with_capacity(aws.items.len());
+        for item in &aws.items {
+            let Some(alternative) = item.alternatives.first() else {
+                continue;
+            };
+            match (item.typ.as_str(), words.last_mut()) {
+                ("punctuation", Some(previous)) => previous.text.push_str(&alternative.content),
+                ("punctuation", None) => {}
+                _ => words.push(TranscriptWord {
+                    text: alternative.content.clone(),
+                    start: item
+                        .start_time
+                        .as_deref()
+                        .map(seconds)
+                        .transpose()?
+                        .unwrap_or_default(),
+                    end: item
+                        .end_time
+                        .as_deref()
+                        .map(seconds)
+                        .transpose()?
+                        .unwrap_or_default(),
+                    speaker: item.speaker_label.as_deref().and_then(speaker),
+                    confidence: alternative.confidence.as_deref().map(seconds).transpose()?,
+                }),
+            }
+        }
+
+        let speaker_segments = aws
+            .speaker_labels
+            .map(|l| l.segments)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|s| {
+                Ok((
+                    seconds(&s.start_time)?,
+                    seconds(&s.end_time)?,
+                    speaker(&s.speaker_label),
+                ))
+            })
+            .collect::<VconResult<Vec<_>>>()?;
+
+        let segments = if speaker_segments.is_empty() {
+            let text = aws
+                .transcripts
+                .into_iter()
+                .map(|t| t.transcript)
+                .collect::<Vec<_>>();
+            vec![TranscriptSegment {
+                text: text.join(" "),
+                start: words.first().map(|w| w.start).unwrap_or_default(),
+                end: words.last().map(|w| w.end).unwrap_or_default(),
+                speaker: None,
+                confidence: mean(words.iter().filter_map(|w| w.confidence)),
+                words,
+            }]
+        } else {
+            let mut words = words.into_iter().peekable();
+            speaker_segments
+                .into_iter()
+                .map(|(start, end, speaker)| {
+                    let mut segment_words = vec![];
+                    while let Some(mut w) = words.next_if(|w| w.start < end) {
+                        w.speaker = w.speaker.or(speaker);
+                        segment_words.push(w);
+                    }
+                    TranscriptSegment {
+                        text: segment_words
+                            .iter()
+                            .map(|w| w.text.as_str())
+                            .collect::<Vec<_>>()
+                            .join(" "),
+                        start,
+                        end,
+                        speaker,
+                        confidence: mean(segment_words.iter().filter_map(|w| w.confidence)),
+                        words: segment_words,
+                    }
+                })
+                .collect()
+        };
+
+        Ok(Self {
+            language: aws.language_code,
+            duration: segments.last().map(|s| s.end),
+            confidence: mean(segments.iter().filter_map(|s| s.confidence)),
+            channels: None,
+            segments,
+        })
+    }
+
+    /// From the response of Google Speech-to-Text `recognize` or `longrunningrecognize`
+    ///
+    /// With diarization enabled, the last result holds every word tagged with its speaker: the
+    /// transcript is then split into segments on speaker change.
+    ///
+    /// See https://cloud.google.com/speech-to-text/docs/reference/rest/v1/speech/recognize#response-body
+    pub fn from_google_stt_json(json: &[u8]) -> VconResult<Self> {
+        #[derive(serde::Deserialize)]
+        struct GoogleResponse {
+            #[serde(default)]
+            results: Vec<GoogleResult>,
+        }
+        #[derive(serde::Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct GoogleResult {
+            #[serde(default)]
+            alternatives: Vec<GoogleAlternative>,
+            #[serde(alias = "language_code")]
+            language_code: Option<String>,
+            #[serde(alias = "result_end_time")]
+            result_end_time: Option<String>,
+        }
+        #[derive(serde::Deserialize)]
+        struct GoogleAlternative {
+            #[serde(default)]
+            transcript: String,
+            confidence: Option<f64>,
+            #[serde(default)]
+            words: Vec<GoogleWord>,
+        }
+        #[derive(serde::Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct GoogleWord {
+            word: String,
+            #[serde(alias = "start_time")]
+            start_time: Option<String>,
+            #[serde(alias = "end_time")]
+            end_time: Option<String>,
+            confidence: Option<f64>,
+            #[serde(alias = "speaker_tag")]
+            speaker_tag: Option<u32>,
+        }
+
+        let invalid = |e: &dyn std::fmt::Display| VconError::InvalidAnalysis(e.to_string());
+        // durations are formatted as e.g. "1.300s"
+        let seconds = |s: &Option<String>| -> VconResult<f64> {
+            s.as_deref()
+                .map(|s| {
+                    s.trim_end_matches('s')
+                        .parse::<f64>()
+                        .map_err(|e| invalid(&e))
+                })
+                .transpose()
+                .map(Option::unwrap_or_default)
+        };
+        let word = |w: &GoogleWord| -> VconResult<TranscriptWord> {
+            Ok(TranscriptWord {
+                text: w.word.clone(),
+                start: seconds(&w.start_time)?,
+                end: seconds(&w.end_time)?,
+                speaker: w.speaker_tag.filter(|t| *t > 0),
+                confidence: w.confidence,
+            })
+        };
+
+        let google = serde_json::from_slice::<GoogleResponse>(json).map_err(|e| invalid(&e))?;
+        let language = google.results.iter().find_map(|r| r.language_code.clone());
+
+        let diarized = google
+            .results
+            .iter()
+            .rev()
+            .filter_map(|r| r.alternatives.first())
+            .find(|a| a.words.iter().any(|w| w.speaker_tag.is_some_and(|t| t > 0)));
+
+        let mut segments = vec![];
+        if let Some(diarized) = diarized {
+            for w in &diarized.words {
+                let w = word(w)?;
+                match segments.last_mut() {
+                    Some(TranscriptSegment { speaker, words, .. }) if *speaker == w.speaker => {
+                        words.push(w)
+                    }
+                    _ => segments.push(TranscriptSegment {
+                        speaker: w.speaker,
+                        words: vec![w],
+                        ..Default::default()
+                    }),
+                }
+            }
+            for segment in &mut segments {
+                segment.start = segment.words.first().map(|w| w.start).unwrap_or_default();
+                segment.end = segment.words.last().map(|w| w.end).unwrap_or_default();
+                segment.text = segment
+                    .words
+                    .iter()
+                    .map(|w| w.text.as_str())
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                segment.confidence = mean(segment.words.iter().filter_map(|w| w.confidence));
+            }
+        } else {
+            let mut previous_end = 0.0;
+            for result in &google.results {
+                let Some(alternative) = result.alternatives.first() else {
+                    continue;
+                };
+                let words = alternative
+                    .words
+                    .iter()
+                    .map(word)
+                    .collect::<VconResult<Vec<_>>>()?;
+                let end = match &result.result_end_time {
+                    Some(_) => seconds(&result.result_end_time)?,
+                    None => words.last().map(|w| w.end).unwrap_or(previous_end),
+                };
+                segments.push(TranscriptSegment {
+                    text: alternative.transcript.trim().to_string(),
+                    start: words.first().map(|w| w.start).unwrap_or(previous_end),
+                    end,
+                    speaker: None,
+                    confidence: alternative.confidence,
+                    words,
+                });
+                previous_end = end;
+            }
+        }
+
+        Ok(Self {
+            language,
+            duration: segments.last().map(|s| s.end),
+            confidence: mean(segments.iter().filter_map(|s| s.confidence)),
+            channels: None,
+            segments,
+        })
+    }
+}
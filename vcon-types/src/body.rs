@@ -1,4 +1,6 @@
-#[cfg(feature = "cbor")]
+use crate::{VconError, VconResult};
+
+/// CBOR tag of base64url bodies
 pub const BASE_64_URL_TAG: u64 = 21;
 
 /// Binary body
@@ -14,7 +16,7 @@ pub const BASE_64_URL_TAG: u64 = 21;
 /// ```rust
 /// // # use serde_json::json;
 /// // # use vcon_types::InlineContent;
-/// // let actual = InlineContent::BinaryBase64Url(b"abcd".to_vec());
+/// // let actual = InlineContent::BinaryBase64Url(Base64Body::encode(b"abcd"));
 /// // # let actual_ser = serde_json::to_string(&actual).unwrap();
 /// // let expected = json!({
 /// //     "encoding": "base64url",
@@ -31,8 +33,8 @@ pub const BASE_64_URL_TAG: u64 = 21;
 /// ```rust
 /// # #[cfg(feature = "cbor")] {
 /// # use ciborium::Value;
-/// # use vcon_types::InlineContent;
-/// let actual = InlineContent::BinaryBase64Url(b"abcd".to_vec());
+/// # use vcon_types::{Base64Body, InlineContent};
+/// let actual = InlineContent::BinaryBase64Url(Base64Body::encode(b"abcd"));
 /// # let actual_ser = Value::serialized(&actual).unwrap();
 /// let expected = Value::Map(vec![
 ///     (Value::Text("encoding".into()), Value::Text("base64url".into())),
@@ -80,14 +82,45 @@ pub const BASE_64_URL_TAG: u64 = 21;
 /// ```
 #[derive(Debug, Clone, Hash, Eq, PartialEq)]
 pub enum InlineContent {
-    BinaryBase64Url(Vec<u8>),
+    BinaryBase64Url(Base64Body),
     TextJson(String),
     TextNone(String),
+    /// `json` encoded body embedded as a structured value rather than as a string
+    #[cfg(feature = "json")]
+    Json(crate::JsonAnyValue),
 }
 
 impl InlineContent {
     /// Base64 encoding in use
     pub const B64: base64::engine::GeneralPurpose = base64::prelude::BASE64_URL_SAFE_NO_PAD;
+
+    /// Decoded body, base64url bodies being decoded on each call
+    pub fn bytes(&self) -> std::borrow::Cow<'_, [u8]> {
+        match self {
+            Self::BinaryBase64Url(body) => body.decode().into(),
+            Self::TextJson(text) | Self::TextNone(text) => text.as_bytes().into(),
+            #[cfg(feature = "json")]
+            Self::Json(value) => value.0.to_string().into_bytes().into(),
+        }
+    }
+
+    /// Writes the decoded body to `writer`, base64url bodies being decoded chunk by chunk
+    pub fn write_to(&self, mut writer: impl std::io::Write) -> std::io::Result<()> {
+        match self {
+            Self::BinaryBase64Url(body) => body.decode_to(writer),
+            _ => writer.write_all(&self.bytes()),
+        }
+    }
+
+    pub fn encoding(&self) -> BodyEncoding {
+        match self {
+            Self::BinaryBase64Url(_) => BodyEncoding::Base64Url,
+            Self::TextNone(_) => BodyEncoding::None,
+            Self::TextJson(_) => BodyEncoding::Json,
+            #[cfg(feature = "json")]
+            Self::Json(_) => BodyEncoding::Json,
+        }
+    }
 }
 
 #[cfg(ser)]
@@ -95,18 +128,16 @@ impl serde::Serialize for InlineContent {
     fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
         let mut bb = serializer.serialize_map(Some(2))?;
         match self {
-            InlineContent::BinaryBase64Url(bytes) => {
+            InlineContent::BinaryBase64Url(body) => {
                 bb.serialize_entry("encoding", &BodyEncoding::Base64Url)?;
-                use base64::Engine as _;
-                let b64 = Self::B64.encode(bytes);
                 #[cfg(feature = "json")]
                 {
-                    bb.serialize_entry("body", &b64)?;
+                    bb.serialize_entry("body", body.as_str())?;
                 }
                 #[cfg(feature = "cbor")]
                 {
                     // TODO: we should not have to b64 encode it but that's what the spec currently says so...
-                    let b64 = ciborium::Value::Bytes(b64.into_bytes());
+                    let b64 = ciborium::Value::Bytes(body.as_str().as_bytes().to_vec());
                     let value = ciborium::tag::Required::<_, BASE_64_URL_TAG>(b64);
                     bb.serialize_entry("body", &value)?;
                 }
@@ -119,6 +150,11 @@ impl serde::Serialize for InlineContent {
                 bb.serialize_entry("encoding", &BodyEncoding::Json)?;
                 bb.serialize_entry("body", &body)?;
             }
+            #[cfg(feature = "json")]
+            InlineContent::Json(body) => {
+                bb.serialize_entry("encoding", &BodyEncoding::Json)?;
+                bb.serialize_entry("body", &body)?;
+            }
         }
         use serde::ser::SerializeMap as _;
         bb.end()
@@ -151,16 +187,14 @@ impl<'de> serde::Deserialize<'de> for InlineContent {
                         ))?;
                 Ok(match encoding {
                     BodyEncoding::Base64Url => {
-                        use base64::Engine as _;
                         use serde::de::Error as _;
                         #[cfg(feature = "json")]
                         {
                             let (_, value) = map
                                 .next_entry::<String, String>()?
                                 .ok_or(A::Error::custom("Invalid Body serialization"))?;
-                            let value = InlineContent::B64
-                                .decode(&value)
-                                .map_err(|e| A::Error::custom(format!("{e:?}")))?;
+                            let value =
+                                Base64Body::from_encoded(value).map_err(A::Error::custom)?;
                             Self::Value::BinaryBase64Url(value)
                         }
                         #[cfg(feature = "cbor")]
@@ -172,9 +206,8 @@ impl<'de> serde::Deserialize<'de> for InlineContent {
                                 .0
                                 .into_bytes()
                                 .map_err(|_| A::Error::custom("Binary Body should be bytes"))?;
-                            let value = InlineContent::B64
-                                .decode(value)
-                                .map_err(|e| A::Error::custom(format!("{e:?}")))?;
+                            let value =
+                                Base64Body::from_encoded(value).map_err(A::Error::custom)?;
                             Self::Value::BinaryBase64Url(value)
                         }
                     }
@@ -195,10 +228,20 @@ impl<'de> serde::Deserialize<'de> for InlineContent {
                         }
                     }
                     BodyEncoding::Json => {
-                        let (_, value) = map
-                            .next_entry::<String, String>()?
-                            .ok_or(A::Error::custom("Invalid Body serialization"))?;
-                        Self::Value::TextJson(value)
+                        #[cfg(feature = "json")]
+                        {
+                            let (_, value) = map
+                                .next_entry::<String, serde_json::Value>()?
+                                .ok_or(A::Error::custom("Invalid Body serialization"))?;
+                            InlineContent::from_json_body(value)
+                        }
+                        #[cfg(feature = "cbor")]
+                        {
+                            let (_, value) = map
+                                .next_entry::<String, String>()?
+                                .ok_or(A::Error::custom("Invalid Body serialization"))?;
+                            Self::Value::TextJson(value)
+                        }
                     }
                 })
             }
@@ -208,6 +251,171 @@ impl<'de> serde::Deserialize<'de> for InlineContent {
     }
 }
 
+#[cfg(feature = "json")]
+impl InlineContent {
+    /// A `json` encoded body is a string as per the draft but is commonly found embedded as is
+    pub(crate) fn from_json_body(body: serde_json::Value) -> Self {
+        match body {
+            serde_json::Value::String(body) => Self::TextJson(body),
+            body => Self::Json(crate::JsonAnyValue(body)),
+        }
+    }
+}
+
+/// Base64url encoded body, decoded on demand
+///
+/// The encoded text is kept as found in the vCon, in [bytes::Bytes] shared by clones, so that
+/// large inline recordings are neither decoded when parsed nor encoded again when serialized.
+/// It is checked to be valid base64url when created so that decoding cannot fail.
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+pub struct Base64Body(bytes::Bytes);
+
+impl Base64Body {
+    /// Encoded characters decoded at once, a multiple of 4
+    const CHUNK: usize = 4 * 1024;
+
+    pub fn encode(decoded: impl AsRef<[u8]>) -> Self {
+        use base64::Engine as _;
+        Self(InlineContent::B64.encode(decoded).into_bytes().into())
+    }
+
+    /// Fails when `encoded` is not unpadded base64url, without keeping the decoded content
+    pub fn from_encoded(encoded: impl Into<bytes::Bytes>) -> VconResult<Self> {
+        use base64::Engine as _;
+        let encoded = encoded.into();
+        let mut buffer = [0u8; Self::CHUNK / 4 * 3];
+        for chunk in encoded.chunks(Self::CHUNK) {
+            InlineContent::B64
+                .decode_slice(chunk, &mut buffer)
+                .map_err(|e| VconError::InvalidBody(e.to_string()))?;
+        }
+        Ok(Self(encoded))
+    }
+
+    /// Encoded body
+    pub fn as_str(&self) -> &str {
+        std::str::from_utf8(&self.0).expect("base64url is ascii")
+    }
+
+    /// Length of the decoded body
+    pub fn decoded_len(&self) -> usize {
+        let len = self.0.len();
+        len / 4 * 3 + (len % 4).saturating_sub(1)
+    }
+
+    pub fn decode(&self) -> Vec<u8> {
+        let mut decoded = Vec::with_capacity(self.decoded_len());
+        self.decode_to(&mut decoded)
+            .expect("writing to a Vec does not fail");
+        decoded
+    }
+
+    /// Writes the decoded body to `writer` without holding all of it in memory
+    pub fn decode_to(&self, mut writer: impl std::io::Write) -> std::io::Result<()> {
+        use base64::Engine as _;
+        let mut buffer = [0u8; Self::CHUNK / 4 * 3];
+        for chunk in self.0.chunks(Self::CHUNK) {
+            let len = InlineContent::B64
+                .decode_slice(chunk, &mut buffer)
+                .expect("checked when created");
+            writer.write_all(&buffer[..len])?;
+        }
+        Ok(())
+    }
+}
+
+/// `body` as found in a vCon, before its `encoding` is known
+#[cfg(feature = "json")]
+pub(crate) enum RawBody {
+    Text(String),
+    /// `json` encoded body embedded as a structured value
+    Json(serde_json::Value),
+}
+
+#[cfg(feature = "json")]
+impl RawBody {
+    pub(crate) fn into_inline(self, encoding: BodyEncoding) -> VconResult<InlineContent> {
+        Ok(match (encoding, self) {
+            (BodyEncoding::Base64Url, Self::Text(body)) => {
+                InlineContent::BinaryBase64Url(Base64Body::from_encoded(body)?)
+            }
+            (BodyEncoding::None, Self::Text(body)) => InlineContent::TextNone(body),
+            (BodyEncoding::Json, body) => match body {
+                Self::Text(body) => InlineContent::TextJson(body),
+                Self::Json(body) => InlineContent::Json(crate::JsonAnyValue(body)),
+            },
+            (_, Self::Json(_)) => {
+                return Err(VconError::InvalidBody(
+                    "body should be a string".to_string(),
+                ))
+            }
+        })
+    }
+}
+
+#[cfg(feature = "json")]
+impl<'de> serde::Deserialize<'de> for RawBody {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::value::{MapAccessDeserializer, SeqAccessDeserializer};
+        use serde::Deserialize as _;
+
+        struct RawBodyVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for RawBodyVisitor {
+            type Value = RawBody;
+
+            fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+                formatter.write_str("a body")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                Ok(RawBody::Text(v.to_string()))
+            }
+
+            // takes the string as is, without copying it
+            fn visit_string<E: serde::de::Error>(self, v: String) -> Result<Self::Value, E> {
+                Ok(RawBody::Text(v))
+            }
+
+            fn visit_bool<E: serde::de::Error>(self, v: bool) -> Result<Self::Value, E> {
+                Ok(RawBody::Json(v.into()))
+            }
+
+            fn visit_i64<E: serde::de::Error>(self, v: i64) -> Result<Self::Value, E> {
+                Ok(RawBody::Json(v.into()))
+            }
+
+            fn visit_u64<E: serde::de::Error>(self, v: u64) -> Result<Self::Value, E> {
+                Ok(RawBody::Json(v.into()))
+            }
+
+            fn visit_f64<E: serde::de::Error>(self, v: f64) -> Result<Self::Value, E> {
+                Ok(RawBody::Json(v.into()))
+            }
+
+            fn visit_unit<E: serde::de::Error>(self) -> Result<Self::Value, E> {
+                Ok(RawBody::Json(serde_json::Value::Null))
+            }
+
+            fn visit_seq<A: serde::de::SeqAccess<'de>>(
+                self,
+                seq: A,
+            ) -> Result<Self::Value, A::Error> {
+                serde_json::Value::deserialize(SeqAccessDeserializer::new(seq)).map(RawBody::Json)
+            }
+
+            fn visit_map<A: serde::de::MapAccess<'de>>(
+                self,
+                map: A,
+            ) -> Result<Self::Value, A::Error> {
+                serde_json::Value::deserialize(MapAccessDeserializer::new(map)).map(RawBody::Json)
+            }
+        }
+
+        deserializer.deserialize_any(RawBodyVisitor)
+    }
+}
+
 #[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
 pub enum BodyEncoding {
     Base64Url,
@@ -1,6 +1,7 @@
 use crate::signature::SignatureAlg;
-use crate::{InlineContent, Mime, Signature, Url};
+use crate::{InlineContent, Mime, Signature, Url, VconError, VconResult, VconStore};
 use derive_more::{From, Into};
+use std::borrow::Cow;
 
 #[derive(Debug, Clone, Hash, Eq, PartialEq)]
 #[cfg_attr(ser, derive(serde::Serialize), serde(untagged))]
@@ -26,63 +27,17 @@ impl<'de> serde::Deserialize<'de> for Content {
                 self,
                 mut map: A,
             ) -> Result<Self::Value, A::Error> {
-                use crate::body::BodyEncoding;
                 use serde::de::Error as _;
 
-                let (mut encoding, mut body, mut url, mut signature, mut alg) =
-                    (None, None, None, None, None);
-
-                while let Ok(Some((k, v))) = map.next_entry::<String, serde_json::Value>() {
-                    match k.as_str() {
-                        "encoding" => encoding = Some(v),
-                        "body" => body = Some(v),
-                        "url" => url = Some(v),
-                        "signature" => signature = Some(v),
-                        "alg" => alg = Some(v),
-                        _ => {}
+                let mut fields = ContentFields::default();
+                // other keys are only met when not flattened, and are skipped
+                while let Some(k) = map.next_key::<String>()? {
+                    if !fields.visit(&k, &mut map)? {
+                        map.next_value::<serde::de::IgnoredAny>()?;
                     }
                 }
-
-                Ok(if let Some((encoding, body)) = encoding.zip(body) {
-                    let encoding =
-                        <BodyEncoding as serde::Deserialize>::deserialize(encoding).unwrap();
-                    let inline_content = match encoding {
-                        BodyEncoding::Base64Url => {
-                            let body = <String as serde::Deserialize>::deserialize(body)
-                                .map_err(A::Error::custom)?;
-
-                            use base64::Engine as _;
-                            let body = InlineContent::B64
-                                .decode(body.as_bytes())
-                                .map_err(A::Error::custom)?;
-                            InlineContent::BinaryBase64Url(body)
-                        }
-                        BodyEncoding::None => {
-                            let body = <String as serde::Deserialize>::deserialize(body)
-                                .map_err(A::Error::custom)?;
-                            InlineContent::TextNone(body)
-                        }
-                        BodyEncoding::Json => {
-                            let body = <String as serde::Deserialize>::deserialize(body)
-                                .map_err(A::Error::custom)?;
-                            InlineContent::TextJson(body)
-                        }
-                    };
-                    Self::Value::Inline(inline_content)
-                } else if let Some(((url, signature), alg)) = url.zip(signature).zip(alg) {
-                    let url =
-                        <Url as serde::Deserialize>::deserialize(url).map_err(A::Error::custom)?;
-                    let alg = <SignatureAlg as serde::Deserialize>::deserialize(alg)
-                        .map_err(A::Error::custom)?;
-                    let signature = <String as serde::Deserialize>::deserialize(signature)
-                        .map_err(A::Error::custom)?;
-                    let signature =
-                        Signature::try_from((alg, signature)).map_err(A::Error::custom)?;
-                    Self::Value::UrlReferenced(UrlReferencedContent { url, signature })
-                } else {
-                    return Err(A::Error::custom(
-                        "Invalid Content, must be either Inline or UrlReferenced",
-                    ));
+                fields.build()?.ok_or_else(|| {
+                    A::Error::custom("Invalid Content, must be either Inline or UrlReferenced")
                 })
             }
 
@@ -95,7 +50,117 @@ impl<'de> serde::Deserialize<'de> for Content {
             }
         }
 
-        deserializer.deserialize_map(ContentVisitor)
+        // as a struct so that, when flattened, its fields are not seen by flattened siblings
+        deserializer.deserialize_struct("Content", Content::FIELDS, ContentVisitor)
+    }
+}
+
+impl Content {
+    /// Keys of inline and url referenced content
+    pub(crate) const FIELDS: &'static [&'static str] =
+        &["encoding", "body", "url", "signature", "alg"];
+
+    /// Decoded body of inline content
+    pub fn inline_bytes(&self) -> Option<Cow<'_, [u8]>> {
+        match self {
+            Self::Inline(inline) => Some(inline.bytes()),
+            Self::UrlReferenced(_) => None,
+        }
+    }
+
+    /// Decoded body, url referenced content being fetched from `store`
+    pub fn bytes(&self, store: &impl VconStore) -> VconResult<Cow<'_, [u8]>> {
+        match self {
+            Self::Inline(inline) => Ok(inline.bytes()),
+            Self::UrlReferenced(referenced) => referenced.fetch(store).map(Cow::Owned),
+        }
+    }
+
+    /// Writes the decoded body to `writer`, url referenced content being fetched from `store`.
+    ///
+    /// Unlike [Content::bytes], inline base64url bodies are never decoded as a whole.
+    pub fn write_to(
+        &self,
+        store: &impl VconStore,
+        mut writer: impl std::io::Write,
+    ) -> VconResult<()> {
+        match self {
+            Self::Inline(inline) => inline.write_to(writer)?,
+            Self::UrlReferenced(referenced) => writer.write_all(&referenced.fetch(store)?)?,
+        }
+        Ok(())
+    }
+}
+
+/// Keys of [Content] met while visiting a map, holding other keys as well
+#[cfg(json)]
+#[derive(Default)]
+pub(crate) struct ContentFields {
+    encoding: Option<crate::body::BodyEncoding>,
+    body: Option<crate::body::RawBody>,
+    url: Option<Url>,
+    signature: Option<String>,
+    alg: Option<SignatureAlg>,
+}
+
+#[cfg(json)]
+impl ContentFields {
+    /// Reads the value of `key` if it is one of [Content::FIELDS], returning whether it was
+    pub(crate) fn visit<'de, A: serde::de::MapAccess<'de>>(
+        &mut self,
+        key: &str,
+        map: &mut A,
+    ) -> Result<bool, A::Error> {
+        match key {
+            "encoding" => self.encoding = Some(map.next_value()?),
+            "body" => self.body = Some(map.next_value()?),
+            "url" => self.url = Some(map.next_value()?),
+            "signature" => self.signature = Some(map.next_value()?),
+            "alg" => self.alg = Some(map.next_value()?),
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    /// Keys met, as they were found, for objects which have no content
+    pub(crate) fn into_values(self) -> serde_json::Map<String, serde_json::Value> {
+        fn value(v: impl serde::Serialize) -> serde_json::Value {
+            serde_json::to_value(v).expect("serializable to JSON")
+        }
+        let body = self.body.map(|body| match body {
+            crate::body::RawBody::Text(body) => serde_json::Value::String(body),
+            crate::body::RawBody::Json(body) => body,
+        });
+        [
+            ("encoding", self.encoding.map(value)),
+            ("body", body),
+            ("url", self.url.map(value)),
+            ("signature", self.signature.map(serde_json::Value::String)),
+            ("alg", self.alg.map(value)),
+        ]
+        .into_iter()
+        .filter_map(|(key, value)| Some((key.to_string(), value?)))
+        .collect()
+    }
+
+    /// Content read, None when the keys of neither inline nor url referenced content were met
+    pub(crate) fn build<E: serde::de::Error>(self) -> Result<Option<Content>, E> {
+        Ok(
+            if let Some((encoding, body)) = self.encoding.zip(self.body) {
+                Some(Content::Inline(
+                    body.into_inline(encoding).map_err(E::custom)?,
+                ))
+            } else if let Some(((url, signature), alg)) = self.url.zip(self.signature).zip(self.alg)
+            {
+                let signature = Signature::try_from((alg, signature)).map_err(E::custom)?;
+                Some(Content::UrlReferenced(UrlReferencedContent {
+                    url,
+                    signature,
+                }))
+            } else {
+                None
+            },
+        )
     }
 }
 
@@ -108,6 +173,17 @@ pub struct UrlReferencedContent {
     pub signature: Signature,
 }
 
+impl UrlReferencedContent {
+    /// Fetches the content from `store` and verifies it against its signature
+    pub fn fetch(&self, store: &impl VconStore) -> VconResult<Vec<u8>> {
+        let content = store.fetch(&self.url)?;
+        if !self.signature.verify(&content) {
+            return Err(VconError::DigestMismatch(self.url.to_string()));
+        }
+        Ok(content)
+    }
+}
+
 /// Flatten at declaration-site
 #[derive(Default, Debug, Clone, Hash, Eq, PartialEq, From, Into)]
 #[cfg_attr(ser, derive(serde::Serialize, serde::Deserialize))]
@@ -27,7 +27,7 @@ use derive_more::{Deref, DerefMut, From, Into};
 /// # .unwrap(),
 /// # )}
 /// ```
-#[derive(Debug, Clone, Hash, Eq, PartialEq, From, Into, Deref, DerefMut)]
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Ord, PartialOrd, From, Into, Deref, DerefMut)]
 #[cfg_attr(ser, derive(serde::Serialize, serde::Deserialize), serde(transparent))]
 pub struct Date(#[cfg_attr(ser, serde(with = "time::serde::rfc3339"))] time::OffsetDateTime);
 
@@ -40,3 +40,17 @@ impl std::str::FromStr for Date {
         Ok(Self(date))
     }
 }
+
+impl Date {
+    /// Current UTC date
+    pub fn now() -> Self {
+        Self(time::OffsetDateTime::now_utc())
+    }
+
+    /// Date `secs` seconds later (earlier when negative), None when `secs` is not finite or the
+    /// date is out of range
+    pub fn checked_add_seconds(&self, secs: f64) -> Option<Self> {
+        let duration = time::Duration::checked_seconds_f64(secs)?;
+        self.0.checked_add(duration).map(Self)
+    }
+}
@@ -1,5 +1,8 @@
-use crate::{Content, ContentParameters, Date, PartyEvent, PartyIndex};
+use crate::{
+    Content, ContentParameters, Date, PartyEvent, PartyIndex, Vcon, VconError, VconResult,
+};
 use derive_more::{From, Into};
+use std::collections::BTreeMap;
 
 pub type DialogIndex = u32;
 
@@ -8,7 +11,8 @@ pub type DialogIndex = u32;
 /// See
 /// - https://ietf-wg-vcon.github.io/draft-ietf-vcon-vcon-container/draft-ietf-vcon-vcon-container.html#name-dialog-object
 #[derive(Debug, Clone, PartialEq, From, Into)]
-#[cfg_attr(ser, derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(ser, derive(serde::Serialize))]
+#[cfg_attr(cbor, derive(serde::Deserialize))]
 #[cfg_attr(feature = "builder", derive(derive_builder::Builder))]
 pub struct DialogObject {
     pub start: Date,
@@ -18,8 +22,163 @@ pub struct DialogObject {
     pub campaign: Option<String>,
     #[cfg_attr(ser, serde(skip_serializing_if = "Option::is_none"))]
     pub interaction: Option<String>,
+    /// Value of the SIP `Session-ID` header (RFC 7989)
+    #[cfg_attr(ser, serde(skip_serializing_if = "Option::is_none"))]
+    pub session_id: Option<String>,
+    /// Application the dialog took place in e.g. `zoom`
+    #[cfg_attr(ser, serde(skip_serializing_if = "Option::is_none"))]
+    pub application: Option<String>,
+    /// `Message-ID` of an email dialog, whose `originator` is the sender
+    #[cfg_attr(ser, serde(skip_serializing_if = "Option::is_none"))]
+    pub message_id: Option<String>,
+    /// Free form metadata
+    #[cfg(json)]
+    #[cfg_attr(json, serde(skip_serializing_if = "Option::is_none"))]
+    pub meta: Option<crate::JsonAnyValue>,
     #[cfg_attr(ser, serde(flatten))]
     pub dialog: Dialog,
+    #[cfg(json)]
+    #[cfg_attr(json, serde(flatten))]
+    pub extension_object: crate::JsonAnyValue,
+}
+
+/// Reads the keys of the object itself and of its [Content] as they come, the few others being
+/// split between its [Dialog] and its extension object once the `type` is known. Unlike flattening
+/// an internally tagged enum, this never buffers the whole object, hence the body.
+#[cfg(json)]
+impl<'de> serde::Deserialize<'de> for DialogObject {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct DialogObjectVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for DialogObjectVisitor {
+            type Value = DialogObject;
+
+            fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+                formatter.write_str("a dialog object")
+            }
+
+            fn visit_map<A: serde::de::MapAccess<'de>>(
+                self,
+                mut map: A,
+            ) -> Result<Self::Value, A::Error> {
+                use serde::de::Error as _;
+
+                let mut start = None;
+                let mut party_history = None;
+                let mut campaign = None;
+                let mut interaction = None;
+                let mut session_id = None;
+                let mut application = None;
+                let mut message_id = None;
+                let mut meta = None;
+                let mut content = crate::content::ContentFields::default();
+                let mut others = serde_json::Map::new();
+                while let Some(key) = map.next_key::<String>()? {
+                    match key.as_str() {
+                        "start" => start = Some(map.next_value()?),
+                        "party_history" => party_history = map.next_value()?,
+                        "campaign" => campaign = map.next_value()?,
+                        "interaction" => interaction = map.next_value()?,
+                        "session_id" => session_id = map.next_value()?,
+                        "application" => application = map.next_value()?,
+                        "message_id" => message_id = map.next_value()?,
+                        "meta" => meta = map.next_value()?,
+                        key if content.visit(key, &mut map)? => {}
+                        _ => {
+                            others.insert(key, map.next_value()?);
+                        }
+                    }
+                }
+
+                let typ = others
+                    .get("type")
+                    .and_then(|typ| typ.as_str())
+                    .map(str::to_string);
+                let typ = typ.as_deref();
+                let mut fields = serde_json::Map::new();
+                for field in ["type"]
+                    .iter()
+                    .chain(typ.map_or(&[][..], Dialog::fields_of))
+                {
+                    if let Some((key, value)) = others.remove_entry(*field) {
+                        fields.insert(key, value);
+                    }
+                }
+                let fields = serde_json::Value::Object(fields);
+                let dialog = match typ {
+                    Some(typ @ ("recording" | "text")) => {
+                        let media: MediaFields =
+                            serde_json::from_value(fields).map_err(A::Error::custom)?;
+                        let content = content.build()?.ok_or_else(|| {
+                            A::Error::custom(
+                                "Invalid Content, must be either Inline or UrlReferenced",
+                            )
+                        })?;
+                        media.into_dialog(typ == "text", content)
+                    }
+                    _ => {
+                        others.extend(content.into_values());
+                        serde_json::from_value(fields).map_err(A::Error::custom)?
+                    }
+                };
+
+                Ok(DialogObject {
+                    start: start.ok_or_else(|| A::Error::missing_field("start"))?,
+                    party_history,
+                    campaign,
+                    interaction,
+                    session_id,
+                    application,
+                    message_id,
+                    meta,
+                    dialog,
+                    extension_object: crate::JsonAnyValue(serde_json::Value::Object(others)),
+                })
+            }
+        }
+
+        deserializer.deserialize_map(DialogObjectVisitor)
+    }
+}
+
+/// Keys of [Dialog::Recording] and [Dialog::Text] but their content
+#[cfg(json)]
+#[derive(serde::Deserialize)]
+struct MediaFields {
+    duration: Option<Duration>,
+    parties: DialogParties,
+    originator: Option<PartyIndex>,
+    #[serde(flatten)]
+    content_parameters: ContentParameters,
+}
+
+#[cfg(json)]
+impl MediaFields {
+    fn into_dialog(self, text: bool, content: Content) -> Dialog {
+        let Self {
+            duration,
+            parties,
+            originator,
+            content_parameters,
+        } = self;
+        if text {
+            Dialog::Text {
+                duration,
+                parties,
+                originator,
+                content_parameters,
+                content,
+            }
+        } else {
+            Dialog::Recording {
+                duration,
+                parties,
+                originator,
+                content_parameters,
+                content,
+            }
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -61,10 +220,171 @@ pub enum Dialog {
         target_dialog: DialogIndex,
     },
     Incomplete {
-        disposition: String,
+        disposition: Disposition,
     },
 }
 
+/// Reason why an [Dialog::Incomplete] dialog failed
+///
+/// Values outside the ones defined by the draft are kept in [Disposition::Other] and rejected by
+/// [Disposition::validate].
+///
+/// See https://ietf-wg-vcon.github.io/draft-ietf-vcon-vcon-container/draft-ietf-vcon-vcon-container.html#name-disposition
+///
+/// # json example
+///
+/// ```rust
+/// # #[cfg(feature = "json")] {
+/// # use vcon_types::Disposition;
+/// # use serde_json::json;
+/// # vcon_types::expect_json_eq(
+/// Disposition::VoicemailNoMessage, // actual
+/// json!("voicemail-no-message"), // expected
+/// # );
+/// # vcon_types::expect_json_eq(
+/// Disposition::Other("rejected".to_string()), // actual
+/// json!("rejected"), // expected
+/// # );
+/// # }
+/// ```
+/// # cbor example
+///
+/// ```rust
+/// # #[cfg(feature = "cbor")] {
+/// # use vcon_types::Disposition;
+/// # use ciborium::cbor;
+/// # vcon_types::expect_cbor_eq(
+/// Disposition::HungUp, // actual
+/// cbor!("hung-up") // expected
+/// # .unwrap(),
+/// # )}
+/// ```
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+pub enum Disposition {
+    NoAnswer,
+    Congestion,
+    Failed,
+    Busy,
+    HungUp,
+    VoicemailNoMessage,
+    Other(String),
+}
+
+impl Disposition {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::NoAnswer => "no-answer",
+            Self::Congestion => "congestion",
+            Self::Failed => "failed",
+            Self::Busy => "busy",
+            Self::HungUp => "hung-up",
+            Self::VoicemailNoMessage => "voicemail-no-message",
+            Self::Other(other) => other,
+        }
+    }
+
+    /// Fails when the disposition is not one defined by the draft
+    pub fn validate(&self) -> VconResult<()> {
+        match self {
+            Self::Other(other) => Err(VconError::UnknownDisposition(other.clone())),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl From<&str> for Disposition {
+    fn from(s: &str) -> Self {
+        match s {
+            "no-answer" => Self::NoAnswer,
+            "congestion" => Self::Congestion,
+            "failed" => Self::Failed,
+            "busy" => Self::Busy,
+            "hung-up" => Self::HungUp,
+            "voicemail-no-message" => Self::VoicemailNoMessage,
+            other => Self::Other(other.to_string()),
+        }
+    }
+}
+
+impl std::fmt::Display for Disposition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(ser)]
+impl serde::Serialize for Disposition {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+#[cfg(ser)]
+impl<'de> serde::de::Deserialize<'de> for Disposition {
+    fn deserialize<D: serde::de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = <String as serde::Deserialize>::deserialize(deserializer)?;
+        Ok(s.as_str().into())
+    }
+}
+
+impl Dialog {
+    /// Keys of a dialog object defined by its `type`, but those of its [Content]
+    fn fields_of(typ: &str) -> &'static [&'static str] {
+        match typ {
+            "recording" | "text" => &["duration", "parties", "originator", "mimetype", "filename"],
+            "transfer" => &[
+                "transferee",
+                "transferor",
+                "transfer_target",
+                "original",
+                "consultation",
+                "target_dialog",
+            ],
+            "incomplete" => &["disposition"],
+            _ => &[],
+        }
+    }
+}
+
+impl DialogObject {
+    /// Disposition of an [Dialog::Incomplete] dialog
+    pub fn disposition(&self) -> Option<&Disposition> {
+        match &self.dialog {
+            Dialog::Incomplete { disposition } => Some(disposition),
+            _ => None,
+        }
+    }
+}
+
+impl Vcon {
+    /// Fails on the first incomplete dialog whose disposition is not defined by the draft
+    pub fn validate_dispositions(&self) -> VconResult<()> {
+        self.dialog
+            .iter()
+            .flatten()
+            .filter_map(DialogObject::disposition)
+            .try_for_each(Disposition::validate)
+    }
+
+    /// Number of incomplete dialogs per disposition
+    pub fn disposition_counts(&self) -> BTreeMap<Disposition, usize> {
+        disposition_counts(self.dialog.iter().flatten())
+    }
+}
+
+/// Number of incomplete dialogs per disposition, e.g. across all the dialogs of many vCons
+pub fn disposition_counts<'a>(
+    dialogs: impl IntoIterator<Item = &'a DialogObject>,
+) -> BTreeMap<Disposition, usize> {
+    dialogs
+        .into_iter()
+        .filter_map(DialogObject::disposition)
+        .fold(BTreeMap::new(), |mut counts, disposition| {
+            *counts.entry(disposition.clone()).or_default() += 1;
+            counts
+        })
+}
+
 #[derive(Debug, Clone, Hash, Eq, PartialEq)]
 #[cfg_attr(ser, derive(serde::Serialize, serde::Deserialize), serde(untagged))]
 pub enum DialogParties {
@@ -72,6 +392,36 @@ pub enum DialogParties {
     List(Vec<PartyIndex>),
 }
 
+impl DialogParties {
+    /// Every party of the dialog, in declaration order
+    pub fn indexes(&self) -> Vec<PartyIndex> {
+        match self {
+            Self::Index(index) => vec![*index],
+            Self::List(list) => list.clone(),
+        }
+    }
+}
+
+impl Dialog {
+    /// Parties of a [Dialog::Recording] or [Dialog::Text] dialog
+    pub fn parties(&self) -> Option<&DialogParties> {
+        match self {
+            Self::Recording { parties, .. } | Self::Text { parties, .. } => Some(parties),
+            _ => None,
+        }
+    }
+
+    /// Value of the `type` field
+    pub fn typ(&self) -> &'static str {
+        match self {
+            Self::Recording { .. } => "recording",
+            Self::Text { .. } => "text",
+            Self::Transfer { .. } => "transfer",
+            Self::Incomplete { .. } => "incomplete",
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(ser, derive(serde::Serialize, serde::Deserialize), serde(untagged))]
 pub enum Duration {
@@ -79,11 +429,49 @@ pub enum Duration {
     Float(f32),
 }
 
+impl Duration {
+    pub fn as_secs_f64(&self) -> f64 {
+        match self {
+            Self::Int(secs) => *secs as f64,
+            Self::Float(secs) => *secs as f64,
+        }
+    }
+
+    /// Duration as a [time::Duration], None when it is negative, not finite or too long to be
+    /// represented
+    pub fn to_time(&self) -> Option<time::Duration> {
+        let secs = self.as_secs_f64();
+        if secs < 0.0 {
+            return None;
+        }
+        time::Duration::checked_seconds_f64(secs)
+    }
+}
+
+impl DialogObject {
+    /// `start` + `duration` of a recording or text dialog, None when it has no `duration` or when
+    /// it is invalid (see [Duration::to_time]) or ends out of the range of dates
+    pub fn end(&self) -> Option<Date> {
+        let duration = match &self.dialog {
+            Dialog::Recording { duration, .. } | Dialog::Text { duration, .. } => duration.as_ref(),
+            _ => None,
+        }?;
+        self.start.checked_add(duration.to_time()?).map(Into::into)
+    }
+
+    /// Absolute date of an offset in seconds from `start` e.g. a transcript timing, None when it
+    /// is out of range (see [Date::checked_add_seconds])
+    pub fn at(&self, offset: f64) -> Option<Date> {
+        self.start.checked_add_seconds(offset)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use serde_json::json;
     use vcon_types::{
-        Content, ContentParameters, Dialog, DialogObject, DialogParties, Duration, InlineContent,
+        disposition_counts, Content, ContentParameters, Dialog, DialogObject, DialogParties,
+        Disposition, Duration, InlineContent,
     };
 
     #[test]
@@ -94,6 +482,10 @@ mod tests {
             party_history: Default::default(),
             campaign: Default::default(),
             interaction: Default::default(),
+            session_id: Default::default(),
+            application: Default::default(),
+            message_id: Default::default(),
+            meta: Default::default(),
             dialog: Dialog::Text {
                 duration: Some(Duration::Int(0)),
                 parties: DialogParties::List(vec![0, 1]),
@@ -104,7 +496,7 @@ mod tests {
                 originator: Default::default(),
                 content: Content::Inline(InlineContent::TextNone("Hi Bob".into())),
             },
-            // extension_object: Default::default(),
+            extension_object: Default::default(),
         };
         let actual_ser = serde_json::to_value(&actual).unwrap();
         let expected = json!({
@@ -120,4 +512,86 @@ mod tests {
         let deser = serde_json::from_value::<DialogObject>(expected).unwrap();
         assert_eq!(actual, deser);
     }
+
+    #[test]
+    fn json_incomplete() {
+        let incomplete = |disposition| {
+            serde_json::from_value::<DialogObject>(json!({
+                "start": "2022-09-23T23:24:59Z",
+                "type": "incomplete",
+                "disposition": disposition
+            }))
+            .unwrap()
+        };
+        let dialogs = [
+            incomplete("busy"),
+            incomplete("no-answer"),
+            incomplete("busy"),
+            incomplete("rejected"),
+        ];
+
+        assert_eq!(dialogs[0].disposition(), Some(&Disposition::Busy));
+        assert!(dialogs[0].disposition().unwrap().validate().is_ok());
+        assert!(dialogs[3].disposition().unwrap().validate().is_err());
+        assert_eq!(
+            serde_json::to_value(&dialogs[1]).unwrap()["disposition"],
+            json!("no-answer")
+        );
+
+        let counts = disposition_counts(&dialogs);
+        assert_eq!(counts[&Disposition::Busy], 2);
+        assert_eq!(counts[&Disposition::NoAnswer], 1);
+        assert_eq!(counts[&Disposition::Other("rejected".to_string())], 1);
+    }
+
+    #[test]
+    fn fields_should_be_the_serialized_keys() {
+        let media = || ContentParameters {
+            mime: Some("text/plain".to_string().into()),
+            filename: Some("hi.txt".to_string()),
+        };
+        let content = || Content::Inline(InlineContent::TextNone("Hi Bob".into()));
+        let dialogs = [
+            Dialog::Recording {
+                duration: Some(Duration::Int(1)),
+                parties: DialogParties::Index(0),
+                originator: Some(0),
+                content_parameters: media(),
+                content: content(),
+            },
+            Dialog::Text {
+                duration: Some(Duration::Int(1)),
+                parties: DialogParties::Index(0),
+                originator: Some(0),
+                content_parameters: media(),
+                content: content(),
+            },
+            Dialog::Transfer {
+                transferee: 0,
+                transferor: 1,
+                transfer_target: 2,
+                original: 0,
+                consultation: Some(1),
+                target_dialog: 2,
+            },
+            Dialog::Incomplete {
+                disposition: Disposition::Busy,
+            },
+        ];
+        for dialog in dialogs {
+            let serde_json::Value::Object(serialized) = serde_json::to_value(&dialog).unwrap()
+            else {
+                unreachable!()
+            };
+            let mut keys = serialized
+                .keys()
+                .map(String::as_str)
+                .filter(|key| !["type", "encoding", "body"].contains(key))
+                .collect::<Vec<_>>();
+            let mut fields = super::Dialog::fields_of(dialog.typ()).to_vec();
+            keys.sort_unstable();
+            fields.sort_unstable();
+            assert_eq!(keys, fields, "{}", dialog.typ());
+        }
+    }
 }
@@ -1,4 +1,72 @@
+use crate::{DialogIndex, Event, PartyIndex, Uuid};
+
 pub type VconResult<T> = Result<T, VconError>;
 
 #[derive(Debug, thiserror::Error)]
-pub enum VconError {}
+pub enum VconError {
+    #[error("Could not resolve referenced vCon: {0}")]
+    UnresolvedReference(String),
+    #[error(
+        "vCon {amended} is not a valid amendment of {original}: '{field}' was altered or removed"
+    )]
+    InvalidAmendment {
+        original: Uuid,
+        amended: Uuid,
+        field: &'static str,
+    },
+    #[error("Amendment chain loops back to vCon {0}")]
+    AmendmentCycle(Uuid),
+    #[error("Content fetched from {0} does not match its signature")]
+    DigestMismatch(String),
+    #[error("Group references loop back to vCon {0}")]
+    GroupCycle(Uuid),
+    #[error("Unknown dialog disposition '{0}'")]
+    UnknownDisposition(String),
+    #[error("Invalid transfer dialog {dialog}: {reason}")]
+    InvalidTransfer { dialog: DialogIndex, reason: String },
+    #[error("{path} references unknown {target} {index}")]
+    UnknownIndex {
+        path: String,
+        target: &'static str,
+        index: u32,
+    },
+    #[error("Invalid body: {0}")]
+    InvalidBody(String),
+    #[error("Invalid analysis: {0}")]
+    InvalidAnalysis(String),
+    #[error("Invalid captions at line {line}: {reason}")]
+    InvalidCaptions { line: usize, reason: String },
+    #[error("Invalid vCon at line {line}: {reason}")]
+    InvalidLine { line: usize, reason: String },
+    #[error("Unsupported critical extensions: {}", .0.join(", "))]
+    UnsupportedCriticalExtensions(Vec<String>),
+    #[error("Unsupported vCon version '{0}'")]
+    UnsupportedVersion(String),
+    #[error("Invalid vCon: {0}")]
+    InvalidVcon(String),
+    #[error("Invalid extension {path}: {reason}")]
+    InvalidExtension { path: String, reason: String },
+    #[error("Unknown field {0}")]
+    UnknownField(String),
+    #[error("{limit} of {actual} exceeds the limit of {max}")]
+    LimitExceeded {
+        limit: &'static str,
+        max: usize,
+        actual: usize,
+    },
+    #[error("Invalid search index: {0}")]
+    InvalidSearchIndex(String),
+    #[error("Storage error: {0}")]
+    Storage(String),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[cfg(feature = "sqlite")]
+    #[error(transparent)]
+    Sqlite(#[from] rusqlite::Error),
+    #[error("Invalid {event:?} event for party {party}: {reason}")]
+    InvalidPartyHistory {
+        party: PartyIndex,
+        event: Event,
+        reason: &'static str,
+    },
+}
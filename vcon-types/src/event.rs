@@ -13,7 +13,7 @@ pub struct PartyEvent {
     pub extension_object: crate::JsonAnyValue,
 }
 
-#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
 #[cfg_attr(
     ser,
     derive(serde::Serialize, serde::Deserialize),
@@ -0,0 +1,202 @@
+use crate::{
+    Analysis, Attachment, CivicAddress, DialogObject, GroupReference, JsonAnyValue, OrEmpty, Party,
+    PartyEvent, RedactedReference, SpeakerMapping, Vcon, VconError, VconResult,
+};
+use std::collections::BTreeSet;
+
+/// Typed value kept in the extension object of vCon objects under [VconExtension::NAME]
+///
+/// ```rust
+/// # use vcon_types::{Extensible, Vcon, VconExtension};
+/// struct CaseId;
+///
+/// impl VconExtension for CaseId {
+///     const NAME: &'static str = "x_case_id";
+///     type Value = String;
+/// }
+///
+/// # let mut vcon: Vcon = serde_json::from_str(include_str!("../examples/json/email-thread-text.json")).unwrap();
+/// vcon.set_extension::<CaseId>(&"C-42".to_string()).unwrap();
+/// assert_eq!(vcon.extension::<CaseId>().unwrap().as_deref(), Some("C-42"));
+/// ```
+pub trait VconExtension {
+    /// Key of the extension in the extension object
+    const NAME: &'static str;
+
+    type Value: serde::Serialize + serde::de::DeserializeOwned;
+}
+
+/// vCon objects holding an extension object, other keys of which are left untouched
+pub trait Extensible {
+    fn extension_object(&self) -> &JsonAnyValue;
+
+    fn extension_object_mut(&mut self) -> &mut JsonAnyValue;
+
+    /// Value of the extension `E`, None when absent
+    fn extension<E: VconExtension>(&self) -> VconResult<Option<E::Value>> {
+        self.extension_object()
+            .0
+            .get(E::NAME)
+            .map(<E::Value as serde::Deserialize>::deserialize)
+            .transpose()
+            .map_err(|e| invalid(E::NAME, e))
+    }
+
+    /// Sets the extension `E`, replacing any previous value
+    fn set_extension<E: VconExtension>(&mut self, value: &E::Value) -> VconResult<()> {
+        let value = serde_json::to_value(value).map_err(|e| invalid(E::NAME, e))?;
+        let extensions = &mut self.extension_object_mut().0;
+        if !extensions.is_object() {
+            *extensions = serde_json::Value::Object(Default::default());
+        }
+        extensions[E::NAME] = value;
+        Ok(())
+    }
+
+    /// Removes the extension `E`, returning whether it was present
+    fn remove_extension<E: VconExtension>(&mut self) -> bool {
+        match &mut self.extension_object_mut().0 {
+            serde_json::Value::Object(extensions) => extensions.remove(E::NAME).is_some(),
+            _ => false,
+        }
+    }
+}
+
+fn invalid(path: &str, e: serde_json::Error) -> VconError {
+    VconError::InvalidExtension {
+        path: path.to_string(),
+        reason: e.to_string(),
+    }
+}
+
+macro_rules! extensible {
+    ($($t:ty),*) => {
+        $(
+            impl Extensible for $t {
+                fn extension_object(&self) -> &JsonAnyValue {
+                    &self.extension_object
+                }
+
+                fn extension_object_mut(&mut self) -> &mut JsonAnyValue {
+                    &mut self.extension_object
+                }
+            }
+        )*
+    };
+}
+
+extensible!(
+    Vcon,
+    Party,
+    CivicAddress,
+    DialogObject,
+    PartyEvent,
+    Attachment,
+    Analysis,
+    RedactedReference,
+    GroupReference
+);
+
+/// Set of [VconExtension]s whose values are validated wherever they are found, other extensions
+/// being left untouched
+///
+/// The default registry knows [SpeakerMapping].
+pub struct ExtensionRegistry {
+    extensions: Vec<RegisteredExtension>,
+}
+
+struct RegisteredExtension {
+    name: &'static str,
+    validate: fn(&serde_json::Value) -> Result<(), serde_json::Error>,
+}
+
+impl Default for ExtensionRegistry {
+    fn default() -> Self {
+        let mut registry = Self::empty();
+        registry.register::<SpeakerMapping>();
+        registry
+    }
+}
+
+impl ExtensionRegistry {
+    /// Registry without any extension
+    pub fn empty() -> Self {
+        Self { extensions: vec![] }
+    }
+
+    /// Registers `E`, replacing any extension with the same name
+    pub fn register<E: VconExtension>(&mut self) -> &mut Self {
+        self.extensions.retain(|e| e.name != E::NAME);
+        self.extensions.push(RegisteredExtension {
+            name: E::NAME,
+            validate: |value| <E::Value as serde::Deserialize>::deserialize(value).map(|_| ()),
+        });
+        self
+    }
+
+    /// Names of the registered extensions, e.g. for [crate::ParseOptions::extensions]
+    pub fn names(&self) -> BTreeSet<String> {
+        self.extensions.iter().map(|e| e.name.to_string()).collect()
+    }
+
+    /// Checks that every registered extension found in `vcon` holds a valid value
+    pub fn validate(&self, vcon: &Vcon) -> VconResult<()> {
+        for (path, extension_object) in vcon.extension_objects() {
+            let serde_json::Value::Object(values) = &extension_object.0 else {
+                continue;
+            };
+            for extension in &self.extensions {
+                if let Some(value) = values.get(extension.name) {
+                    (extension.validate)(value)
+                        .map_err(|e| invalid(&path_of(&path, extension.name), e))?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Vcon {
+    /// Extension objects of the vCon and of its objects along with their path e.g. `parties[0]`,
+    /// the one of the vCon itself being empty
+    pub(crate) fn extension_objects(&self) -> Vec<(String, &JsonAnyValue)> {
+        let mut objects = vec![(String::new(), &self.extension_object)];
+        for (key, reference) in [("redacted", &self.redacted), ("amended", &self.amended)] {
+            if let Some(OrEmpty::Some(reference)) = reference {
+                objects.push((key.to_string(), &reference.extension_object));
+            }
+        }
+        for (i, reference) in self.group.iter().flatten().enumerate() {
+            objects.push((format!("group[{i}]"), &reference.extension_object));
+        }
+        for (i, party) in self.parties.iter().flatten().enumerate() {
+            objects.push((format!("parties[{i}]"), &party.extension_object));
+            if let Some(address) = &party.civic_address {
+                let path = format!("parties[{i}].civic_address");
+                objects.push((path, &address.extension_object));
+            }
+        }
+        for (i, object) in self.dialog.iter().flatten().enumerate() {
+            objects.push((format!("dialog[{i}]"), &object.extension_object));
+            for (j, event) in object.party_history.iter().flatten().enumerate() {
+                let path = format!("dialog[{i}].party_history[{j}]");
+                objects.push((path, &event.extension_object));
+            }
+        }
+        for (i, attachment) in self.attachments.iter().flatten().enumerate() {
+            objects.push((format!("attachments[{i}]"), &attachment.extension_object));
+        }
+        for (i, analysis) in self.analysis.iter().flatten().enumerate() {
+            objects.push((format!("analysis[{i}]"), &analysis.extension_object));
+        }
+        objects
+    }
+}
+
+/// Path of `key` in the object at `path`
+pub(crate) fn path_of(path: &str, key: &str) -> String {
+    match path {
+        "" => key.to_string(),
+        path => format!("{path}.{key}"),
+    }
+}
@@ -0,0 +1,223 @@
+use crate::{Dialog, DialogIndex, PartyIndex, Vcon, VconError, VconResult};
+use std::fmt::Write as _;
+
+/// Directed graph of how a conversation moved between dialogs through [Dialog::Transfer]
+///
+/// See https://ietf-wg-vcon.github.io/draft-ietf-vcon-vcon-container/draft-ietf-vcon-vcon-container.html#name-transfer
+#[derive(Debug, Clone, PartialEq)]
+pub struct CallFlow {
+    /// Every recording, text or incomplete dialog
+    pub nodes: Vec<FlowNode>,
+    /// Links between dialogs derived from transfers
+    pub edges: Vec<FlowEdge>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlowNode {
+    pub dialog: DialogIndex,
+    /// `type` of the dialog
+    pub typ: &'static str,
+    /// Display names of the parties of the dialog
+    pub parties: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlowEdge {
+    pub from: DialogIndex,
+    pub to: DialogIndex,
+    pub kind: FlowEdgeKind,
+    /// Index of the [Dialog::Transfer] this edge comes from
+    pub transfer: DialogIndex,
+    /// Display name of the party who initiated the transfer
+    pub transferor: String,
+}
+
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+pub enum FlowEdgeKind {
+    /// Transferee moved from the original dialog to the target one
+    Transfer(TransferKind),
+    /// Transferor talked to the transfer target before completing the transfer
+    Consultation,
+}
+
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+pub enum TransferKind {
+    /// Without consultation dialog
+    Blind,
+    /// With a consultation dialog
+    Attended,
+}
+
+impl Vcon {
+    /// Builds the graph of dialogs and transfers between them.
+    ///
+    /// Fails if a transfer references a dialog which does not exist or is neither a recording nor
+    /// a text, or a party which does not exist.
+    pub fn call_flow(&self) -> VconResult<CallFlow> {
+        let dialogs = self.dialog.as_deref().unwrap_or_default();
+        let party_name = |index: PartyIndex| {
+            self.parties
+                .as_ref()
+                .and_then(|parties| parties.get(index as usize))
+                .and_then(|p| p.display_name())
+                .map(ToString::to_string)
+                .unwrap_or_else(|| format!("party {index}"))
+        };
+
+        let mut flow = CallFlow {
+            nodes: vec![],
+            edges: vec![],
+        };
+        for (index, dialog) in dialogs.iter().enumerate() {
+            let index = index as DialogIndex;
+            let Dialog::Transfer {
+                transferee,
+                transferor,
+                transfer_target,
+                original,
+                consultation,
+                target_dialog,
+            } = &dialog.dialog
+            else {
+                flow.nodes.push(FlowNode {
+                    dialog: index,
+                    typ: dialog.dialog.typ(),
+                    parties: dialog
+                        .dialog
+                        .parties()
+                        .map(|p| p.indexes().into_iter().map(party_name).collect())
+                        .unwrap_or_default(),
+                });
+                continue;
+            };
+
+            let invalid = |reason: String| VconError::InvalidTransfer {
+                dialog: index,
+                reason,
+            };
+            for party in [transferee, transferor, transfer_target] {
+                if self
+                    .parties
+                    .as_ref()
+                    .is_some_and(|p| *party as usize >= p.len())
+                {
+                    return Err(invalid(format!("unknown party {party}")));
+                }
+            }
+            for referenced in [Some(original), consultation.as_ref(), Some(target_dialog)]
+                .into_iter()
+                .flatten()
+            {
+                match dialogs.get(*referenced as usize).map(|d| &d.dialog) {
+                    Some(Dialog::Recording { .. } | Dialog::Text { .. }) => {}
+                    Some(other) => {
+                        return Err(invalid(format!(
+                            "dialog {referenced} is a {} whereas a recording or text is expected",
+                            other.typ()
+                        )))
+                    }
+                    None => return Err(invalid(format!("unknown dialog {referenced}"))),
+                }
+            }
+
+            let edge = |from, to, kind| FlowEdge {
+                from,
+                to,
+                kind,
+                transfer: index,
+                transferor: party_name(*transferor),
+            };
+            let kind = match consultation {
+                Some(consultation) => {
+                    flow.edges
+                        .push(edge(*original, *consultation, FlowEdgeKind::Consultation));
+                    TransferKind::Attended
+                }
+                None => TransferKind::Blind,
+            };
+            flow.edges.push(edge(
+                *original,
+                *target_dialog,
+                FlowEdgeKind::Transfer(kind),
+            ));
+        }
+        Ok(flow)
+    }
+}
+
+impl FlowNode {
+    fn label(&self) -> String {
+        match self.parties.as_slice() {
+            [] => format!("{}: {}", self.dialog, self.typ),
+            parties => format!("{}: {} ({})", self.dialog, self.typ, parties.join(", ")),
+        }
+    }
+}
+
+impl FlowEdge {
+    fn label(&self) -> String {
+        match self.kind {
+            FlowEdgeKind::Transfer(TransferKind::Blind) => {
+                format!("blind transfer by {}", self.transferor)
+            }
+            FlowEdgeKind::Transfer(TransferKind::Attended) => {
+                format!("attended transfer by {}", self.transferor)
+            }
+            FlowEdgeKind::Consultation => format!("consultation by {}", self.transferor),
+        }
+    }
+}
+
+impl CallFlow {
+    /// Graphviz DOT representation
+    pub fn to_dot(&self) -> String {
+        let escape = |s: String| s.replace('\\', "\\\\").replace('"', "\\\"");
+        let mut dot = String::from("digraph call_flow {\n");
+        for node in &self.nodes {
+            let _ = writeln!(
+                dot,
+                "  d{} [label=\"{}\"];",
+                node.dialog,
+                escape(node.label())
+            );
+        }
+        for edge in &self.edges {
+            let style = match edge.kind {
+                FlowEdgeKind::Consultation => ", style=dashed",
+                FlowEdgeKind::Transfer(_) => "",
+            };
+            let _ = writeln!(
+                dot,
+                "  d{} -> d{} [label=\"{}\"{style}];",
+                edge.from,
+                edge.to,
+                escape(edge.label())
+            );
+        }
+        dot.push('}');
+        dot
+    }
+
+    /// Mermaid flowchart representation
+    pub fn to_mermaid(&self) -> String {
+        let escape = |s: String| s.replace('"', "#quot;").replace('|', "#124;");
+        let mut mermaid = String::from("flowchart LR\n");
+        for node in &self.nodes {
+            let _ = writeln!(mermaid, "  d{}[\"{}\"]", node.dialog, escape(node.label()));
+        }
+        for edge in &self.edges {
+            let arrow = match edge.kind {
+                FlowEdgeKind::Consultation => "-.->",
+                FlowEdgeKind::Transfer(_) => "-->",
+            };
+            let _ = writeln!(
+                mermaid,
+                "  d{} {arrow}|\"{}\"| d{}",
+                edge.from,
+                escape(edge.label()),
+                edge.to
+            );
+        }
+        mermaid
+    }
+}
@@ -0,0 +1,78 @@
+use crate::{Uuid, Vcon, VconError, VconResolver, VconResult};
+use std::collections::HashSet;
+
+/// Flattened graph of the vCons linked from a root vCon through `group`
+///
+/// See https://ietf-wg-vcon.github.io/draft-ietf-vcon-vcon-container/draft-ietf-vcon-vcon-container.html#name-group
+#[derive(Debug, Clone, PartialEq)]
+pub struct VconGroup {
+    /// Every vCon of the graph, root first then in depth-first order. A vCon referenced from
+    /// multiple places appears only once.
+    pub vcons: Vec<Vcon>,
+    /// `(parent, child)` links, `child` being referenced in the `group` of `parent`
+    pub links: Vec<(Uuid, Uuid)>,
+}
+
+impl VconGroup {
+    /// The vCon the group was resolved from
+    pub fn root(&self) -> &Vcon {
+        &self.vcons[0]
+    }
+
+    pub fn get(&self, uuid: &Uuid) -> Option<&Vcon> {
+        self.vcons.iter().find(|v| &v.uuid == uuid)
+    }
+
+    /// vCons directly referenced in the `group` of the given one
+    pub fn members<'a>(&'a self, uuid: &'a Uuid) -> impl Iterator<Item = &'a Vcon> + 'a {
+        self.links
+            .iter()
+            .filter(move |(parent, _)| parent == uuid)
+            .filter_map(|(_, child)| self.get(child))
+    }
+}
+
+impl Vcon {
+    /// Recursively loads the vCons referenced in `group`.
+    ///
+    /// Url references are verified against their signature by the resolver (see
+    /// [crate::VconStore]). Fails if a vCon (transitively) references itself.
+    pub fn resolve_group(&self, resolver: &impl VconResolver) -> VconResult<VconGroup> {
+        let mut group = VconGroup {
+            vcons: vec![],
+            links: vec![],
+        };
+        let mut visited = HashSet::new();
+        let mut path = vec![];
+        visit(self.clone(), resolver, &mut path, &mut visited, &mut group)?;
+        Ok(group)
+    }
+}
+
+fn visit(
+    vcon: Vcon,
+    resolver: &impl VconResolver,
+    path: &mut Vec<Uuid>,
+    visited: &mut HashSet<Uuid>,
+    group: &mut VconGroup,
+) -> VconResult<()> {
+    let uuid = vcon.uuid.clone();
+    let references = vcon.group.clone().unwrap_or_default();
+    visited.insert(uuid.clone());
+    group.vcons.push(vcon);
+    path.push(uuid.clone());
+
+    for reference in &references {
+        let member = resolver.resolve(&reference.vcon_reference)?;
+        if path.contains(&member.uuid) {
+            return Err(VconError::GroupCycle(member.uuid));
+        }
+        group.links.push((uuid.clone(), member.uuid.clone()));
+        if !visited.contains(&member.uuid) {
+            visit(member, resolver, path, visited, group)?;
+        }
+    }
+
+    path.pop();
+    Ok(())
+}
@@ -4,6 +4,7 @@
 #![allow(dead_code)]
 
 mod address;
+mod amendment;
 mod analysis;
 mod attachment;
 mod body;
@@ -14,12 +15,33 @@ mod dialog;
 mod doc;
 mod error;
 mod event;
+#[cfg(feature = "json")]
+mod extension;
+mod flow;
+mod group;
+mod migration;
 mod mime;
+#[cfg(feature = "json")]
+mod ndjson;
+#[cfg(feature = "json")]
+mod parse;
 mod party;
+mod presence;
+mod redaction;
 mod reference;
+#[cfg(ser)]
+mod repository;
+mod resolver;
+mod search;
 mod signature;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+mod timeline;
+#[cfg(feature = "transcode")]
+mod transcode;
 mod url;
 mod uuid;
+mod validation;
 mod version;
 
 #[cfg(all(feature = "doctest", feature = "cbor"))]
@@ -27,23 +49,48 @@ pub use doc::expect_cbor_eq;
 #[cfg(all(feature = "doctest", feature = "json"))]
 pub use doc::expect_json_eq;
 
+#[cfg(feature = "json")]
+pub use extension::{Extensible, ExtensionRegistry, VconExtension};
+#[cfg(all(feature = "json", feature = "tokio"))]
+pub use ndjson::{AsyncVconReader, AsyncVconWriter};
+#[cfg(feature = "json")]
+pub use ndjson::{Compression, ErrorPolicy, VconReader, VconWriter};
+#[cfg(feature = "json")]
+pub use parse::{ParseOptions, UnknownFields};
+#[cfg(ser)]
+pub use repository::{StorageFormat, VconRepository};
+#[cfg(feature = "sqlite")]
+pub use sqlite::{Migration, SqliteStore};
+
 pub use {
     address::CivicAddress,
-    analysis::Analysis,
+    analysis::{
+        deepgram, Analysis, AnalysisDecoder, AnalysisRegistry, ConversationMetrics, PartyMetrics,
+        SpeakerMapping, Transcript, TranscriptSegment, TranscriptWord,
+    },
     attachment::Attachment,
-    body::InlineContent,
+    body::{Base64Body, BodyEncoding, InlineContent, BASE_64_URL_TAG},
     content::{Content, ContentParameters, UrlReferencedContent},
     date::Date,
-    dialog::{Dialog, DialogIndex, DialogObject, DialogParties, Duration},
+    dialog::{
+        disposition_counts, Dialog, DialogIndex, DialogObject, DialogParties, Disposition, Duration,
+    },
     error::{VconError, VconResult},
     event::{Event, PartyEvent},
+    flow::{CallFlow, FlowEdge, FlowEdgeKind, FlowNode, TransferKind},
+    group::VconGroup,
+    migration::LossyConversion,
     mime::Mime,
     party::Party,
-    reference::{RedactedReference, VconReference},
+    presence::{Interval, PartyPresence},
+    reference::{GroupReference, RedactedReference, VconReference},
+    resolver::{VconResolver, VconStore},
+    search::{HitWord, SearchHit, SearchIndex, SearchQuery, SearchSource},
     signature::Signature,
+    timeline::{TimelineEntry, TimelineEntryKind},
     url::Url,
     uuid::Uuid,
-    version::VconVersion,
+    version::{VconVersion, Version},
 };
 
 #[cfg(feature = "json")]
@@ -74,7 +121,7 @@ impl std::hash::Hash for CborAnyValue {
 #[cfg(all(feature = "cbor", feature = "json"))]
 compile_error!("feature \"cbor\" and feature \"json\" cannot be enabled at the same time");
 
-type PartyIndex = u32;
+pub type PartyIndex = u32;
 
 #[derive(Debug, Clone, PartialEq, derive_more::From, derive_more::Into)]
 #[cfg_attr(ser, derive(serde::Serialize, serde::Deserialize))]
@@ -92,14 +139,14 @@ pub struct Vcon {
     pub updated_at: Option<Date>,
     #[cfg_attr(ser, serde(skip_serializing_if = "Option::is_none"))]
     pub redacted: Option<OrEmpty<RedactedReference>>,
-    // FIXME: typo in draft
+    /// Read as `ammended` too, the spelling of draft 0.0.1, but always written `amended`
     #[cfg_attr(
         ser,
-        serde(rename = "ammended", skip_serializing_if = "Option::is_none")
+        serde(alias = "ammended", skip_serializing_if = "Option::is_none")
     )]
     pub amended: Option<OrEmpty<RedactedReference>>,
     #[cfg_attr(ser, serde(skip_serializing_if = "Option::is_none"))]
-    pub group: Option<Vec<VconReference>>,
+    pub group: Option<Vec<GroupReference>>,
     #[cfg_attr(ser, serde(skip_serializing_if = "Option::is_none"))]
     pub parties: Option<Vec<Party>>,
     #[cfg_attr(ser, serde(skip_serializing_if = "Option::is_none"))]
@@ -108,6 +155,15 @@ pub struct Vcon {
     pub attachments: Option<Vec<Attachment>>,
     #[cfg_attr(ser, serde(skip_serializing_if = "Option::is_none"))]
     pub analysis: Option<Vec<Analysis>>,
+    /// Names of the extensions used in this vCon
+    #[cfg_attr(ser, serde(skip_serializing_if = "Option::is_none"))]
+    pub extensions: Option<Vec<String>>,
+    /// Names of the extensions a consumer must support to process this vCon
+    #[cfg_attr(
+        ser,
+        serde(alias = "must_support", skip_serializing_if = "Option::is_none")
+    )]
+    pub critical: Option<Vec<String>>,
     #[cfg(json)]
     #[cfg_attr(json, serde(flatten))]
     pub extension_object: crate::JsonAnyValue,
@@ -0,0 +1,64 @@
+use crate::{Vcon, VconResult, Version};
+
+/// Value dropped by [Vcon::migrate] as the target draft has no room for it
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct LossyConversion {
+    /// Path of the value e.g. `parties[0].sip`
+    pub path: String,
+    pub reason: String,
+}
+
+impl Vcon {
+    /// Moves this vCon to the draft `to`, reporting the values which could not be kept.
+    ///
+    /// Upgrading is lossless as later drafts only add fields, legacy spellings being read by the
+    /// parser whatever the declared version. Downgrading drops the fields the target draft does not
+    /// define. Fails when the declared version is not supported.
+    pub fn migrate(&mut self, to: Version) -> VconResult<Vec<LossyConversion>> {
+        let from = self.version.version()?;
+        let lossy = self
+            .take_newer_fields(to)
+            .into_iter()
+            .map(|path| LossyConversion {
+                path,
+                reason: format!("not defined by draft {to}, migrating from {from}"),
+            })
+            .collect();
+        self.version = to.into();
+        Ok(lossy)
+    }
+
+    /// Clears the fields introduced by drafts later than `version`, returning their paths
+    pub(crate) fn take_newer_fields(&mut self, version: Version) -> Vec<String> {
+        let mut taken = vec![];
+        if version >= Version::V0_3_0 {
+            return taken;
+        }
+        take(&mut taken, &mut self.extensions, || "extensions".into());
+        take(&mut taken, &mut self.critical, || "critical".into());
+        for (i, party) in self.parties.iter_mut().flatten().enumerate() {
+            let path = |field: &str| format!("parties[{i}].{field}");
+            take(&mut taken, &mut party.sip, || path("sip"));
+            take(&mut taken, &mut party.did, || path("did"));
+            #[cfg(json)]
+            take(&mut taken, &mut party.jcard, || path("jCard"));
+            take(&mut taken, &mut party.timezone, || path("timezone"));
+            take(&mut taken, &mut party.contact_list, || path("contact_list"));
+        }
+        for (i, object) in self.dialog.iter_mut().flatten().enumerate() {
+            let path = |field: &str| format!("dialog[{i}].{field}");
+            take(&mut taken, &mut object.session_id, || path("session_id"));
+            take(&mut taken, &mut object.application, || path("application"));
+            take(&mut taken, &mut object.message_id, || path("message_id"));
+            #[cfg(json)]
+            take(&mut taken, &mut object.meta, || path("meta"));
+        }
+        taken
+    }
+}
+
+fn take<T>(taken: &mut Vec<String>, field: &mut Option<T>, path: impl FnOnce() -> String) {
+    if field.take().is_some() {
+        taken.push(path());
+    }
+}
@@ -0,0 +1,473 @@
+use crate::{ParseOptions, Vcon, VconError, VconResult};
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+
+/// Compression of a stream of vCons
+///
+/// Readers detect it from the first bytes of the stream, writers have to be told.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub enum Compression {
+    #[default]
+    None,
+    #[cfg(feature = "gzip")]
+    Gzip,
+    #[cfg(feature = "zstd")]
+    Zstd,
+}
+
+impl Compression {
+    /// Compression of a stream starting with `header`
+    fn detect(header: &[u8]) -> Self {
+        #[cfg(feature = "gzip")]
+        if header.starts_with(&[0x1f, 0x8b]) {
+            return Self::Gzip;
+        }
+        #[cfg(feature = "zstd")]
+        if header.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            return Self::Zstd;
+        }
+        let _ = header;
+        Self::None
+    }
+}
+
+/// What a reader does after a line which is not a valid vCon
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub enum ErrorPolicy {
+    /// Yields the error then carries on with the next line
+    #[default]
+    Continue,
+    /// Yields the error then stops
+    Abort,
+}
+
+/// Line bookkeeping shared by the sync and async readers
+#[derive(Debug, Default)]
+struct Lines {
+    line: usize,
+    policy: ErrorPolicy,
+    options: ParseOptions,
+    done: bool,
+}
+
+impl Lines {
+    /// vCon on the next line, None when the line is blank
+    fn parse(&mut self, line: &[u8]) -> Option<VconResult<Vcon>> {
+        self.line += 1;
+        let line = line.trim_ascii();
+        if line.is_empty() {
+            return None;
+        }
+        Some(Vcon::parse_json(line, &self.options).map_err(|e| {
+            self.done |= self.policy == ErrorPolicy::Abort;
+            let reason = match e {
+                VconError::InvalidVcon(reason) => reason,
+                e => e.to_string(),
+            };
+            VconError::InvalidLine {
+                line: self.line,
+                reason,
+            }
+        }))
+    }
+
+    /// Error of the next line, longer than the size limit
+    fn too_long(&mut self) -> VconResult<Vcon> {
+        self.line += 1;
+        self.done |= self.policy == ErrorPolicy::Abort;
+        Err(VconError::InvalidLine {
+            line: self.line,
+            reason: format!("size exceeds the limit of {}", self.options.max_size),
+        })
+    }
+
+    /// Most bytes a line within the size limit is read with, its newline included
+    fn max_read(&self) -> u64 {
+        self.options.max_size as u64 + 1
+    }
+
+    fn fail(&mut self, e: std::io::Error) -> Option<VconResult<Vcon>> {
+        self.done = true;
+        Some(Err(e.into()))
+    }
+}
+
+/// Reads newline-delimited JSON vCons one by one, transparently decompressing them.
+///
+/// Lines which are not a valid vCon, or exceed the [ParseOptions] (the default ones unless
+/// [VconReader::with_options]), are yielded as [VconError::InvalidLine] with their 1-based line
+/// number, blank lines being skipped, invalid UTF-8 included. Lines longer than `max_size` are
+/// skipped without being kept in memory. I/O errors end the stream.
+///
+/// ```rust
+/// # use vcon_types::VconReader;
+/// let ndjson = "{\"vcon\":\"0.0.1\",\"uuid\":\"01928e10-193e-8231-b9a2-279e0d16bc46\"}\nnot a vCon\n";
+/// let read = VconReader::new(ndjson.as_bytes()).unwrap().collect::<Vec<_>>();
+/// assert!(read[0].is_ok());
+/// assert!(read[1].as_ref().unwrap_err().to_string().starts_with("Invalid vCon at line 2"));
+/// ```
+pub struct VconReader<R: Read> {
+    decoder: Decoder<R>,
+    buffer: Vec<u8>,
+    lines: Lines,
+}
+
+enum Decoder<R: Read> {
+    Plain(BufReader<R>),
+    #[cfg(feature = "gzip")]
+    Gzip(BufReader<flate2::bufread::MultiGzDecoder<BufReader<R>>>),
+    #[cfg(feature = "zstd")]
+    Zstd(BufReader<zstd::Decoder<'static, BufReader<R>>>),
+}
+
+impl<R: Read> VconReader<R> {
+    /// Fails when the beginning of the stream cannot be read to detect its [Compression]
+    pub fn new(reader: R) -> VconResult<Self> {
+        let mut reader = BufReader::new(reader);
+        let decoder = match Compression::detect(reader.fill_buf()?) {
+            Compression::None => Decoder::Plain(reader),
+            #[cfg(feature = "gzip")]
+            Compression::Gzip => {
+                Decoder::Gzip(BufReader::new(flate2::bufread::MultiGzDecoder::new(reader)))
+            }
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => Decoder::Zstd(BufReader::new(zstd::Decoder::with_buffer(reader)?)),
+        };
+        Ok(Self {
+            decoder,
+            buffer: Vec::new(),
+            lines: Lines::default(),
+        })
+    }
+
+    pub fn on_error(mut self, policy: ErrorPolicy) -> Self {
+        self.lines.policy = policy;
+        self
+    }
+
+    /// Limits each line is parsed within
+    pub fn with_options(mut self, options: ParseOptions) -> Self {
+        self.lines.options = options;
+        self
+    }
+
+    /// Number of lines read so far
+    pub fn line(&self) -> usize {
+        self.lines.line
+    }
+
+    /// Reads the next line as bytes, so that invalid UTF-8 is reported as an invalid line
+    fn read_line(&mut self) -> std::io::Result<Line> {
+        let max = self.lines.max_read();
+        match &mut self.decoder {
+            Decoder::Plain(r) => read_line(r, &mut self.buffer, max),
+            #[cfg(feature = "gzip")]
+            Decoder::Gzip(r) => read_line(r, &mut self.buffer, max),
+            #[cfg(feature = "zstd")]
+            Decoder::Zstd(r) => read_line(r, &mut self.buffer, max),
+        }
+    }
+}
+
+/// Outcome of reading a line
+enum Line {
+    Read,
+    TooLong,
+    End,
+}
+
+/// Reads a line of at most `max` bytes into `buffer`, skipping a longer one
+fn read_line(reader: &mut impl BufRead, buffer: &mut Vec<u8>, max: u64) -> std::io::Result<Line> {
+    buffer.clear();
+    if reader.by_ref().take(max).read_until(b'\n', buffer)? == 0 {
+        return Ok(Line::End);
+    }
+    if (buffer.len() as u64) < max || buffer.ends_with(b"\n") {
+        return Ok(Line::Read);
+    }
+    loop {
+        let available = reader.fill_buf()?;
+        let (consumed, end) = match available.iter().position(|b| *b == b'\n') {
+            Some(newline) => (newline + 1, true),
+            None => (available.len(), available.is_empty()),
+        };
+        reader.consume(consumed);
+        if end {
+            return Ok(Line::TooLong);
+        }
+    }
+}
+
+impl<R: Read> Iterator for VconReader<R> {
+    type Item = VconResult<Vcon>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.lines.done {
+            match self.read_line() {
+                Ok(Line::End) => self.lines.done = true,
+                Ok(Line::TooLong) => return Some(self.lines.too_long()),
+                Ok(Line::Read) => {
+                    if let Some(vcon) = self.lines.parse(&self.buffer) {
+                        return Some(vcon);
+                    }
+                }
+                Err(e) => return self.lines.fail(e),
+            }
+        }
+        None
+    }
+}
+
+/// Writes vCons as newline-delimited JSON, optionally compressed.
+///
+/// [VconWriter::finish] has to be called for everything to be written.
+pub struct VconWriter<W: Write> {
+    encoder: Encoder<W>,
+}
+
+enum Encoder<W: Write> {
+    Plain(BufWriter<W>),
+    #[cfg(feature = "gzip")]
+    Gzip(flate2::write::GzEncoder<BufWriter<W>>),
+    #[cfg(feature = "zstd")]
+    Zstd(zstd::Encoder<'static, BufWriter<W>>),
+}
+
+impl<W: Write> VconWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            encoder: Encoder::Plain(BufWriter::new(writer)),
+        }
+    }
+
+    pub fn with_compression(writer: W, compression: Compression) -> VconResult<Self> {
+        let writer = BufWriter::new(writer);
+        let encoder = match compression {
+            Compression::None => Encoder::Plain(writer),
+            #[cfg(feature = "gzip")]
+            Compression::Gzip => Encoder::Gzip(flate2::write::GzEncoder::new(
+                writer,
+                flate2::Compression::default(),
+            )),
+            #[cfg(feature = "zstd")]
+            Compression::Zstd => Encoder::Zstd(zstd::Encoder::new(writer, 0)?),
+        };
+        Ok(Self { encoder })
+    }
+
+    // a single variant without compression features
+    #[allow(clippy::infallible_destructuring_match)]
+    pub fn write(&mut self, vcon: &Vcon) -> VconResult<()> {
+        let writer: &mut dyn Write = match &mut self.encoder {
+            Encoder::Plain(w) => w,
+            #[cfg(feature = "gzip")]
+            Encoder::Gzip(w) => w,
+            #[cfg(feature = "zstd")]
+            Encoder::Zstd(w) => w,
+        };
+        serde_json::to_writer(&mut *writer, vcon).map_err(std::io::Error::from)?;
+        writer.write_all(b"\n")?;
+        Ok(())
+    }
+
+    /// Flushes what is buffered and ends the compressed stream, if any
+    #[allow(clippy::infallible_destructuring_match)]
+    pub fn finish(self) -> VconResult<W> {
+        let writer = match self.encoder {
+            Encoder::Plain(w) => w,
+            #[cfg(feature = "gzip")]
+            Encoder::Gzip(w) => w.finish()?,
+            #[cfg(feature = "zstd")]
+            Encoder::Zstd(w) => w.finish()?,
+        };
+        Ok(writer.into_inner().map_err(|e| e.into_error())?)
+    }
+}
+
+#[cfg(feature = "tokio")]
+pub use self::r#async::{AsyncVconReader, AsyncVconWriter};
+
+#[cfg(feature = "tokio")]
+mod r#async {
+    use super::{Compression, ErrorPolicy, Line, Lines};
+    use crate::ParseOptions;
+    use crate::{Vcon, VconResult};
+    use tokio::io::{
+        AsyncBufRead, AsyncBufReadExt as _, AsyncRead, AsyncReadExt as _, AsyncWrite,
+        AsyncWriteExt as _, BufReader,
+    };
+
+    /// Async counterpart of [super::VconReader]
+    pub struct AsyncVconReader<R: AsyncRead + Unpin> {
+        decoder: Decoder<R>,
+        buffer: Vec<u8>,
+        lines: Lines,
+    }
+
+    enum Decoder<R: AsyncRead + Unpin> {
+        Plain(BufReader<R>),
+        #[cfg(feature = "gzip")]
+        Gzip(BufReader<async_compression::tokio::bufread::GzipDecoder<BufReader<R>>>),
+        #[cfg(feature = "zstd")]
+        Zstd(BufReader<async_compression::tokio::bufread::ZstdDecoder<BufReader<R>>>),
+    }
+
+    impl<R: AsyncRead + Unpin> AsyncVconReader<R> {
+        /// Fails when the beginning of the stream cannot be read to detect its [Compression]
+        pub async fn new(reader: R) -> VconResult<Self> {
+            let mut reader = BufReader::new(reader);
+            let decoder = match Compression::detect(reader.fill_buf().await?) {
+                Compression::None => Decoder::Plain(reader),
+                #[cfg(feature = "gzip")]
+                Compression::Gzip => {
+                    let mut decoder = async_compression::tokio::bufread::GzipDecoder::new(reader);
+                    decoder.multiple_members(true);
+                    Decoder::Gzip(BufReader::new(decoder))
+                }
+                #[cfg(feature = "zstd")]
+                Compression::Zstd => Decoder::Zstd(BufReader::new(
+                    async_compression::tokio::bufread::ZstdDecoder::new(reader),
+                )),
+            };
+            Ok(Self {
+                decoder,
+                buffer: Vec::new(),
+                lines: Lines::default(),
+            })
+        }
+
+        pub fn on_error(mut self, policy: ErrorPolicy) -> Self {
+            self.lines.policy = policy;
+            self
+        }
+
+        /// Limits each line is parsed within
+        pub fn with_options(mut self, options: ParseOptions) -> Self {
+            self.lines.options = options;
+            self
+        }
+
+        /// Number of lines read so far
+        pub fn line(&self) -> usize {
+            self.lines.line
+        }
+
+        /// Next vCon or invalid line, None at the end of the stream
+        pub async fn next(&mut self) -> Option<VconResult<Vcon>> {
+            while !self.lines.done {
+                let max = self.lines.max_read();
+                let read = match &mut self.decoder {
+                    Decoder::Plain(r) => read_line(r, &mut self.buffer, max).await,
+                    #[cfg(feature = "gzip")]
+                    Decoder::Gzip(r) => read_line(r, &mut self.buffer, max).await,
+                    #[cfg(feature = "zstd")]
+                    Decoder::Zstd(r) => read_line(r, &mut self.buffer, max).await,
+                };
+                match read {
+                    Ok(Line::End) => self.lines.done = true,
+                    Ok(Line::TooLong) => return Some(self.lines.too_long()),
+                    Ok(Line::Read) => {
+                        if let Some(vcon) = self.lines.parse(&self.buffer) {
+                            return Some(vcon);
+                        }
+                    }
+                    Err(e) => return self.lines.fail(e),
+                }
+            }
+            None
+        }
+    }
+
+    /// Async counterpart of [super::read_line]
+    async fn read_line(
+        reader: &mut (impl AsyncBufRead + Unpin),
+        buffer: &mut Vec<u8>,
+        max: u64,
+    ) -> std::io::Result<Line> {
+        buffer.clear();
+        if (&mut *reader).take(max).read_until(b'\n', buffer).await? == 0 {
+            return Ok(Line::End);
+        }
+        if (buffer.len() as u64) < max || buffer.ends_with(b"\n") {
+            return Ok(Line::Read);
+        }
+        loop {
+            let available = reader.fill_buf().await?;
+            let (consumed, end) = match available.iter().position(|b| *b == b'\n') {
+                Some(newline) => (newline + 1, true),
+                None => (available.len(), available.is_empty()),
+            };
+            reader.consume(consumed);
+            if end {
+                return Ok(Line::TooLong);
+            }
+        }
+    }
+
+    /// Async counterpart of [super::VconWriter]
+    pub struct AsyncVconWriter<W: AsyncWrite + Unpin> {
+        encoder: Encoder<W>,
+    }
+
+    enum Encoder<W: AsyncWrite + Unpin> {
+        Plain(tokio::io::BufWriter<W>),
+        #[cfg(feature = "gzip")]
+        Gzip(async_compression::tokio::write::GzipEncoder<W>),
+        #[cfg(feature = "zstd")]
+        Zstd(async_compression::tokio::write::ZstdEncoder<W>),
+    }
+
+    impl<W: AsyncWrite + Unpin> AsyncVconWriter<W> {
+        pub fn new(writer: W) -> Self {
+            Self::with_compression(writer, Compression::None)
+        }
+
+        pub fn with_compression(writer: W, compression: Compression) -> Self {
+            let encoder = match compression {
+                Compression::None => Encoder::Plain(tokio::io::BufWriter::new(writer)),
+                #[cfg(feature = "gzip")]
+                Compression::Gzip => {
+                    Encoder::Gzip(async_compression::tokio::write::GzipEncoder::new(writer))
+                }
+                #[cfg(feature = "zstd")]
+                Compression::Zstd => {
+                    Encoder::Zstd(async_compression::tokio::write::ZstdEncoder::new(writer))
+                }
+            };
+            Self { encoder }
+        }
+
+        pub async fn write(&mut self, vcon: &Vcon) -> VconResult<()> {
+            let mut line = serde_json::to_vec(vcon).map_err(std::io::Error::from)?;
+            line.push(b'\n');
+            match &mut self.encoder {
+                Encoder::Plain(w) => w.write_all(&line).await?,
+                #[cfg(feature = "gzip")]
+                Encoder::Gzip(w) => w.write_all(&line).await?,
+                #[cfg(feature = "zstd")]
+                Encoder::Zstd(w) => w.write_all(&line).await?,
+            }
+            Ok(())
+        }
+
+        /// Flushes what is buffered, ends the compressed stream if any and shuts the underlying
+        /// writer down
+        pub async fn finish(self) -> VconResult<W> {
+            Ok(match self.encoder {
+                Encoder::Plain(mut w) => {
+                    w.shutdown().await?;
+                    w.into_inner()
+                }
+                #[cfg(feature = "gzip")]
+                Encoder::Gzip(mut w) => {
+                    w.shutdown().await?;
+                    w.into_inner()
+                }
+                #[cfg(feature = "zstd")]
+                Encoder::Zstd(mut w) => {
+                    w.shutdown().await?;
+                    w.into_inner()
+                }
+            })
+        }
+    }
+}
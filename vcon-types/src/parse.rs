@@ -0,0 +1,535 @@
+use crate::extension::path_of;
+use crate::{ExtensionRegistry, Vcon, VconError, VconResult, Version};
+use serde::de::{
+    DeserializeSeed, Deserializer, EnumAccess, MapAccess, SeqAccess, VariantAccess, Visitor,
+};
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeSet;
+
+/// Options of parsing vCons from untrusted input
+///
+/// Limits are checked by a first pass over the document which keeps nothing of it (escaped strings
+/// are only unescaped one at a time), so that a malicious document is rejected before anything is
+/// built from it. Documents in other formats e.g. CBOR are checked while being transcoded to JSON
+/// with [ParseOptions::transcode].
+///
+/// ```rust
+/// # use vcon_types::{ParseOptions, Vcon, VconError};
+/// let options = ParseOptions { max_parties: 1, ..Default::default() };
+/// let json = br#"{"vcon":"0.0.1","uuid":"01928e10-193e-8231-b9a2-279e0d16bc46","parties":[{},{}]}"#;
+/// assert!(matches!(
+///     Vcon::parse_json(json, &options),
+///     Err(VconError::LimitExceeded { limit: "parties", max: 1, .. })
+/// ));
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ParseOptions {
+    /// Size of the document in bytes
+    pub max_size: usize,
+    /// Size of any `body`, as found in the document i.e. base64url encoded, or serialized as
+    /// compact JSON when it is not a string
+    pub max_body_size: usize,
+    pub max_parties: usize,
+    pub max_dialogs: usize,
+    pub max_analysis: usize,
+    pub max_attachments: usize,
+    /// Nesting of objects and arrays, extension objects and json bodies included, the vCon
+    /// itself being at depth 1
+    pub max_depth: usize,
+    pub unknown_fields: UnknownFields,
+    /// Extensions this consumer implements, documents marking any other as `critical` being
+    /// rejected
+    pub supported_extensions: BTreeSet<String>,
+    /// Keys of registered extensions, accepted in any object whatever `unknown_fields`, those of
+    /// the default [ExtensionRegistry] by default
+    pub extensions: BTreeSet<String>,
+}
+
+/// What becomes of keys neither defined by the draft nor a registered extension
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub enum UnknownFields {
+    /// Kept in the `extension_object` of the object they are found in, so that round-trips are
+    /// lossless
+    #[default]
+    Preserve,
+    /// Rejected with [VconError::UnknownField]
+    Reject,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            max_size: 64 * 1024 * 1024,
+            max_body_size: 48 * 1024 * 1024,
+            max_parties: 1024,
+            max_dialogs: 4096,
+            max_analysis: 4096,
+            max_attachments: 4096,
+            max_depth: 64,
+            unknown_fields: UnknownFields::Preserve,
+            supported_extensions: BTreeSet::new(),
+            extensions: ExtensionRegistry::default().names(),
+        }
+    }
+}
+
+impl Vcon {
+    /// Parses a JSON vCon, failing with [VconError::LimitExceeded] when it exceeds `options` and
+    /// with [VconError::UnknownField] on the first unknown field when they are rejected
+    ///
+    /// The declared version must be a supported [Version], legacy spellings being accepted
+    /// whatever it is, and its `critical` extensions supported (see [Vcon::check_critical]).
+    /// When unknown fields are rejected, those of drafts later than the declared one are as well.
+    pub fn parse_json(json: &[u8], options: &ParseOptions) -> VconResult<Vcon> {
+        let version = options
+            .check(json)?
+            .ok_or_else(|| VconError::InvalidVcon("missing field `vcon`".to_string()))?
+            .parse::<Version>()?;
+        let mut vcon: Vcon = serde_json::from_slice(json).map_err(invalid)?;
+        vcon.check_critical(&options.supported_extensions)?;
+        if options.unknown_fields == UnknownFields::Reject {
+            let mut unknown = vcon
+                .take_newer_fields(version)
+                .into_iter()
+                .chain(vcon.unknown_fields(&options.extensions));
+            if let Some(path) = unknown.next() {
+                return Err(VconError::UnknownField(path));
+            }
+        }
+        Ok(vcon)
+    }
+
+    /// Paths of the keys found in extension objects which are not in `extensions`, e.g.
+    /// `parties[0].x_case`
+    pub fn unknown_fields(&self, extensions: &BTreeSet<String>) -> Vec<String> {
+        let mut unknown = vec![];
+        for (path, extension_object) in self.extension_objects() {
+            if let serde_json::Value::Object(values) = &extension_object.0 {
+                let keys = values.keys().filter(|key| !extensions.contains(*key));
+                unknown.extend(keys.map(|key| path_of(&path, key)));
+            }
+        }
+        unknown
+    }
+}
+
+impl ParseOptions {
+    /// Checks the limits, returning the declared version if any
+    fn check(&self, json: &[u8]) -> VconResult<Option<String>> {
+        if json.len() > self.max_size {
+            return Err(VconError::LimitExceeded {
+                limit: "size",
+                max: self.max_size,
+                actual: json.len(),
+            });
+        }
+        let exceeded = Cell::new(None);
+        let version = Cell::new(None);
+        let body_size = Cell::new(0);
+        let walker = Walker {
+            options: self,
+            exceeded: &exceeded,
+            version: &version,
+            body_size: &body_size,
+            out: None,
+            depth: 1,
+            node: Node::Root,
+            base64url: false,
+        };
+        let mut deserializer = serde_json::Deserializer::from_slice(json);
+        let walked = walker.deserialize(&mut deserializer);
+        if let Some(error) = exceeded.take() {
+            return Err(error);
+        }
+        walked.map_err(invalid)?;
+        deserializer.end().map_err(invalid)?;
+        Ok(version.take())
+    }
+}
+
+impl ParseOptions {
+    /// Transcodes a vCon from another self-describing format e.g. CBOR to JSON, to be parsed with
+    /// [Vcon::parse_json], failing as soon as a limit is exceeded. The size limit applies to the
+    /// JSON written: the size of the input is left to the caller.
+    ///
+    /// Byte strings become base64url strings, except those tagged [crate::BASE_64_URL_TAG] which
+    /// already hold base64url text as the `cbor` feature writes bodies.
+    pub fn transcode<'de, D: Deserializer<'de>>(&self, deserializer: D) -> VconResult<Vec<u8>> {
+        let exceeded = Cell::new(None);
+        let version = Cell::new(None);
+        let body_size = Cell::new(0);
+        let out = RefCell::new(vec![]);
+        let walker = Walker {
+            options: self,
+            exceeded: &exceeded,
+            version: &version,
+            body_size: &body_size,
+            out: Some(&out),
+            depth: 1,
+            node: Node::Root,
+            base64url: false,
+        };
+        let walked = walker.deserialize(deserializer);
+        if let Some(error) = exceeded.take() {
+            return Err(error);
+        }
+        walked.map_err(|e| VconError::InvalidVcon(e.to_string()))?;
+        Ok(out.into_inner())
+    }
+
+    /// Transcodes a CBOR vCon to JSON with [ParseOptions::transcode], reading it one item at a
+    /// time
+    ///
+    /// ```rust
+    /// # use vcon_types::{ParseOptions, Vcon};
+    /// // {"vcon": "0.0.1", "uuid": "01928e10-193e-8231-b9a2-279e0d16bc46"}
+    /// let cbor = b"\xa2\x64vcon\x650.0.1\x64uuid\x78\x2401928e10-193e-8231-b9a2-279e0d16bc46";
+    /// let options = ParseOptions::default();
+    /// let json = options.transcode_cbor(cbor).unwrap();
+    /// assert!(Vcon::parse_json(&json, &options).is_ok());
+    /// ```
+    #[cfg(feature = "transcode")]
+    pub fn transcode_cbor(&self, cbor: &[u8]) -> VconResult<Vec<u8>> {
+        if cbor.len() > self.max_size {
+            return Err(VconError::LimitExceeded {
+                limit: "size",
+                max: self.max_size,
+                actual: cbor.len(),
+            });
+        }
+        let mut deserializer = crate::transcode::CborDeserializer::new(cbor);
+        let json = self.transcode(&mut deserializer)?;
+        deserializer
+            .end(cbor.len())
+            .map_err(|e| VconError::InvalidVcon(e.to_string()))?;
+        Ok(json)
+    }
+}
+
+fn invalid(e: serde_json::Error) -> VconError {
+    VconError::InvalidVcon(e.to_string())
+}
+
+/// What is being walked through
+#[derive(Copy, Clone)]
+enum Node {
+    Root,
+    /// Top level array whose length is limited
+    Items {
+        limit: &'static str,
+        max: usize,
+    },
+    /// Value of a `body`
+    Body,
+    /// Value nested in a `body` holding JSON
+    JsonBody,
+    Version,
+    Other,
+}
+
+/// Walks through a document without keeping anything of it but its version, recording the first
+/// limit exceeded and writing it as JSON to `out` if any
+#[derive(Copy, Clone)]
+struct Walker<'a> {
+    options: &'a ParseOptions,
+    exceeded: &'a Cell<Option<VconError>>,
+    version: &'a Cell<Option<String>>,
+    /// Serialized size of the `body` holding JSON being walked through
+    body_size: &'a Cell<usize>,
+    out: Option<&'a RefCell<Vec<u8>>>,
+    depth: usize,
+    node: Node,
+    /// Whether the value is tagged as base64url text
+    base64url: bool,
+}
+
+impl Walker<'_> {
+    fn exceed<E: serde::de::Error>(&self, limit: &'static str, max: usize, actual: usize) -> E {
+        self.exceeded
+            .set(Some(VconError::LimitExceeded { limit, max, actual }));
+        E::custom(format!("{limit} limit exceeded"))
+    }
+
+    /// Walker of a value nested in this one
+    fn nested<E: serde::de::Error>(&self, node: Node) -> Result<Self, E> {
+        if self.depth >= self.options.max_depth {
+            return Err(self.exceed("depth", self.options.max_depth, self.depth + 1));
+        }
+        Ok(Self {
+            depth: self.depth + 1,
+            node,
+            base64url: false,
+            ..*self
+        })
+    }
+
+    /// Appends JSON to `out`, when transcoding
+    fn write<E: serde::de::Error>(
+        &self,
+        write: impl FnOnce(&mut Vec<u8>) -> serde_json::Result<()>,
+    ) -> Result<(), E> {
+        let Some(out) = self.out else {
+            return Ok(());
+        };
+        let mut out = out.borrow_mut();
+        write(&mut out).map_err(E::custom)?;
+        if out.len() > self.options.max_size {
+            return Err(self.exceed("size", self.options.max_size, out.len()));
+        }
+        Ok(())
+    }
+
+    fn punct<E: serde::de::Error>(&self, c: u8) -> Result<(), E> {
+        self.count_body(1)?;
+        self.write(|out| {
+            out.push(c);
+            Ok(())
+        })
+    }
+
+    /// Closes an array or object whose items are each followed by a comma, the last one standing
+    /// for `end` in the size of a body unless it is `empty`
+    fn close<E: serde::de::Error>(&self, end: u8, empty: bool) -> Result<(), E> {
+        if empty {
+            self.count_body(1)?;
+        }
+        self.write(|out| {
+            if out.last() == Some(&b',') {
+                out.pop();
+            }
+            out.push(end);
+            Ok(())
+        })
+    }
+
+    fn check_body<E: serde::de::Error>(&self, len: usize) -> Result<(), E> {
+        match self.node {
+            Node::Body if len > self.options.max_body_size => {
+                Err(self.exceed("body size", self.options.max_body_size, len))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Adds `len` to the size of the `body` holding JSON walked through, if any
+    fn count_body<E: serde::de::Error>(&self, len: usize) -> Result<(), E> {
+        if !matches!(self.node, Node::Body | Node::JsonBody) {
+            return Ok(());
+        }
+        let size = self.body_size.get() + len;
+        self.body_size.set(size);
+        if size > self.options.max_body_size {
+            return Err(self.exceed("body size", self.options.max_body_size, size));
+        }
+        Ok(())
+    }
+
+    /// Counts the serialized size of a value which is not a string `body`, then writes it
+    fn scalar<E: serde::de::Error>(&self, value: &impl serde::Serialize) -> Result<(), E> {
+        if let Node::Body | Node::JsonBody = self.node {
+            self.count_body(serialized_len(value).map_err(E::custom)?)?;
+        }
+        self.write(|out| serde_json::to_writer(out, value))
+    }
+
+    /// Walker of the values of an array or object
+    fn item<E: serde::de::Error>(&self, node: Node) -> Result<Self, E> {
+        match self.node {
+            Node::Body | Node::JsonBody => self.nested(Node::JsonBody),
+            _ => self.nested(node),
+        }
+    }
+}
+
+/// Length of `value` serialized as JSON
+fn serialized_len(value: &impl serde::Serialize) -> serde_json::Result<usize> {
+    struct Len(usize);
+
+    impl std::io::Write for Len {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0 += buf.len();
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let mut len = Len(0);
+    serde_json::to_writer(&mut len, value)?;
+    Ok(len.0)
+}
+
+impl<'de> DeserializeSeed<'de> for Walker<'_> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for Walker<'_> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("a vCon")
+    }
+
+    fn visit_bool<E: serde::de::Error>(self, v: bool) -> Result<(), E> {
+        self.scalar(&v)
+    }
+
+    fn visit_i64<E: serde::de::Error>(self, v: i64) -> Result<(), E> {
+        self.scalar(&v)
+    }
+
+    fn visit_u64<E: serde::de::Error>(self, v: u64) -> Result<(), E> {
+        self.scalar(&v)
+    }
+
+    fn visit_f64<E: serde::de::Error>(self, v: f64) -> Result<(), E> {
+        self.scalar(&v)
+    }
+
+    fn visit_unit<E: serde::de::Error>(self) -> Result<(), E> {
+        self.scalar(&())
+    }
+
+    fn visit_none<E: serde::de::Error>(self) -> Result<(), E> {
+        self.visit_unit()
+    }
+
+    fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<(), E> {
+        match self.node {
+            Node::Version => self.version.set(Some(v.to_string())),
+            Node::JsonBody => return self.scalar(&v),
+            _ => {}
+        }
+        self.check_body(v.len())?;
+        self.write(|out| serde_json::to_writer(out, v))
+    }
+
+    fn visit_bytes<E: serde::de::Error>(self, v: &[u8]) -> Result<(), E> {
+        use base64::Engine as _;
+        if self.base64url {
+            return self.visit_str(std::str::from_utf8(v).map_err(E::custom)?);
+        }
+        self.visit_str(&crate::InlineContent::B64.encode(v))
+    }
+
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<(), A::Error> {
+        // CBOR tags, the tagged value being kept, nested so that tags of tags are limited too
+        let (tag, value) = data.variant::<u64>()?;
+        value.newtype_variant_seed(Self {
+            base64url: tag == crate::BASE_64_URL_TAG,
+            ..self.nested(self.node)?
+        })
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        let element = self.item(Node::Other)?;
+        self.punct(b'[')?;
+        let mut len = 0;
+        while seq.next_element_seed(element)?.is_some() {
+            len += 1;
+            if let Node::Items { limit, max } = self.node {
+                if len > max {
+                    return Err(self.exceed(limit, max, len));
+                }
+            }
+            self.punct(b',')?;
+        }
+        self.close(b']', len == 0)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        self.punct(b'{')?;
+        let mut empty = true;
+        while let Some(key) = map.next_key_seed(KeySeed(self))? {
+            let options = self.options;
+            let node = match (self.node, key) {
+                (Node::Body | Node::JsonBody, _) => Node::JsonBody,
+                (Node::Root, Key::Parties) => Node::Items {
+                    limit: "parties",
+                    max: options.max_parties,
+                },
+                (Node::Root, Key::Dialog) => Node::Items {
+                    limit: "dialogs",
+                    max: options.max_dialogs,
+                },
+                (Node::Root, Key::Analysis) => Node::Items {
+                    limit: "analysis",
+                    max: options.max_analysis,
+                },
+                (Node::Root, Key::Attachments) => Node::Items {
+                    limit: "attachments",
+                    max: options.max_attachments,
+                },
+                (Node::Root, Key::Vcon) => Node::Version,
+                (_, Key::Body) => {
+                    self.body_size.set(0);
+                    Node::Body
+                }
+                _ => Node::Other,
+            };
+            map.next_value_seed(self.item(node)?)?;
+            self.punct(b',')?;
+            empty = false;
+        }
+        self.close(b'}', empty)
+    }
+}
+
+/// Keys the walker cares about, read without being allocated
+#[derive(Copy, Clone)]
+enum Key {
+    Parties,
+    Dialog,
+    Analysis,
+    Attachments,
+    Body,
+    Vcon,
+    Other,
+}
+
+/// Reads a key of the object walked through by a [Walker], writing it when transcoding
+struct KeySeed<'a>(Walker<'a>);
+
+impl<'de> DeserializeSeed<'de> for KeySeed<'_> {
+    type Value = Key;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Key, D::Error> {
+        deserializer.deserialize_str(self)
+    }
+}
+
+impl<'de> Visitor<'de> for KeySeed<'_> {
+    type Value = Key;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("a key")
+    }
+
+    fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Key, E> {
+        if let Node::Body | Node::JsonBody = self.0.node {
+            // followed by a colon
+            self.0
+                .count_body(serialized_len(&v).map_err(E::custom)? + 1)?;
+        }
+        self.0.write(|out| {
+            serde_json::to_writer(&mut *out, v)?;
+            out.push(b':');
+            Ok(())
+        })?;
+        Ok(match v {
+            "parties" => Key::Parties,
+            "dialog" => Key::Dialog,
+            "analysis" => Key::Analysis,
+            "attachments" => Key::Attachments,
+            "body" => Key::Body,
+            "vcon" => Key::Vcon,
+            _ => Key::Other,
+        })
+    }
+}
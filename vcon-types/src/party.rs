@@ -13,17 +13,49 @@ pub struct Party {
     pub mailto: Option<String>,
     #[cfg_attr(ser, serde(skip_serializing_if = "Option::is_none"))]
     pub name: Option<String>,
-    #[cfg_attr(ser, serde(skip_serializing_if = "Option::is_none"))]
+    #[cfg_attr(
+        ser,
+        serde(alias = "validataion", skip_serializing_if = "Option::is_none")
+    )]
     pub validation: Option<String>,
     #[cfg_attr(ser, serde(skip_serializing_if = "Option::is_none"))]
     pub gmlpos: Option<String>,
-    #[cfg_attr(ser, serde(skip_serializing_if = "Option::is_none"))]
+    #[cfg_attr(
+        ser,
+        serde(alias = "civicaddress", skip_serializing_if = "Option::is_none")
+    )]
     pub civic_address: Option<CivicAddress>,
     #[cfg_attr(ser, serde(skip_serializing_if = "Option::is_none"))]
     pub uuid: Option<Uuid>,
     #[cfg_attr(ser, serde(skip_serializing_if = "Option::is_none"))]
     pub role: Option<String>,
+    /// SIP URI
+    #[cfg_attr(ser, serde(skip_serializing_if = "Option::is_none"))]
+    pub sip: Option<String>,
+    /// Decentralized identifier
+    #[cfg_attr(ser, serde(skip_serializing_if = "Option::is_none"))]
+    pub did: Option<String>,
+    /// Contact card in jCard format (RFC 7095)
+    #[cfg(json)]
+    #[cfg_attr(json, serde(rename = "jCard", skip_serializing_if = "Option::is_none"))]
+    pub jcard: Option<crate::JsonAnyValue>,
+    /// IANA time zone e.g. `Europe/Paris`
+    #[cfg_attr(ser, serde(skip_serializing_if = "Option::is_none"))]
+    pub timezone: Option<String>,
+    /// Url of the contact list the party was reached through
+    #[cfg_attr(ser, serde(skip_serializing_if = "Option::is_none"))]
+    pub contact_list: Option<String>,
     #[cfg(json)]
     #[cfg_attr(json, serde(flatten))]
     pub extension_object: crate::JsonAnyValue,
 }
+
+impl Party {
+    /// Human friendly identifier: name, else phone number, else email
+    pub fn display_name(&self) -> Option<&str> {
+        self.name
+            .as_deref()
+            .or(self.tel.as_deref())
+            .or(self.mailto.as_deref())
+    }
+}
@@ -0,0 +1,187 @@
+use crate::{Date, DialogObject, Event, PartyIndex, Vcon, VconError, VconResult};
+use std::collections::BTreeMap;
+
+/// Time span between two dates
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+pub struct Interval {
+    pub start: Date,
+    pub end: Date,
+}
+
+impl Interval {
+    pub fn duration(&self) -> time::Duration {
+        *self.end - *self.start
+    }
+}
+
+/// Presence of a party in a dialog, derived from its `party_history`
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PartyPresence {
+    pub party: PartyIndex,
+    /// Between a [Event::Join] and a [Event::Drop]
+    pub present: Vec<Interval>,
+    /// Between a [Event::Hold] and a [Event::Unhold]
+    pub on_hold: Vec<Interval>,
+    /// Between a [Event::Mute] and a [Event::Unmute]
+    pub muted: Vec<Interval>,
+}
+
+impl PartyPresence {
+    fn new(party: PartyIndex) -> Self {
+        Self {
+            party,
+            present: vec![],
+            on_hold: vec![],
+            muted: vec![],
+        }
+    }
+
+    pub fn time_present(&self) -> time::Duration {
+        self.present.iter().map(Interval::duration).sum()
+    }
+
+    pub fn time_on_hold(&self) -> time::Duration {
+        self.on_hold.iter().map(Interval::duration).sum()
+    }
+
+    pub fn time_muted(&self) -> time::Duration {
+        self.muted.iter().map(Interval::duration).sum()
+    }
+}
+
+/// Open intervals of a party while replaying its events
+#[derive(Default)]
+struct PresenceState {
+    joined: Option<Date>,
+    hold: Option<Date>,
+    mute: Option<Date>,
+}
+
+impl PresenceState {
+    fn apply(
+        &mut self,
+        event: Event,
+        time: &Date,
+        presence: &mut PartyPresence,
+    ) -> Result<(), &'static str> {
+        let close = |since: &mut Option<Date>, intervals: &mut Vec<Interval>| {
+            if let Some(start) = since.take() {
+                intervals.push(Interval {
+                    start,
+                    end: time.clone(),
+                });
+            }
+        };
+
+        match event {
+            Event::Join if self.joined.is_some() => return Err("already present"),
+            Event::Join => self.joined = Some(time.clone()),
+            _ if self.joined.is_none() => return Err("party is not present"),
+            Event::Drop => {
+                close(&mut self.hold, &mut presence.on_hold);
+                close(&mut self.mute, &mut presence.muted);
+                close(&mut self.joined, &mut presence.present);
+            }
+            Event::Hold if self.hold.is_some() => return Err("already on hold"),
+            Event::Hold => self.hold = Some(time.clone()),
+            Event::Unhold if self.hold.is_none() => return Err("not on hold"),
+            Event::Unhold => close(&mut self.hold, &mut presence.on_hold),
+            Event::Mute if self.mute.is_some() => return Err("already muted"),
+            Event::Mute => self.mute = Some(time.clone()),
+            Event::Unmute if self.mute.is_none() => return Err("not muted"),
+            Event::Unmute => close(&mut self.mute, &mut presence.muted),
+        }
+        Ok(())
+    }
+}
+
+impl DialogObject {
+    /// Replays `party_history` to compute when each party was present, on hold or muted.
+    ///
+    /// A party whose first event is not a [Event::Join] (or which has no event but is part of
+    /// the dialog's `parties`) is considered present since `start`. Intervals still open are
+    /// closed at the end of the dialog, or at its last event when it has no `duration`.
+    ///
+    /// Fails when an event happens before `start`, events of a party are not in chronological
+    /// order or an event is not allowed in the party's current state (e.g. [Event::Unhold]
+    /// without [Event::Hold]).
+    pub fn presence(&self) -> VconResult<BTreeMap<PartyIndex, PartyPresence>> {
+        let events = self.party_history.as_deref().unwrap_or_default();
+        let end = events
+            .iter()
+            .map(|e| &e.time)
+            .chain(self.end().as_ref())
+            .max()
+            .unwrap_or(&self.start)
+            .clone();
+
+        let mut presences = BTreeMap::new();
+        let mut states = BTreeMap::<PartyIndex, (PresenceState, Date)>::new();
+
+        for party in self
+            .dialog
+            .parties()
+            .map(|p| p.indexes())
+            .unwrap_or_default()
+        {
+            presences.insert(party, PartyPresence::new(party));
+        }
+
+        for event in events {
+            let invalid = |reason| VconError::InvalidPartyHistory {
+                party: event.party,
+                event: event.event,
+                reason,
+            };
+            if event.time < self.start {
+                return Err(invalid("event before dialog start"));
+            }
+
+            let presence = presences
+                .entry(event.party)
+                .or_insert_with(|| PartyPresence::new(event.party));
+            let (state, last) = states.entry(event.party).or_insert_with(|| {
+                let state = PresenceState {
+                    joined: (event.event != Event::Join).then(|| self.start.clone()),
+                    ..Default::default()
+                };
+                (state, event.time.clone())
+            });
+            if event.time < *last {
+                return Err(invalid("event out of chronological order"));
+            }
+            *last = event.time.clone();
+            state
+                .apply(event.event, &event.time, presence)
+                .map_err(invalid)?;
+        }
+
+        for (party, presence) in presences.iter_mut() {
+            match states.get_mut(party) {
+                Some((state, _)) => {
+                    if state.joined.is_some() {
+                        state
+                            .apply(Event::Drop, &end, presence)
+                            .expect("present party can always drop");
+                    }
+                }
+                None => presence.present.push(Interval {
+                    start: self.start.clone(),
+                    end: end.clone(),
+                }),
+            }
+        }
+        Ok(presences)
+    }
+}
+
+impl Vcon {
+    /// [DialogObject::presence] of every dialog, by dialog index
+    pub fn presence(&self) -> VconResult<Vec<BTreeMap<PartyIndex, PartyPresence>>> {
+        self.dialog
+            .iter()
+            .flatten()
+            .map(DialogObject::presence)
+            .collect()
+    }
+}
@@ -0,0 +1,119 @@
+use crate::{
+    Content, Date, Dialog, DialogIndex, InlineContent, OrEmpty, PartyIndex, RedactedReference,
+    Uuid, Vcon, VconError, VconReference, VconResult,
+};
+
+/// Redactions
+///
+/// A redacted vCon is a copy of another one from which sensitive data (e.g. a phone number or a
+/// recording) is removed. It references the unredacted vCon through `redacted`.
+///
+/// See https://ietf-wg-vcon.github.io/draft-ietf-vcon-vcon-container/draft-ietf-vcon-vcon-container.html#name-redacted
+impl Vcon {
+    /// Creates a copy of `original` referencing it through `redacted`, `typ` describing the kind of
+    /// redaction e.g. "PII Masked".
+    ///
+    /// The new vCon gets a fresh [Uuid] and `updated_at` is set to now. Data is then removed with
+    /// [Vcon::redact_party], [Vcon::redact_dialog] or [Vcon::redact_attachment].
+    pub fn redact(original: &Vcon, typ: impl Into<String>) -> Vcon {
+        let updated_at = Date::now();
+
+        let mut seed = b"redacted".to_vec();
+        seed.extend_from_slice(original.uuid.as_bytes());
+        seed.extend_from_slice(&updated_at.unix_timestamp_nanos().to_be_bytes());
+
+        let mut redacted = original.clone();
+        redacted.uuid = Uuid::derive(&seed);
+        redacted.updated_at = Some(updated_at);
+        redacted.redacted = Some(OrEmpty::Some(RedactedReference {
+            typ: Some(typ.into()),
+            vcon_reference: VconReference::Uuid {
+                uuid: original.uuid.clone(),
+            },
+            #[cfg(json)]
+            extension_object: Default::default(),
+        }));
+        redacted
+    }
+
+    /// Removes what identifies a party, only its `role` and `uuid` being kept
+    pub fn redact_party(&mut self, index: PartyIndex) -> VconResult<()> {
+        let party = self
+            .parties
+            .as_mut()
+            .and_then(|parties| parties.get_mut(index as usize))
+            .ok_or_else(|| VconError::UnknownIndex {
+                path: "parties".to_string(),
+                target: "party",
+                index,
+            })?;
+        party.tel = None;
+        party.stir = None;
+        party.mailto = None;
+        party.name = None;
+        party.validation = None;
+        party.gmlpos = None;
+        party.civic_address = None;
+        party.sip = None;
+        party.did = None;
+        #[cfg(json)]
+        {
+            party.jcard = None;
+        }
+        party.contact_list = None;
+        Ok(())
+    }
+
+    /// Empties the body of a recording or text dialog, keeping its timing and parties. Analysis
+    /// of the dialog is removed as it is derived from the body.
+    pub fn redact_dialog(&mut self, index: DialogIndex) -> VconResult<()> {
+        let unknown = || VconError::UnknownIndex {
+            path: "dialog".to_string(),
+            target: "dialog",
+            index,
+        };
+        let object = self
+            .dialog
+            .as_mut()
+            .and_then(|dialog| dialog.get_mut(index as usize))
+            .ok_or_else(unknown)?;
+        match &mut object.dialog {
+            Dialog::Recording {
+                content,
+                content_parameters,
+                ..
+            }
+            | Dialog::Text {
+                content,
+                content_parameters,
+                ..
+            } => {
+                *content = Content::Inline(InlineContent::TextNone(String::new()));
+                content_parameters.filename = None;
+            }
+            Dialog::Transfer { .. } | Dialog::Incomplete { .. } => {}
+        }
+        if let Some(analysis) = &mut self.analysis {
+            analysis.retain(|a| a.dialog != index);
+        }
+        Ok(())
+    }
+    /// Removes an attachment, those after it moving down by one. `attachments` is unset when the
+    /// last one is removed.
+    pub fn redact_attachment(&mut self, index: u32) -> VconResult<()> {
+        let attachments = self
+            .attachments
+            .as_mut()
+            .filter(|attachments| (index as usize) < attachments.len())
+            .ok_or_else(|| VconError::UnknownIndex {
+                path: "attachments".to_string(),
+                target: "attachment",
+                index,
+            })?;
+        attachments.remove(index as usize);
+        if attachments.is_empty() {
+            self.attachments = None;
+        }
+        Ok(())
+    }
+}
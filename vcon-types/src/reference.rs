@@ -9,22 +9,118 @@ pub struct RedactedReference {
     pub typ: Option<String>,
     #[cfg_attr(ser, serde(flatten))]
     pub vcon_reference: VconReference,
+    #[cfg(json)]
+    #[cfg_attr(json, serde(flatten))]
+    pub extension_object: crate::JsonAnyValue,
 }
 
+/// Entry of `group`, its keys which are not part of the reference being kept in the extension
+/// object
+#[derive(Debug, Clone, Hash, Eq, PartialEq, From, Into)]
+#[cfg_attr(ser, derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "builder", derive(derive_builder::Builder))]
+pub struct GroupReference {
+    #[cfg_attr(ser, serde(flatten))]
+    pub vcon_reference: VconReference,
+    #[cfg(json)]
+    #[cfg_attr(json, serde(flatten))]
+    pub extension_object: crate::JsonAnyValue,
+}
+
+#[cfg(json)]
+impl From<VconReference> for GroupReference {
+    fn from(vcon_reference: VconReference) -> Self {
+        Self {
+            vcon_reference,
+            extension_object: Default::default(),
+        }
+    }
+}
+
+/// Reference to a vCon by UUID, inline content or URL, its keys sitting in the referencing object
+///
+/// Other keys are ignored: [RedactedReference] and [GroupReference] keep them.
+///
+/// ```rust
+/// # use vcon_types::VconReference;
+/// let json = r#"{"uuid":"01928e10-193e-8231-b9a2-279e0d16bc46"}"#;
+/// let reference: VconReference = serde_json::from_str(json).unwrap();
+/// assert!(matches!(reference, VconReference::Uuid { .. }));
+/// assert_eq!(serde_json::to_string(&reference).unwrap(), json);
+/// ```
 #[derive(Debug, Clone, Hash, Eq, PartialEq)]
-#[cfg_attr(
-    ser,
-    derive(serde::Serialize, serde::Deserialize),
-    serde(rename_all = "lowercase")
-)]
+#[cfg_attr(ser, derive(serde::Serialize), serde(untagged))]
 pub enum VconReference {
     Url {
+        #[cfg_attr(ser, serde(flatten))]
         vcon_url_referenced: UrlReferencedContent,
     },
     Inline {
+        #[cfg_attr(ser, serde(flatten))]
         inline_content: InlineContent,
     },
     Uuid {
         uuid: Uuid,
     },
 }
+
+#[cfg(ser)]
+impl<'de> serde::Deserialize<'de> for VconReference {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ReferenceVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for ReferenceVisitor {
+            type Value = VconReference;
+
+            fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+                formatter.write_str("a Map of vCon reference with uuid, inline or url content")
+            }
+
+            #[cfg(json)]
+            fn visit_map<A: serde::de::MapAccess<'de>>(
+                self,
+                mut map: A,
+            ) -> Result<Self::Value, A::Error> {
+                use crate::{content::ContentFields, Content};
+                use serde::de::Error as _;
+
+                let (mut uuid, mut content) = (None, ContentFields::default());
+                while let Some(k) = map.next_key::<String>()? {
+                    if k == "uuid" {
+                        uuid = Some(map.next_value()?);
+                    } else if !content.visit(&k, &mut map)? {
+                        map.next_value::<serde::de::IgnoredAny>()?;
+                    }
+                }
+                Ok(match (uuid, content.build()?) {
+                    (Some(uuid), _) => VconReference::Uuid { uuid },
+                    (None, Some(Content::Inline(inline_content))) => {
+                        VconReference::Inline { inline_content }
+                    }
+                    (None, Some(Content::UrlReferenced(vcon_url_referenced))) => {
+                        VconReference::Url {
+                            vcon_url_referenced,
+                        }
+                    }
+                    (None, None) => {
+                        return Err(A::Error::custom(
+                            "Invalid vCon reference, must have a uuid, inline or url content",
+                        ))
+                    }
+                })
+            }
+
+            #[cfg(cbor)]
+            fn visit_map<A: serde::de::MapAccess<'de>>(
+                self,
+                mut map: A,
+            ) -> Result<Self::Value, A::Error> {
+                todo!()
+            }
+        }
+
+        // as a struct so that, when flattened, its fields are not seen by flattened siblings
+        const FIELDS: &[&str] = &["uuid", "encoding", "body", "url", "signature", "alg"];
+        deserializer.deserialize_struct("VconReference", FIELDS, ReferenceVisitor)
+    }
+}
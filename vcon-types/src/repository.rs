@@ -0,0 +1,433 @@
+#[cfg(feature = "json")]
+use crate::ParseOptions;
+use crate::{
+    Content, Date, Dialog, Signature, Url, UrlReferencedContent, Uuid, Vcon, VconError,
+    VconReference, VconResult, VconStore,
+};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+
+/// Serialization of the vCons stored in a [VconRepository]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum StorageFormat {
+    #[cfg(feature = "json")]
+    Json,
+    #[cfg(feature = "cbor")]
+    Cbor,
+}
+
+impl StorageFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            #[cfg(feature = "json")]
+            Self::Json => "json",
+            #[cfg(feature = "cbor")]
+            Self::Cbor => "cbor",
+        }
+    }
+
+    fn encode(&self, vcon: &Vcon) -> VconResult<Vec<u8>> {
+        match self {
+            #[cfg(feature = "json")]
+            Self::Json => serde_json::to_vec(vcon).map_err(|e| VconError::Storage(e.to_string())),
+            #[cfg(feature = "cbor")]
+            Self::Cbor => {
+                let mut bytes = vec![];
+                ciborium::into_writer(vcon, &mut bytes)
+                    .map_err(|e| VconError::Storage(e.to_string()))?;
+                Ok(bytes)
+            }
+        }
+    }
+}
+
+/// vCons stored in a local directory
+///
+/// Layout of the directory:
+/// - `vcons/<uuid>.<json|cbor>`: one file per vCon
+/// - `blobs/<sha256>`: externalized bodies, named after the hex encoded SHA-256 of their content
+///
+/// Every file is written to a temporary file first then renamed, so that a crash never leaves a
+/// partially written vCon. Secondary indexes on `created_at`, party `tel` and `mailto` and `group`
+/// membership are kept in memory and rebuilt when the repository is opened.
+///
+/// Bodies of dialogs and attachments can be moved to the blob store on [VconRepository::put] (see
+/// [VconRepository::externalize_bodies]). Since this repository is a [VconStore], they are then
+/// fetched back by [Content::bytes].
+///
+/// JSON vCons are read back within [ParseOptions] (see [VconRepository::open_with]), so that a
+/// file tampered with cannot exhaust memory. Files which cannot be read back are left out when the
+/// repository is opened, and reported by [VconRepository::skipped].
+#[derive(Debug)]
+pub struct VconRepository {
+    root: PathBuf,
+    format: StorageFormat,
+    #[cfg(feature = "json")]
+    options: ParseOptions,
+    externalize: Option<(Url, usize)>,
+    skipped: Vec<(PathBuf, VconError)>,
+    entries: HashMap<Uuid, IndexEntry>,
+    created_at: BTreeMap<Date, BTreeSet<Uuid>>,
+    tels: BTreeMap<String, BTreeSet<Uuid>>,
+    mailtos: BTreeMap<String, BTreeSet<Uuid>>,
+    /// Groups containing each vCon
+    groups: BTreeMap<Uuid, BTreeSet<Uuid>>,
+}
+
+/// What is indexed of a vCon, to remove it from the secondary indexes
+#[derive(Debug)]
+struct IndexEntry {
+    created_at: Option<Date>,
+    tels: Vec<String>,
+    mailtos: Vec<String>,
+    members: Vec<Uuid>,
+}
+
+impl VconRepository {
+    const VCONS: &'static str = "vcons";
+    const BLOBS: &'static str = "blobs";
+
+    /// Opens the repository in `root`, creating it when it does not exist, reading JSON vCons
+    /// within the default [ParseOptions]
+    pub fn open(root: impl Into<PathBuf>, format: StorageFormat) -> VconResult<Self> {
+        Self::new(root.into(), format).read_all()
+    }
+
+    /// Opens the repository in `root` like [VconRepository::open], reading JSON vCons within
+    /// `options` instead of the default ones
+    #[cfg(feature = "json")]
+    pub fn open_with(
+        root: impl Into<PathBuf>,
+        format: StorageFormat,
+        options: ParseOptions,
+    ) -> VconResult<Self> {
+        Self {
+            options,
+            ..Self::new(root.into(), format)
+        }
+        .read_all()
+    }
+
+    fn new(root: PathBuf, format: StorageFormat) -> Self {
+        Self {
+            root,
+            format,
+            #[cfg(feature = "json")]
+            options: ParseOptions::default(),
+            externalize: None,
+            skipped: vec![],
+            entries: HashMap::new(),
+            created_at: BTreeMap::new(),
+            tels: BTreeMap::new(),
+            mailtos: BTreeMap::new(),
+            groups: BTreeMap::new(),
+        }
+    }
+
+    /// Indexes the vCons stored, creating the directories if needed, skipping those which cannot
+    /// be read back
+    fn read_all(mut self) -> VconResult<Self> {
+        std::fs::create_dir_all(self.root.join(Self::VCONS))?;
+        std::fs::create_dir_all(self.root.join(Self::BLOBS))?;
+
+        for file in std::fs::read_dir(self.root.join(Self::VCONS))? {
+            let path = file?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some(self.format.extension()) {
+                continue;
+            }
+            match std::fs::read(&path)
+                .map_err(VconError::from)
+                .and_then(|bytes| self.decode(&bytes))
+            {
+                Ok(vcon) => self.index(&vcon),
+                Err(e) => self.skipped.push((path, e)),
+            }
+        }
+        Ok(self)
+    }
+
+    /// Files of the vCons left out when the repository was opened, with the reason why
+    pub fn skipped(&self) -> &[(PathBuf, VconError)] {
+        &self.skipped
+    }
+
+    /// Fails like [VconRepository::get] would on `vcon` once stored
+    fn check(&self, vcon: &Vcon) -> VconResult<()> {
+        vcon.version.version()?;
+        #[cfg(feature = "json")]
+        vcon.check_critical(&self.options.supported_extensions)?;
+        Ok(())
+    }
+
+    fn decode(&self, bytes: &[u8]) -> VconResult<Vcon> {
+        match self.format {
+            #[cfg(feature = "json")]
+            StorageFormat::Json => Vcon::parse_json(bytes, &self.options),
+            #[cfg(feature = "cbor")]
+            StorageFormat::Cbor => {
+                ciborium::from_reader(bytes).map_err(|e| VconError::Storage(e.to_string()))
+            }
+        }
+    }
+
+    /// Moves inline bodies of dialogs and attachments of at least `min_size` bytes to the blob
+    /// store when they are [put](VconRepository::put), replacing them with a reference to
+    /// `<base>/<sha256>`.
+    ///
+    /// `base` is the https URL under which the blobs directory is meant to be served, ending with a
+    /// `/`.
+    pub fn externalize_bodies(mut self, base: Url, min_size: usize) -> Self {
+        self.externalize = Some((base, min_size));
+        self
+    }
+
+    /// Stores `vcon`, replacing any vCon with the same `uuid`. Fails with
+    /// [VconError::UnsupportedVersion] or [VconError::UnsupportedCriticalExtensions] as it could not
+    /// be read back otherwise.
+    pub fn put(&mut self, vcon: &Vcon) -> VconResult<()> {
+        self.check(vcon)?;
+        let mut vcon = vcon.clone();
+        if let Some((base, min_size)) = self.externalize.clone() {
+            let dialogs = vcon
+                .dialog
+                .iter_mut()
+                .flatten()
+                .filter_map(|d| match &mut d.dialog {
+                    Dialog::Recording { content, .. } | Dialog::Text { content, .. } => {
+                        Some(content)
+                    }
+                    _ => None,
+                });
+            let attachments = vcon
+                .attachments
+                .iter_mut()
+                .flatten()
+                .map(|a| &mut a.content);
+            for content in dialogs.chain(attachments) {
+                let Content::Inline(inline) = content else {
+                    continue;
+                };
+                let body = inline.bytes();
+                if body.len() < min_size {
+                    continue;
+                }
+                let digest = self.put_blob(&body)?;
+                let url = base
+                    .join(&hex(&digest))
+                    .map_err(|e| VconError::Storage(e.to_string()))?;
+                *content = Content::UrlReferenced(UrlReferencedContent {
+                    url: Url(url),
+                    signature: Signature::Sha256 { signature: digest },
+                });
+            }
+        }
+
+        let path = self.vcon_path(&vcon.uuid);
+        write_atomically(&path, &self.format.encode(&vcon)?)?;
+        self.unindex(&vcon.uuid);
+        self.index(&vcon);
+        Ok(())
+    }
+
+    /// vCon with this `uuid`, None when it is not stored
+    pub fn get(&self, uuid: &Uuid) -> VconResult<Option<Vcon>> {
+        if !self.entries.contains_key(uuid) {
+            return Ok(None);
+        }
+        let bytes = std::fs::read(self.vcon_path(uuid))?;
+        self.decode(&bytes).map(Some)
+    }
+
+    /// Removes a vCon, returns whether it was stored. Blobs are kept as they may be shared.
+    pub fn delete(&mut self, uuid: &Uuid) -> VconResult<bool> {
+        if !self.entries.contains_key(uuid) {
+            return Ok(false);
+        }
+        std::fs::remove_file(self.vcon_path(uuid))?;
+        self.unindex(uuid);
+        Ok(true)
+    }
+
+    /// Every stored vCon by `created_at`, the ones without it coming last
+    pub fn list(&self) -> Vec<Uuid> {
+        let mut undated = self
+            .entries
+            .iter()
+            .filter(|(_, e)| e.created_at.is_none())
+            .map(|(uuid, _)| uuid.clone())
+            .collect::<Vec<_>>();
+        undated.sort();
+        self.created_between(None, None)
+            .into_iter()
+            .chain(undated)
+            .collect()
+    }
+
+    /// vCons created within `[from, to]`, by `created_at`
+    pub fn created_between(&self, from: Option<&Date>, to: Option<&Date>) -> Vec<Uuid> {
+        use std::ops::Bound::{Included, Unbounded};
+        let (from, to) = (
+            from.map_or(Unbounded, Included),
+            to.map_or(Unbounded, Included),
+        );
+        self.created_at
+            .range((from, to))
+            .flat_map(|(_, uuids)| uuids.iter().cloned())
+            .collect()
+    }
+
+    /// vCons having a party with this `tel`
+    pub fn with_tel(&self, tel: &str) -> Vec<Uuid> {
+        uuids(&self.tels, tel)
+    }
+
+    /// vCons having a party with this `mailto`, case insensitive
+    pub fn with_mailto(&self, mailto: &str) -> Vec<Uuid> {
+        uuids(&self.mailtos, mailto.to_lowercase().as_str())
+    }
+
+    /// vCons whose `group` references `uuid`
+    pub fn groups_containing(&self, uuid: &Uuid) -> Vec<Uuid> {
+        uuids(&self.groups, uuid)
+    }
+
+    /// Stores `content` in the blob store and returns its SHA-256
+    pub fn put_blob(&self, content: &[u8]) -> VconResult<[u8; 32]> {
+        use sha2::Digest as _;
+        let digest: [u8; 32] = sha2::Sha256::digest(content).into();
+        let path = self.blob_path(&digest);
+        if !path.exists() {
+            write_atomically(&path, content)?;
+        }
+        Ok(digest)
+    }
+
+    /// Content stored in the blob store under this SHA-256
+    pub fn blob(&self, digest: &[u8; 32]) -> VconResult<Vec<u8>> {
+        Ok(std::fs::read(self.blob_path(digest))?)
+    }
+
+    fn vcon_path(&self, uuid: &Uuid) -> PathBuf {
+        self.root
+            .join(Self::VCONS)
+            .join(format!("{uuid}.{}", self.format.extension()))
+    }
+
+    fn blob_path(&self, digest: &[u8; 32]) -> PathBuf {
+        self.root.join(Self::BLOBS).join(hex(digest))
+    }
+
+    fn index(&mut self, vcon: &Vcon) {
+        let parties = vcon.parties.iter().flatten();
+        let entry = IndexEntry {
+            created_at: vcon.created_at.clone(),
+            tels: parties.clone().filter_map(|p| p.tel.clone()).collect(),
+            mailtos: parties
+                .filter_map(|p| Some(p.mailto.as_ref()?.to_lowercase()))
+                .collect(),
+            members: vcon
+                .group
+                .iter()
+                .flatten()
+                .filter_map(|r| match &r.vcon_reference {
+                    VconReference::Uuid { uuid } => Some(uuid.clone()),
+                    _ => None,
+                })
+                .collect(),
+        };
+        let uuid = &vcon.uuid;
+        if let Some(created_at) = &entry.created_at {
+            insert(&mut self.created_at, created_at.clone(), uuid);
+        }
+        for tel in &entry.tels {
+            insert(&mut self.tels, tel.clone(), uuid);
+        }
+        for mailto in &entry.mailtos {
+            insert(&mut self.mailtos, mailto.clone(), uuid);
+        }
+        for member in &entry.members {
+            insert(&mut self.groups, member.clone(), uuid);
+        }
+        self.entries.insert(uuid.clone(), entry);
+    }
+
+    fn unindex(&mut self, uuid: &Uuid) {
+        let Some(entry) = self.entries.remove(uuid) else {
+            return;
+        };
+        if let Some(created_at) = entry.created_at {
+            remove(&mut self.created_at, created_at, uuid);
+        }
+        for tel in entry.tels {
+            remove(&mut self.tels, tel, uuid);
+        }
+        for mailto in entry.mailtos {
+            remove(&mut self.mailtos, mailto, uuid);
+        }
+        for member in entry.members {
+            remove(&mut self.groups, member, uuid);
+        }
+    }
+}
+
+fn insert<K: Ord>(index: &mut BTreeMap<K, BTreeSet<Uuid>>, key: K, uuid: &Uuid) {
+    index.entry(key).or_default().insert(uuid.clone());
+}
+
+fn remove<K: Ord>(index: &mut BTreeMap<K, BTreeSet<Uuid>>, key: K, uuid: &Uuid) {
+    if let Some(uuids) = index.get_mut(&key) {
+        uuids.remove(uuid);
+        if uuids.is_empty() {
+            index.remove(&key);
+        }
+    }
+}
+
+/// vCons of a secondary index entry
+fn uuids<K, Q>(index: &BTreeMap<Q, BTreeSet<Uuid>>, key: &K) -> Vec<Uuid>
+where
+    K: Ord + ?Sized,
+    Q: Ord + std::borrow::Borrow<K>,
+{
+    index.get(key).into_iter().flatten().cloned().collect()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn write_atomically(path: &Path, content: &[u8]) -> VconResult<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(format!(".tmp-{}", std::process::id()));
+    let mut file = std::fs::File::create(&tmp)?;
+    file.write_all(content)?;
+    file.sync_all()?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+impl VconStore for VconRepository {
+    fn load(&self, uuid: &Uuid) -> VconResult<Vcon> {
+        self.get(uuid)?
+            .ok_or_else(|| VconError::UnresolvedReference(format!("unknown uuid {uuid}")))
+    }
+
+    /// Reads externalized bodies back from the blob store
+    fn fetch(&self, url: &Url) -> VconResult<Vec<u8>> {
+        let digest = url
+            .path_segments()
+            .and_then(|mut segments| segments.next_back())
+            .filter(|name| name.len() == 64)
+            .and_then(|name| {
+                let digest = (0..32)
+                    .map(|i| u8::from_str_radix(name.get(i * 2..i * 2 + 2)?, 16).ok())
+                    .collect::<Option<Vec<_>>>()?;
+                <[u8; 32]>::try_from(digest).ok()
+            })
+            .ok_or_else(|| {
+                VconError::UnresolvedReference(format!("{} is not a blob", url.as_str()))
+            })?;
+        self.blob(&digest)
+    }
+}
@@ -0,0 +1,68 @@
+use crate::{InlineContent, Url, Uuid, Vcon, VconError, VconReference, VconResult};
+use std::collections::HashMap;
+
+/// Loads the vCon a [VconReference] points to
+///
+/// Used to walk links between vCons e.g. `amended` or `group`
+pub trait VconResolver {
+    fn resolve(&self, reference: &VconReference) -> VconResult<Vcon>;
+}
+
+/// Backend holding vCons and the content they reference by url
+///
+/// Every [VconStore] is a [VconResolver]: inline references are parsed, url referenced ones are
+/// fetched then verified against their signature before being parsed.
+pub trait VconStore {
+    /// Loads a vCon by its [Uuid]
+    fn load(&self, uuid: &Uuid) -> VconResult<Vcon>;
+
+    /// Fetches the raw content behind a url
+    fn fetch(&self, url: &Url) -> VconResult<Vec<u8>>;
+}
+
+impl<S: VconStore> VconResolver for S {
+    fn resolve(&self, reference: &VconReference) -> VconResult<Vcon> {
+        match reference {
+            VconReference::Uuid { uuid } => self.load(uuid),
+            VconReference::Inline { inline_content } => resolve_inline(inline_content),
+            VconReference::Url {
+                vcon_url_referenced,
+            } => parse(&vcon_url_referenced.fetch(self)?),
+        }
+    }
+}
+
+/// In-memory store, unable to fetch url referenced content
+impl VconStore for HashMap<Uuid, Vcon> {
+    fn load(&self, uuid: &Uuid) -> VconResult<Vcon> {
+        self.get(uuid)
+            .cloned()
+            .ok_or_else(|| VconError::UnresolvedReference(format!("unknown uuid {uuid}")))
+    }
+
+    fn fetch(&self, url: &Url) -> VconResult<Vec<u8>> {
+        Err(VconError::UnresolvedReference(format!(
+            "cannot fetch {}",
+            url.as_str()
+        )))
+    }
+}
+
+/// Parses a vCon embedded in a reference
+fn resolve_inline(content: &InlineContent) -> VconResult<Vcon> {
+    parse(&content.bytes())
+}
+
+fn parse(bytes: &[u8]) -> VconResult<Vcon> {
+    #[cfg(feature = "json")]
+    {
+        serde_json::from_slice(bytes).map_err(|e| VconError::UnresolvedReference(e.to_string()))
+    }
+    #[cfg(not(feature = "json"))]
+    {
+        let _ = bytes;
+        Err(VconError::UnresolvedReference(
+            "parsing referenced vCons requires the 'json' feature".to_string(),
+        ))
+    }
+}
@@ -0,0 +1,449 @@
+use crate::{Content, Date, Dialog, DialogIndex, PartyIndex, Uuid, Vcon, VconResult};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Inverted index of the words of a collection of vCons
+///
+/// Indexes the `subject`, party names, inline text dialogs and attachments, and transcripts (see
+/// [Vcon::transcript]). Words are lowercased and split on anything which is not alphanumeric.
+///
+/// It lives in memory, the slots of removed vCons being reclaimed once they outnumber the indexed
+/// ones. [SearchIndex::save] persists it as a single JSON snapshot rewritten as a whole on every
+/// save, which suits collections whose index fits in memory: there is no incremental on-disk
+/// structure.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(ser, derive(serde::Serialize, serde::Deserialize))]
+pub struct SearchIndex {
+    /// Indexed vCons, None once removed until compacted
+    documents: Vec<Option<IndexedVcon>>,
+    /// Occurrences of every word, by order of document
+    postings: BTreeMap<String, Vec<Posting>>,
+    #[cfg_attr(ser, serde(skip))]
+    by_uuid: HashMap<Uuid, u32>,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(ser, derive(serde::Serialize, serde::Deserialize))]
+struct IndexedVcon {
+    uuid: Uuid,
+    tels: Vec<String>,
+    mailtos: Vec<String>,
+    /// `start` of every dialog
+    dialogs: Vec<Date>,
+    units: Vec<Unit>,
+}
+
+/// Text of a single [SearchSource]
+#[derive(Debug, Clone)]
+#[cfg_attr(ser, derive(serde::Serialize, serde::Deserialize))]
+struct Unit {
+    source: SearchSource,
+    words: Vec<HitWord>,
+}
+
+/// Position of a word: document, unit, index of the word in the unit
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(ser, derive(serde::Serialize, serde::Deserialize))]
+struct Posting(u32, u32, u32);
+
+/// Where a [SearchHit] was found
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+#[cfg_attr(
+    ser,
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "source", rename_all = "snake_case")
+)]
+pub enum SearchSource {
+    Subject,
+    PartyName {
+        party: PartyIndex,
+    },
+    /// Body of a text dialog
+    Text {
+        dialog: DialogIndex,
+    },
+    Transcript {
+        dialog: DialogIndex,
+        analysis: usize,
+    },
+    Attachment {
+        attachment: usize,
+    },
+}
+
+impl SearchSource {
+    pub fn dialog(&self) -> Option<DialogIndex> {
+        match self {
+            Self::Text { dialog } | Self::Transcript { dialog, .. } => Some(*dialog),
+            _ => None,
+        }
+    }
+}
+
+/// Normalized word with its offsets in seconds from the start of the dialog, when known
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(ser, derive(serde::Serialize, serde::Deserialize))]
+pub struct HitWord {
+    pub word: String,
+    #[cfg_attr(ser, serde(skip_serializing_if = "Option::is_none"))]
+    pub start: Option<f64>,
+    #[cfg_attr(ser, serde(skip_serializing_if = "Option::is_none"))]
+    pub end: Option<f64>,
+}
+
+/// Occurrence of the phrase of a [SearchQuery]
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchHit {
+    pub uuid: Uuid,
+    pub source: SearchSource,
+    /// Matched words
+    pub words: Vec<HitWord>,
+    /// Absolute date of the first matched word: the dialog `start` plus its offset, None when
+    /// unknown or out of range
+    pub time: Option<Date>,
+}
+
+impl SearchHit {
+    pub fn dialog(&self) -> Option<DialogIndex> {
+        self.source.dialog()
+    }
+}
+
+/// Phrase to look for, all its words having to appear consecutively, and filters on the vCons
+#[derive(Debug, Clone, Default)]
+pub struct SearchQuery {
+    pub phrase: String,
+    /// Only vCons having a party with this `tel`
+    pub tel: Option<String>,
+    /// Only vCons having a party with this `mailto`, case insensitive
+    pub mailto: Option<String>,
+    /// Only hits in dialogs starting at or after this date. Hits outside dialogs only need one of
+    /// the vCon's dialogs to be in range.
+    pub from: Option<Date>,
+    /// Only hits in dialogs starting at or before this date
+    pub to: Option<Date>,
+}
+
+impl SearchQuery {
+    pub fn new(phrase: impl Into<String>) -> Self {
+        Self {
+            phrase: phrase.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn tel(mut self, tel: impl Into<String>) -> Self {
+        self.tel = Some(tel.into());
+        self
+    }
+
+    pub fn mailto(mut self, mailto: impl Into<String>) -> Self {
+        self.mailto = Some(mailto.into());
+        self
+    }
+
+    pub fn between(mut self, from: Option<Date>, to: Option<Date>) -> Self {
+        (self.from, self.to) = (from, to);
+        self
+    }
+
+    fn in_range(&self, start: &Date) -> bool {
+        self.from.as_ref().is_none_or(|from| start >= from)
+            && self.to.as_ref().is_none_or(|to| start <= to)
+    }
+}
+
+impl SearchIndex {
+    /// Number of indexed vCons
+    pub fn len(&self) -> usize {
+        self.by_uuid.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_uuid.is_empty()
+    }
+
+    pub fn contains(&self, uuid: &Uuid) -> bool {
+        self.by_uuid.contains_key(uuid)
+    }
+
+    /// Indexes `vcon`, replacing any previously indexed vCon with the same `uuid`.
+    ///
+    /// Fails when one of its transcripts cannot be decoded.
+    pub fn add(&mut self, vcon: &Vcon) -> VconResult<()> {
+        let mut units = vec![];
+        let mut text = |source, text: &str| units.push(Unit::from_text(source, text));
+
+        if let Some(subject) = &vcon.subject {
+            text(SearchSource::Subject, subject);
+        }
+        for (party, name) in (0..).zip(vcon.parties.iter().flatten().map(|p| &p.name)) {
+            if let Some(name) = name {
+                text(SearchSource::PartyName { party }, name);
+            }
+        }
+        for (dialog, object) in (0..).zip(vcon.dialog.iter().flatten()) {
+            if let Dialog::Text {
+                content_parameters,
+                content,
+                ..
+            } = &object.dialog
+            {
+                if let Some(body) = inline_text(content, content_parameters.mime.as_deref()) {
+                    text(SearchSource::Text { dialog }, &body);
+                }
+            }
+        }
+        for (attachment, a) in vcon.attachments.iter().flatten().enumerate() {
+            if let Some(body) = inline_text(&a.content, a.content_parameters.mime.as_deref()) {
+                text(SearchSource::Attachment { attachment }, &body);
+            }
+        }
+        for (analysis, a) in vcon.analysis.iter().flatten().enumerate() {
+            let Some(transcript) = vcon.transcript(analysis)? else {
+                continue;
+            };
+            let mut unit = Unit {
+                source: SearchSource::Transcript {
+                    dialog: a.dialog,
+                    analysis,
+                },
+                words: vec![],
+            };
+            for segment in &transcript.segments {
+                if segment.words.is_empty() {
+                    unit.push(&segment.text, Some(segment.start), Some(segment.end));
+                }
+                for word in &segment.words {
+                    unit.push(&word.text, Some(word.start), Some(word.end));
+                }
+            }
+            units.push(unit);
+        }
+
+        self.remove(&vcon.uuid);
+        let document = self.documents.len() as u32;
+        for (index, unit) in (0..).zip(&units) {
+            for (position, word) in (0..).zip(&unit.words) {
+                self.postings
+                    .entry(word.word.clone())
+                    .or_default()
+                    .push(Posting(document, index, position));
+            }
+        }
+        let parties = vcon.parties.iter().flatten();
+        self.documents.push(Some(IndexedVcon {
+            uuid: vcon.uuid.clone(),
+            tels: parties.clone().filter_map(|p| p.tel.clone()).collect(),
+            mailtos: parties.filter_map(|p| p.mailto.clone()).collect(),
+            dialogs: vcon
+                .dialog
+                .iter()
+                .flatten()
+                .map(|d| d.start.clone())
+                .collect(),
+            units,
+        }));
+        self.by_uuid.insert(vcon.uuid.clone(), document);
+        Ok(())
+    }
+
+    /// Removes a vCon from the index, returns whether it was indexed
+    pub fn remove(&mut self, uuid: &Uuid) -> bool {
+        let Some(document) = self.by_uuid.remove(uuid) else {
+            return false;
+        };
+        let removed = self.documents[document as usize].take().expect("indexed");
+        let words = removed.units.iter().flat_map(|u| &u.words);
+        for word in words.map(|w| &w.word).collect::<BTreeSet<_>>() {
+            let Some(postings) = self.postings.get_mut(word) else {
+                continue;
+            };
+            let from = postings.partition_point(|p| p.0 < document);
+            let to = postings.partition_point(|p| p.0 <= document);
+            postings.drain(from..to);
+            if postings.is_empty() {
+                self.postings.remove(word);
+            }
+        }
+        if self.documents.len() > 2 * self.by_uuid.len() {
+            self.compact();
+        }
+        true
+    }
+
+    /// Drops the slots of removed vCons, renumbering the others
+    fn compact(&mut self) {
+        let mut numbers = vec![None; self.documents.len()];
+        let mut next = 0;
+        for (number, document) in numbers.iter_mut().zip(&self.documents) {
+            if document.is_some() {
+                *number = Some(next);
+                next += 1;
+            }
+        }
+        self.documents.retain(Option::is_some);
+        for posting in self.postings.values_mut().flatten() {
+            posting.0 = numbers[posting.0 as usize].expect("postings of removed vCons are dropped");
+        }
+        for document in self.by_uuid.values_mut() {
+            *document = numbers[*document as usize].expect("indexed");
+        }
+    }
+
+    /// Every occurrence of the query phrase, by order of indexing
+    pub fn search(&self, query: &SearchQuery) -> Vec<SearchHit> {
+        let phrase = tokenize(&query.phrase).collect::<Vec<_>>();
+        let Some(first) = phrase.first() else {
+            return vec![];
+        };
+
+        let mut hits = vec![];
+        for Posting(document, unit, position) in self.postings.get(first).into_iter().flatten() {
+            let Some(document) = &self.documents[*document as usize] else {
+                continue;
+            };
+            if !document.matches(query) {
+                continue;
+            }
+            let unit = &document.units[*unit as usize];
+            let position = *position as usize;
+            let Some(words) = unit.words.get(position..position + phrase.len()) else {
+                continue;
+            };
+            if words.iter().zip(&phrase).any(|(w, p)| w.word != *p) {
+                continue;
+            }
+
+            let dialog_start = unit
+                .source
+                .dialog()
+                .and_then(|d| document.dialogs.get(d as usize));
+            let in_range = match dialog_start {
+                Some(start) => query.in_range(start),
+                None => {
+                    (query.from.is_none() && query.to.is_none())
+                        || document.dialogs.iter().any(|s| query.in_range(s))
+                }
+            };
+            if !in_range {
+                continue;
+            }
+            let time = dialog_start.and_then(|start| match words[0].start {
+                Some(offset) => start.checked_add_seconds(offset),
+                None => Some(start.clone()),
+            });
+            hits.push(SearchHit {
+                uuid: document.uuid.clone(),
+                source: unit.source,
+                words: words.to_vec(),
+                time,
+            });
+        }
+        hits
+    }
+}
+
+impl IndexedVcon {
+    fn matches(&self, query: &SearchQuery) -> bool {
+        query.tel.as_ref().is_none_or(|tel| self.tels.contains(tel))
+            && query
+                .mailto
+                .as_ref()
+                .is_none_or(|mailto| self.mailtos.iter().any(|m| m.eq_ignore_ascii_case(mailto)))
+    }
+}
+
+impl Unit {
+    fn from_text(source: SearchSource, text: &str) -> Self {
+        let mut unit = Self {
+            source,
+            words: vec![],
+        };
+        unit.push(text, None, None);
+        unit
+    }
+
+    fn push(&mut self, text: &str, start: Option<f64>, end: Option<f64>) {
+        self.words
+            .extend(tokenize(text).map(|word| HitWord { word, start, end }));
+    }
+}
+
+fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+}
+
+/// Inline body which is text, as far as its mimetype tells
+fn inline_text(content: &Content, mime: Option<&String>) -> Option<String> {
+    if mime.is_some_and(|m| !m.starts_with("text/")) {
+        return None;
+    }
+    String::from_utf8(content.inline_bytes()?.into_owned()).ok()
+}
+
+#[cfg(feature = "json")]
+impl SearchIndex {
+    /// Loads an index saved with [SearchIndex::save], an empty one when the file does not exist
+    pub fn open(path: impl AsRef<std::path::Path>) -> VconResult<Self> {
+        let bytes = match std::fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut index: Self = serde_json::from_slice(&bytes)
+            .map_err(|e| crate::VconError::InvalidSearchIndex(e.to_string()))?;
+        index.by_uuid = (0..)
+            .zip(&index.documents)
+            .filter_map(|(i, d)| Some((d.as_ref()?.uuid.clone(), i)))
+            .collect();
+        index
+            .check()
+            .map_err(crate::VconError::InvalidSearchIndex)?;
+        Ok(index)
+    }
+
+    /// Checks that every posting points at its word in an indexed vCon, by order of document,
+    /// and that no uuid is indexed twice
+    fn check(&self) -> Result<(), String> {
+        let indexed = self.documents.iter().filter(|d| d.is_some()).count();
+        if self.by_uuid.len() != indexed {
+            return Err("a vCon is indexed twice".to_string());
+        }
+        for (word, postings) in &self.postings {
+            if postings.is_empty() {
+                return Err(format!("no postings for {word:?}"));
+            }
+            if postings.windows(2).any(|p| p[0].0 > p[1].0) {
+                return Err(format!("postings of {word:?} are out of order"));
+            }
+            for Posting(document, unit, position) in postings {
+                let found = self
+                    .documents
+                    .get(*document as usize)
+                    .and_then(Option::as_ref)
+                    .and_then(|d| d.units.get(*unit as usize))
+                    .and_then(|u| u.words.get(*position as usize));
+                if found.is_none_or(|w| w.word != *word) {
+                    return Err(format!(
+                        "posting ({document}, {unit}, {position}) of {word:?} is out of range"
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the index to `path`, replacing it atomically
+    pub fn save(&self, path: impl AsRef<std::path::Path>) -> VconResult<()> {
+        let path = path.as_ref();
+        let bytes = serde_json::to_vec(self)
+            .map_err(|e| crate::VconError::InvalidSearchIndex(e.to_string()))?;
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        std::fs::write(&tmp, bytes)?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
+}
@@ -93,6 +93,16 @@ impl Signature {
             Self::Sha512 { .. } => SignatureAlg::Sha512,
         }
     }
+
+    /// Whether `content` hashes to this digest
+    pub fn verify(&self, content: &[u8]) -> bool {
+        use sha2::Digest as _;
+        match self {
+            Self::Sha256 { signature } => sha2::Sha256::digest(content).as_slice() == signature,
+            Self::Sha384 { signature } => sha2::Sha384::digest(content).as_slice() == signature,
+            Self::Sha512 { signature } => sha2::Sha512::digest(content).as_slice() == signature,
+        }
+    }
 }
 
 #[cfg(ser)]
@@ -0,0 +1,454 @@
+use crate::{Dialog, ParseOptions, Uuid, Vcon, VconError, VconResult, VconStore};
+use rusqlite::{params, Connection, OptionalExtension as _, Transaction};
+use std::path::Path;
+
+/// vCons persisted in SQLite, with a relational projection for analytics
+///
+/// The `vcons` table keeps every vCon as a JSON document, which is what [SqliteStore::load]
+/// reads back, so that nothing is lost. Its content is also projected into the following tables,
+/// keyed by the vCon `uuid` and the index of each item in its array:
+/// - `vcons(uuid, version, subject, created_at, updated_at, extensions, critical, document)`,
+///   `extensions` and `critical` being JSON arrays
+/// - `parties(vcon, idx, tel, mailto, name, role, uuid, sip, did, timezone, contact_list)`
+/// - `dialogs(vcon, idx, type, start, duration, originator, disposition, mimetype,
+///   party_history_error, session_id, application, message_id)` and
+///   `dialog_parties(vcon, dialog, party)`, `party_history_error` being why the `party_history` of
+///   the dialog is inconsistent, if it is
+/// - `party_events(vcon, dialog, idx, party, event, time)`
+/// - `party_intervals(vcon, dialog, party, kind, start, end, seconds)` where `kind` is one of
+///   `present`, `on_hold` or `muted` (see [crate::DialogObject::presence]), none being stored for
+///   an inconsistent `party_history`
+/// - `attachments(vcon, idx, type, start, party, mimetype)`
+/// - `analysis(vcon, idx, type, dialog, vendor, schema, product, document)`, `document` being the
+///   raw JSON of the analysis
+///
+/// Dates are RFC 3339 strings, usable with SQLite date functions.
+///
+/// ```sql
+/// -- vCons in which Alice was on hold more than 2 minutes
+/// SELECT i.vcon FROM party_intervals i
+/// JOIN parties p ON p.vcon = i.vcon AND p.idx = i.party
+/// WHERE p.name = 'Alice' AND i.kind = 'on_hold'
+/// GROUP BY i.vcon HAVING SUM(i.seconds) > 120
+/// ```
+///
+/// # Migrations
+///
+/// The schema is created and upgraded by [SqliteStore::MIGRATIONS], applied in order when the
+/// store is opened, the number of applied ones being kept in `PRAGMA user_version`. Each migration
+/// names the [crate::VconVersion] whose fields it projects: supporting a new version of the draft
+/// means appending a migration, never editing an applied one. The version of every vCon is kept in
+/// `vcons.version`.
+pub struct SqliteStore {
+    connection: Connection,
+    options: ParseOptions,
+}
+
+/// Step of the schema of a [SqliteStore]
+#[derive(Debug, Copy, Clone)]
+pub struct Migration {
+    /// [crate::VconVersion] this step introduces support for
+    pub vcon_version: &'static str,
+    pub sql: &'static str,
+}
+
+impl SqliteStore {
+    pub const MIGRATIONS: &'static [Migration] = &[
+        Migration {
+            vcon_version: "0.0.1",
+            sql: "
+            CREATE TABLE vcons (
+                uuid TEXT PRIMARY KEY,
+                version TEXT NOT NULL,
+                subject TEXT,
+                created_at TEXT,
+                updated_at TEXT,
+                document TEXT NOT NULL
+            );
+            CREATE TABLE parties (
+                vcon TEXT NOT NULL REFERENCES vcons(uuid) ON DELETE CASCADE,
+                idx INTEGER NOT NULL,
+                tel TEXT,
+                mailto TEXT,
+                name TEXT,
+                role TEXT,
+                uuid TEXT,
+                PRIMARY KEY (vcon, idx)
+            );
+            CREATE INDEX parties_tel ON parties(tel);
+            CREATE INDEX parties_mailto ON parties(mailto);
+            CREATE TABLE dialogs (
+                vcon TEXT NOT NULL REFERENCES vcons(uuid) ON DELETE CASCADE,
+                idx INTEGER NOT NULL,
+                type TEXT NOT NULL,
+                start TEXT NOT NULL,
+                duration REAL,
+                originator INTEGER,
+                disposition TEXT,
+                mimetype TEXT,
+                PRIMARY KEY (vcon, idx)
+            );
+            CREATE TABLE dialog_parties (
+                vcon TEXT NOT NULL REFERENCES vcons(uuid) ON DELETE CASCADE,
+                dialog INTEGER NOT NULL,
+                party INTEGER NOT NULL
+            );
+            CREATE TABLE party_events (
+                vcon TEXT NOT NULL REFERENCES vcons(uuid) ON DELETE CASCADE,
+                dialog INTEGER NOT NULL,
+                idx INTEGER NOT NULL,
+                party INTEGER NOT NULL,
+                event TEXT NOT NULL,
+                time TEXT NOT NULL,
+                PRIMARY KEY (vcon, dialog, idx)
+            );
+            CREATE TABLE party_intervals (
+                vcon TEXT NOT NULL REFERENCES vcons(uuid) ON DELETE CASCADE,
+                dialog INTEGER NOT NULL,
+                party INTEGER NOT NULL,
+                kind TEXT NOT NULL,
+                start TEXT NOT NULL,
+                end TEXT NOT NULL,
+                seconds REAL NOT NULL
+            );
+            CREATE TABLE attachments (
+                vcon TEXT NOT NULL REFERENCES vcons(uuid) ON DELETE CASCADE,
+                idx INTEGER NOT NULL,
+                type TEXT NOT NULL,
+                start TEXT NOT NULL,
+                party INTEGER NOT NULL,
+                mimetype TEXT,
+                PRIMARY KEY (vcon, idx)
+            );
+            CREATE TABLE analysis (
+                vcon TEXT NOT NULL REFERENCES vcons(uuid) ON DELETE CASCADE,
+                idx INTEGER NOT NULL,
+                type TEXT NOT NULL,
+                dialog INTEGER NOT NULL,
+                vendor TEXT,
+                schema TEXT,
+                product TEXT,
+                document TEXT NOT NULL,
+                PRIMARY KEY (vcon, idx)
+            );
+        ",
+        },
+        Migration {
+            vcon_version: "0.0.1",
+            sql: "ALTER TABLE dialogs ADD COLUMN party_history_error TEXT;",
+        },
+        Migration {
+            vcon_version: "0.3.0",
+            sql: "
+            ALTER TABLE vcons ADD COLUMN extensions TEXT;
+            ALTER TABLE vcons ADD COLUMN critical TEXT;
+            UPDATE vcons SET
+                extensions = json_extract(document, '$.extensions'),
+                critical = json_extract(document, '$.critical');
+            ALTER TABLE parties ADD COLUMN sip TEXT;
+            ALTER TABLE parties ADD COLUMN did TEXT;
+            ALTER TABLE parties ADD COLUMN timezone TEXT;
+            ALTER TABLE parties ADD COLUMN contact_list TEXT;
+            UPDATE parties SET (sip, did, timezone, contact_list) = (
+                SELECT
+                    json_extract(document, '$.parties[' || parties.idx || '].sip'),
+                    json_extract(document, '$.parties[' || parties.idx || '].did'),
+                    json_extract(document, '$.parties[' || parties.idx || '].timezone'),
+                    json_extract(document, '$.parties[' || parties.idx || '].contact_list')
+                FROM vcons WHERE vcons.uuid = parties.vcon
+            );
+            ALTER TABLE dialogs ADD COLUMN session_id TEXT;
+            ALTER TABLE dialogs ADD COLUMN application TEXT;
+            ALTER TABLE dialogs ADD COLUMN message_id TEXT;
+            UPDATE dialogs SET (session_id, application, message_id) = (
+                SELECT
+                    json_extract(document, '$.dialog[' || dialogs.idx || '].session_id'),
+                    json_extract(document, '$.dialog[' || dialogs.idx || '].application'),
+                    json_extract(document, '$.dialog[' || dialogs.idx || '].message_id')
+                FROM vcons WHERE vcons.uuid = dialogs.vcon
+            );
+        ",
+        },
+    ];
+
+    pub fn open(path: impl AsRef<Path>) -> VconResult<Self> {
+        Self::from_connection(Connection::open(path)?)
+    }
+
+    pub fn open_in_memory() -> VconResult<Self> {
+        Self::from_connection(Connection::open_in_memory()?)
+    }
+
+    /// Uses an existing connection, applying the migrations it lacks
+    pub fn from_connection(mut connection: Connection) -> VconResult<Self> {
+        connection.pragma_update(None, "foreign_keys", true)?;
+        let applied: usize = connection.pragma_query_value(None, "user_version", |r| r.get(0))?;
+        if applied > Self::MIGRATIONS.len() {
+            return Err(VconError::Storage(format!(
+                "schema has {applied} migrations whereas only {} are known",
+                Self::MIGRATIONS.len()
+            )));
+        }
+        let tx = connection.transaction()?;
+        for migration in &Self::MIGRATIONS[applied..] {
+            tx.execute_batch(migration.sql)?;
+        }
+        tx.pragma_update(None, "user_version", Self::MIGRATIONS.len())?;
+        tx.commit()?;
+        Ok(Self {
+            connection,
+            options: ParseOptions::default(),
+        })
+    }
+
+    /// Limits stored vCons are parsed within when loaded, the default ones otherwise
+    pub fn with_options(mut self, options: ParseOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Connection to run analytics queries on
+    pub fn connection(&self) -> &Connection {
+        &self.connection
+    }
+
+    /// Latest [crate::VconVersion] supported by the schema
+    pub fn schema_version(&self) -> &'static str {
+        Self::MIGRATIONS
+            .last()
+            .map(|m| m.vcon_version)
+            .unwrap_or_default()
+    }
+
+    /// Stores `vcon` and its projection, replacing any vCon with the same `uuid`. Fails with
+    /// [VconError::UnsupportedVersion] or [VconError::UnsupportedCriticalExtensions] as it could
+    /// not be loaded back otherwise.
+    pub fn save(&mut self, vcon: &Vcon) -> VconResult<()> {
+        vcon.version.version()?;
+        vcon.check_critical(&self.options.supported_extensions)?;
+        let tx = self.connection.transaction()?;
+        let uuid = vcon.uuid.to_string();
+        tx.execute("DELETE FROM vcons WHERE uuid = ?1", [&uuid])?;
+        tx.execute(
+            "INSERT INTO vcons (uuid, version, subject, created_at, updated_at, extensions, critical,
+                                document)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                uuid,
+                vcon.version.as_str(),
+                vcon.subject,
+                vcon.created_at.as_ref().map(text).transpose()?,
+                vcon.updated_at.as_ref().map(text).transpose()?,
+                vcon.extensions.as_ref().map(json).transpose()?,
+                vcon.critical.as_ref().map(json).transpose()?,
+                json(vcon)?,
+            ],
+        )?;
+        project(&tx, &uuid, vcon)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// vCon with this `uuid`, None when it is not stored. The stored document is parsed like
+    /// [Vcon::parse_json] does, within the [ParseOptions] of this store.
+    pub fn load(&self, uuid: &Uuid) -> VconResult<Option<Vcon>> {
+        let document: Option<String> = self
+            .connection
+            .query_row(
+                "SELECT document FROM vcons WHERE uuid = ?1",
+                [uuid.to_string()],
+                |r| r.get(0),
+            )
+            .optional()?;
+        document
+            .map(|d| Vcon::parse_json(d.as_bytes(), &self.options))
+            .transpose()
+    }
+
+    /// Removes a vCon and its projection, returns whether it was stored
+    pub fn delete(&mut self, uuid: &Uuid) -> VconResult<bool> {
+        let deleted = self
+            .connection
+            .execute("DELETE FROM vcons WHERE uuid = ?1", [uuid.to_string()])?;
+        Ok(deleted > 0)
+    }
+}
+
+fn project(tx: &Transaction, uuid: &str, vcon: &Vcon) -> VconResult<()> {
+    for (idx, party) in vcon.parties.iter().flatten().enumerate() {
+        tx.execute(
+            "INSERT INTO parties (vcon, idx, tel, mailto, name, role, uuid, sip, did, timezone,
+                                  contact_list)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            params![
+                uuid,
+                idx,
+                party.tel,
+                party.mailto,
+                party.name,
+                party.role,
+                party.uuid.as_ref().map(ToString::to_string),
+                party.sip,
+                party.did,
+                party.timezone,
+                party.contact_list,
+            ],
+        )?;
+    }
+
+    for (idx, object) in vcon.dialog.iter().flatten().enumerate() {
+        let (duration, originator, disposition, mime) = match &object.dialog {
+            Dialog::Recording {
+                duration,
+                originator,
+                content_parameters,
+                ..
+            }
+            | Dialog::Text {
+                duration,
+                originator,
+                content_parameters,
+                ..
+            } => (
+                duration.as_ref().map(|d| d.as_secs_f64()),
+                *originator,
+                None,
+                content_parameters.mime.as_deref().cloned(),
+            ),
+            Dialog::Incomplete { disposition } => (None, None, Some(disposition.as_str()), None),
+            Dialog::Transfer { .. } => (None, None, None, None),
+        };
+        let presence = object.presence();
+        tx.execute(
+            "INSERT INTO dialogs (vcon, idx, type, start, duration, originator, disposition, mimetype,
+                                  party_history_error, session_id, application, message_id)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+            params![
+                uuid,
+                idx,
+                object.dialog.typ(),
+                text(&object.start)?,
+                duration,
+                originator,
+                disposition,
+                mime,
+                presence.as_ref().err().map(ToString::to_string),
+                object.session_id,
+                object.application,
+                object.message_id,
+            ],
+        )?;
+        for party in object
+            .dialog
+            .parties()
+            .map(|p| p.indexes())
+            .unwrap_or_default()
+        {
+            tx.execute(
+                "INSERT INTO dialog_parties (vcon, dialog, party) VALUES (?1, ?2, ?3)",
+                params![uuid, idx, party],
+            )?;
+        }
+        for (event_idx, event) in object.party_history.iter().flatten().enumerate() {
+            tx.execute(
+                "INSERT INTO party_events (vcon, dialog, idx, party, event, time)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    uuid,
+                    idx,
+                    event_idx,
+                    event.party,
+                    text(&event.event)?,
+                    text(&event.time)?,
+                ],
+            )?;
+        }
+        // an inconsistent party_history is stored as is, without intervals
+        for (party, presence) in presence.unwrap_or_default() {
+            let intervals = [
+                ("present", &presence.present),
+                ("on_hold", &presence.on_hold),
+                ("muted", &presence.muted),
+            ];
+            for (kind, intervals) in intervals {
+                for interval in intervals {
+                    tx.execute(
+                        "INSERT INTO party_intervals (vcon, dialog, party, kind, start, end, seconds)
+                         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                        params![
+                            uuid,
+                            idx,
+                            party,
+                            kind,
+                            text(&interval.start)?,
+                            text(&interval.end)?,
+                            interval.duration().as_seconds_f64(),
+                        ],
+                    )?;
+                }
+            }
+        }
+    }
+
+    for (idx, attachment) in vcon.attachments.iter().flatten().enumerate() {
+        tx.execute(
+            "INSERT INTO attachments (vcon, idx, type, start, party, mimetype)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                uuid,
+                idx,
+                attachment.typ,
+                text(&attachment.start)?,
+                attachment.party,
+                attachment.content_parameters.mime.as_deref(),
+            ],
+        )?;
+    }
+
+    for (idx, analysis) in vcon.analysis.iter().flatten().enumerate() {
+        tx.execute(
+            "INSERT INTO analysis (vcon, idx, type, dialog, vendor, schema, product, document)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                uuid,
+                idx,
+                analysis.typ,
+                analysis.dialog,
+                analysis.vendor,
+                analysis.schema,
+                analysis.product,
+                json(analysis)?,
+            ],
+        )?;
+    }
+    Ok(())
+}
+
+fn json(value: &impl serde::Serialize) -> VconResult<String> {
+    serde_json::to_string(value).map_err(|e| VconError::Storage(e.to_string()))
+}
+
+/// Serialized form of a value serialized as a JSON string e.g. a date
+fn text(value: &impl serde::Serialize) -> VconResult<String> {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(s)) => Ok(s),
+        Ok(other) => Err(VconError::Storage(format!(
+            "expected a string, got {other}"
+        ))),
+        Err(e) => Err(VconError::Storage(e.to_string())),
+    }
+}
+
+impl VconStore for SqliteStore {
+    fn load(&self, uuid: &Uuid) -> VconResult<Vcon> {
+        SqliteStore::load(self, uuid)?
+            .ok_or_else(|| VconError::UnresolvedReference(format!("unknown uuid {uuid}")))
+    }
+
+    fn fetch(&self, url: &crate::Url) -> VconResult<Vec<u8>> {
+        Err(VconError::UnresolvedReference(format!(
+            "cannot fetch {}",
+            url.as_str()
+        )))
+    }
+}
@@ -0,0 +1,116 @@
+use crate::{Date, Dialog, DialogIndex, Event, PartyIndex, Vcon, VconResult};
+
+/// Something which happened during a conversation, at an absolute date
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimelineEntry {
+    pub time: Date,
+    /// Dialog it happened in
+    pub dialog: DialogIndex,
+    pub kind: TimelineEntryKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TimelineEntryKind {
+    /// `start` of a recording or text dialog
+    DialogStart,
+    /// `start` + `duration` of a recording or text dialog
+    DialogEnd,
+    /// Entry of the dialog's `party_history`
+    PartyEvent { party: PartyIndex, event: Event },
+    /// Text dialog, with its body when it is inline UTF-8
+    Message {
+        originator: Option<PartyIndex>,
+        text: Option<String>,
+    },
+    /// Segment of a transcript of a recording
+    Utterance {
+        /// Index of the transcript analysis
+        analysis: usize,
+        /// See [Vcon::transcript]
+        party: Option<PartyIndex>,
+        text: String,
+        end: Date,
+    },
+}
+
+impl Vcon {
+    /// Everything which happened in every dialog, in chronological order.
+    ///
+    /// Merges dialog boundaries, `party_history` events, text messages and the segments of
+    /// transcript analyses (see [Vcon::transcript]) whose offsets are converted to dates
+    /// with [DialogObject::at], segments whose offsets are out of range being skipped. The sort is
+    /// stable so that e.g. a dialog starts before the events happening at the same date.
+    ///
+    /// Fails when a transcript references a dialog which does not exist or cannot be decoded.
+    pub fn timeline(&self) -> VconResult<Vec<TimelineEntry>> {
+        let dialogs = self.dialog.as_deref().unwrap_or_default();
+        let mut timeline = vec![];
+
+        for (index, object) in dialogs.iter().enumerate() {
+            let dialog = index as DialogIndex;
+            let entry = |time: Date, kind| TimelineEntry { time, dialog, kind };
+
+            match &object.dialog {
+                Dialog::Recording { .. } => {
+                    timeline.push(entry(object.start.clone(), TimelineEntryKind::DialogStart))
+                }
+                Dialog::Text {
+                    originator,
+                    content,
+                    ..
+                } => {
+                    timeline.push(entry(object.start.clone(), TimelineEntryKind::DialogStart));
+                    let text = content
+                        .inline_bytes()
+                        .and_then(|b| String::from_utf8(b.into_owned()).ok());
+                    timeline.push(entry(
+                        object.start.clone(),
+                        TimelineEntryKind::Message {
+                            originator: *originator,
+                            text,
+                        },
+                    ));
+                }
+                Dialog::Transfer { .. } | Dialog::Incomplete { .. } => {}
+            }
+            for event in object.party_history.iter().flatten() {
+                timeline.push(entry(
+                    event.time.clone(),
+                    TimelineEntryKind::PartyEvent {
+                        party: event.party,
+                        event: event.event,
+                    },
+                ));
+            }
+            if let Some(end) = object.end() {
+                timeline.push(entry(end, TimelineEntryKind::DialogEnd));
+            }
+        }
+
+        for (index, analysis) in self.analysis.iter().flatten().enumerate() {
+            let Some(transcript) = self.transcript(index)? else {
+                continue;
+            };
+            let object = &dialogs[analysis.dialog as usize];
+            for segment in transcript.segments {
+                let (Some(time), Some(end)) = (object.at(segment.start), object.at(segment.end))
+                else {
+                    continue;
+                };
+                timeline.push(TimelineEntry {
+                    time,
+                    dialog: analysis.dialog,
+                    kind: TimelineEntryKind::Utterance {
+                        analysis: index,
+                        party: segment.speaker,
+                        text: segment.text,
+                        end,
+                    },
+                });
+            }
+        }
+
+        timeline.sort_by(|a, b| a.time.cmp(&b.time));
+        Ok(timeline)
+    }
+}
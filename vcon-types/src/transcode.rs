@@ -0,0 +1,213 @@
+//! Streaming CBOR deserializer, reading one item at a time so that [crate::ParseOptions] limits are
+//! checked before the rest of the document is decoded
+
+use ciborium_ll::{simple, Decoder, Header};
+use serde::de::{
+    DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess, VariantAccess, Visitor,
+};
+use std::fmt::{Debug, Display};
+
+/// Size of the chunks byte and text strings are read by
+const CHUNK: usize = 4096;
+
+#[derive(Debug)]
+pub(crate) struct Error(String);
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl serde::de::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Self(msg.to_string())
+    }
+}
+
+impl<T: Debug> From<ciborium_ll::Error<T>> for Error {
+    fn from(e: ciborium_ll::Error<T>) -> Self {
+        match e {
+            ciborium_ll::Error::Io(e) => Self(format!("invalid CBOR: {e:?}")),
+            ciborium_ll::Error::Syntax(offset) => Self(format!("invalid CBOR at byte {offset}")),
+        }
+    }
+}
+
+/// Deserializes CBOR through [serde::Deserializer::deserialize_any] only, tags being given as
+/// enums whose variant is the tag number
+pub(crate) struct CborDeserializer<'a> {
+    decoder: Decoder<&'a [u8]>,
+}
+
+impl<'a> CborDeserializer<'a> {
+    pub(crate) fn new(cbor: &'a [u8]) -> Self {
+        Self {
+            decoder: cbor.into(),
+        }
+    }
+
+    /// Fails unless the whole input was read
+    pub(crate) fn end(&mut self, len: usize) -> Result<(), Error> {
+        let offset = self.decoder.offset();
+        if offset != len {
+            return Err(Error(format!("trailing bytes at byte {offset}")));
+        }
+        Ok(())
+    }
+
+    fn bytes(&mut self, len: Option<usize>) -> Result<Vec<u8>, Error> {
+        let mut bytes = vec![];
+        let mut chunk = [0; CHUNK];
+        let mut segments = self.decoder.bytes(len);
+        while let Some(mut segment) = segments.pull()? {
+            while let Some(read) = segment.pull(&mut chunk)? {
+                bytes.extend_from_slice(read);
+            }
+        }
+        Ok(bytes)
+    }
+
+    fn text(&mut self, len: Option<usize>) -> Result<String, Error> {
+        let mut text = String::new();
+        let mut chunk = [0; CHUNK];
+        let mut segments = self.decoder.text(len);
+        while let Some(mut segment) = segments.pull()? {
+            while let Some(read) = segment.pull(&mut chunk)? {
+                text.push_str(read);
+            }
+        }
+        Ok(text)
+    }
+
+    /// Whether an indefinite length array or map goes on, its break being consumed otherwise
+    fn more(&mut self, len: &mut Option<usize>) -> Result<bool, Error> {
+        match len {
+            Some(0) => Ok(false),
+            Some(left) => {
+                *left -= 1;
+                Ok(true)
+            }
+            None => match self.decoder.pull()? {
+                Header::Break => Ok(false),
+                header => {
+                    self.decoder.push(header);
+                    Ok(true)
+                }
+            },
+        }
+    }
+}
+
+impl<'de> serde::Deserializer<'de> for &mut CborDeserializer<'_> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let offset = self.decoder.offset();
+        match self.decoder.pull()? {
+            Header::Positive(n) => visitor.visit_u64(n),
+            Header::Negative(n) => match i64::try_from(n) {
+                Ok(n) => visitor.visit_i64(-1 - n),
+                Err(_) => Err(Error(format!("integer out of range at byte {offset}"))),
+            },
+            Header::Float(f) => visitor.visit_f64(f),
+            Header::Simple(simple::FALSE) => visitor.visit_bool(false),
+            Header::Simple(simple::TRUE) => visitor.visit_bool(true),
+            Header::Simple(simple::NULL | simple::UNDEFINED) => visitor.visit_none(),
+            Header::Tag(tag) => visitor.visit_enum(Tagged { de: self, tag }),
+            Header::Bytes(len) => visitor.visit_byte_buf(self.bytes(len)?),
+            Header::Text(len) => visitor.visit_string(self.text(len)?),
+            Header::Array(len) => visitor.visit_seq(Items { de: self, len }),
+            Header::Map(len) => visitor.visit_map(Items { de: self, len }),
+            Header::Simple(_) | Header::Break => {
+                Err(Error(format!("unexpected CBOR item at byte {offset}")))
+            }
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+/// Items of an array or entries of a map, `len` being those left if known
+struct Items<'a, 'b> {
+    de: &'a mut CborDeserializer<'b>,
+    len: Option<usize>,
+}
+
+impl<'de> SeqAccess<'de> for Items<'_, '_> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Error> {
+        if !self.de.more(&mut self.len)? {
+            return Ok(None);
+        }
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+}
+
+impl<'de> MapAccess<'de> for Items<'_, '_> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        if !self.de.more(&mut self.len)? {
+            return Ok(None);
+        }
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        seed.deserialize(&mut *self.de)
+    }
+}
+
+/// Tagged item
+struct Tagged<'a, 'b> {
+    de: &'a mut CborDeserializer<'b>,
+    tag: u64,
+}
+
+impl<'de> EnumAccess<'de> for Tagged<'_, '_> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self), Error> {
+        let tag = seed.deserialize(IntoDeserializer::<Error>::into_deserializer(self.tag))?;
+        Ok((tag, self))
+    }
+}
+
+impl<'de> VariantAccess<'de> for Tagged<'_, '_> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        Err(Error("expected a tagged item".to_string()))
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Error> {
+        seed.deserialize(self.de)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, _visitor: V) -> Result<V::Value, Error> {
+        Err(Error("expected a tagged item".to_string()))
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Error> {
+        Err(Error("expected a tagged item".to_string()))
+    }
+}
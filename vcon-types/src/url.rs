@@ -50,7 +50,7 @@ use derive_more::{Deref, DerefMut, Into};
 #[derive(Debug, Clone, Hash, Eq, PartialEq, Into, Deref, DerefMut)]
 #[cfg_attr(ser, derive(serde::Serialize, serde::Deserialize), serde(transparent))]
 #[repr(transparent)]
-pub struct Url(url::Url);
+pub struct Url(pub(crate) url::Url);
 
 impl std::str::FromStr for Url {
     type Err = Box<dyn core::error::Error>;
@@ -27,7 +27,7 @@ use derive_more::{Deref, DerefMut, Into};
 /// # .unwrap(),
 /// # )}
 /// ```
-#[derive(Debug, Clone, Hash, Eq, PartialEq, Into, Deref, DerefMut)]
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Ord, PartialOrd, Into, Deref, DerefMut)]
 #[cfg_attr(ser, derive(serde::Serialize, serde::Deserialize), serde(transparent))]
 #[repr(transparent)]
 pub struct Uuid(#[cfg_attr(feature = "cbor", serde(with = "hyphenated"))] uuid::Uuid);
@@ -57,4 +57,19 @@ impl Uuid {
     pub fn new(udf: [u8; 16]) -> Self {
         Self(uuid::Uuid::new_v8(udf))
     }
+
+    /// Deterministically derives a v8 Uuid from arbitrary bytes by hashing them
+    pub fn derive(seed: &[u8]) -> Self {
+        use sha2::Digest as _;
+        let digest = sha2::Sha256::digest(seed);
+        let mut udf = [0u8; 16];
+        udf.copy_from_slice(&digest[..16]);
+        Self::new(udf)
+    }
+}
+
+impl std::fmt::Display for Uuid {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
 }
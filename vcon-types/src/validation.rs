@@ -0,0 +1,96 @@
+use crate::{Dialog, PartyIndex, Vcon, VconError, VconResult};
+use std::collections::BTreeSet;
+
+impl Vcon {
+    /// Referential checks on top of what deserialization already enforces.
+    ///
+    /// Fails on the first of:
+    /// - a dialog, party event or attachment referencing a party which does not exist
+    /// - an analysis referencing a dialog which does not exist
+    /// - a dialog whose `duration` is negative, not finite or ends out of the range of dates
+    /// - an incomplete dialog whose disposition is not defined by the draft
+    /// - an invalid transfer (see [Vcon::call_flow]) or party history (see [Vcon::presence])
+    pub fn validate(&self) -> VconResult<()> {
+        let parties = self.parties.as_deref().unwrap_or_default().len();
+        let party = |path: String, index: PartyIndex| {
+            if (index as usize) < parties {
+                Ok(())
+            } else {
+                Err(VconError::UnknownIndex {
+                    path,
+                    target: "party",
+                    index,
+                })
+            }
+        };
+
+        let dialogs = self.dialog.as_deref().unwrap_or_default();
+        for (i, object) in dialogs.iter().enumerate() {
+            match &object.dialog {
+                Dialog::Recording {
+                    parties,
+                    originator,
+                    duration,
+                    ..
+                }
+                | Dialog::Text {
+                    parties,
+                    originator,
+                    duration,
+                    ..
+                } => {
+                    if let Some(duration) = duration.as_ref().filter(|_| object.end().is_none()) {
+                        return Err(VconError::InvalidVcon(format!(
+                            "dialog[{i}].duration of {} seconds is out of range",
+                            duration.as_secs_f64()
+                        )));
+                    }
+                    for index in parties.indexes() {
+                        party(format!("dialog[{i}].parties"), index)?;
+                    }
+                    if let Some(originator) = originator {
+                        party(format!("dialog[{i}].originator"), *originator)?;
+                    }
+                }
+                Dialog::Incomplete { disposition } => disposition.validate()?,
+                Dialog::Transfer { .. } => {}
+            }
+            for (j, event) in object.party_history.iter().flatten().enumerate() {
+                party(format!("dialog[{i}].party_history[{j}]"), event.party)?;
+            }
+        }
+        for (i, attachment) in self.attachments.iter().flatten().enumerate() {
+            party(format!("attachments[{i}].party"), attachment.party)?;
+        }
+        for (i, analysis) in self.analysis.iter().flatten().enumerate() {
+            if analysis.dialog as usize >= dialogs.len() {
+                return Err(VconError::UnknownIndex {
+                    path: format!("analysis[{i}].dialog"),
+                    target: "dialog",
+                    index: analysis.dialog,
+                });
+            }
+        }
+
+        self.call_flow()?;
+        self.presence()?;
+        Ok(())
+    }
+
+    /// Checks that every extension this vCon marks as `critical` is in `supported`, failing with
+    /// [VconError::UnsupportedCriticalExtensions] listing the others
+    pub fn check_critical(&self, supported: &BTreeSet<String>) -> VconResult<()> {
+        let unsupported: Vec<String> = self
+            .critical
+            .iter()
+            .flatten()
+            .filter(|extension| !supported.contains(*extension))
+            .cloned()
+            .collect();
+        if unsupported.is_empty() {
+            Ok(())
+        } else {
+            Err(VconError::UnsupportedCriticalExtensions(unsupported))
+        }
+    }
+}
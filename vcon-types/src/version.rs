@@ -1,3 +1,4 @@
+use crate::{VconError, VconResult};
 use derive_more::{Deref, DerefMut, From, Into};
 
 /// Vcon version
@@ -41,9 +42,70 @@ impl From<&str> for VconVersion {
     }
 }
 
+impl From<Version> for VconVersion {
+    fn from(version: Version) -> Self {
+        version.as_str().into()
+    }
+}
+
+/// [Version::V0_0_1]
+///
 /// See https://www.ietf.org/archive/id/draft-petrie-vcon-04.html#section-4.1.1-2.1.2
 impl Default for VconVersion {
     fn default() -> Self {
-        "0.0.1".into()
+        Version::V0_0_1.into()
+    }
+}
+
+impl VconVersion {
+    /// Draft this version stands for, failing with [VconError::UnsupportedVersion] when unknown
+    pub fn version(&self) -> VconResult<Version> {
+        self.vcon.parse()
+    }
+}
+
+/// Drafts of the vCon specification this crate supports
+///
+/// Documents of any of them are read, legacy spellings included, and written with the spellings
+/// of the latest one whatever their declared version: notably `amended`, which used to be written
+/// `ammended`, so consumers of 0.0.1 documents must accept both. See [crate::Vcon::migrate] to
+/// move a vCon from one to another.
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+pub enum Version {
+    /// See https://www.ietf.org/archive/id/draft-petrie-vcon-04.html, whose CDDL spells
+    /// `ammended`, `validataion` and `civicaddress`
+    V0_0_1,
+    /// See https://datatracker.ietf.org/doc/draft-ietf-vcon-vcon-core/, adding party `sip`, `did`,
+    /// `jCard`, `timezone` and `contact_list`, dialog `session_id`, `application`, `message_id`
+    /// and `meta` as well as `extensions` and `critical`
+    V0_3_0,
+}
+
+impl Version {
+    pub const LATEST: Self = Self::V0_3_0;
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::V0_0_1 => "0.0.1",
+            Self::V0_3_0 => "0.3.0",
+        }
+    }
+}
+
+impl std::str::FromStr for Version {
+    type Err = VconError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "0.0.1" => Ok(Self::V0_0_1),
+            "0.3.0" => Ok(Self::V0_3_0),
+            _ => Err(VconError::UnsupportedVersion(s.to_string())),
+        }
+    }
+}
+
+impl std::fmt::Display for Version {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
     }
 }
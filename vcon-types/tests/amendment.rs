@@ -0,0 +1,108 @@
+use serde_json::json;
+use std::collections::HashMap;
+use vcon_types::{Attachment, Content, InlineContent, Vcon, VconError};
+
+fn email_thread() -> Vcon {
+    serde_json::from_str(include_str!("../examples/json/email-thread-text.json")).unwrap()
+}
+
+fn attachment() -> Attachment {
+    Attachment {
+        typ: "transcript".to_string(),
+        start: "2022-09-23T23:40:00Z".parse().unwrap(),
+        party: 0,
+        content_parameters: Default::default(),
+        content: Content::Inline(InlineContent::TextNone("late data".into())),
+        extension_object: Default::default(),
+    }
+}
+
+#[test]
+fn amendment_chain_should_verify() {
+    let original = email_thread();
+    let mut first = Vcon::amend(&original);
+    first
+        .attachments
+        .get_or_insert_with(Vec::new)
+        .push(attachment());
+    let second = Vcon::amend(&first);
+
+    assert_ne!(first.uuid, original.uuid);
+    assert!(first.updated_at.is_some());
+
+    let store = HashMap::from([
+        (original.uuid.clone(), original.clone()),
+        (first.uuid.clone(), first.clone()),
+    ]);
+    let chain = second.verify_amendments(&store).unwrap();
+    assert_eq!(chain, vec![first, original]);
+}
+
+#[test]
+fn amendment_removing_data_should_fail() {
+    let original = email_thread();
+    let mut amended = Vcon::amend(&original);
+    amended.dialog.as_mut().unwrap().pop();
+
+    let store = HashMap::from([(original.uuid.clone(), original)]);
+    assert!(matches!(
+        amended.verify_amendments(&store),
+        Err(VconError::InvalidAmendment {
+            field: "dialog",
+            ..
+        })
+    ));
+}
+
+#[test]
+fn unresolvable_amendment_should_fail() {
+    let amended = Vcon::amend(&email_thread());
+    assert!(matches!(
+        amended.verify_amendments(&HashMap::new()),
+        Err(VconError::UnresolvedReference(_))
+    ));
+}
+
+#[test]
+fn amended_reference_should_be_written_as_in_the_draft() {
+    let original = email_thread();
+    let amended = Vcon::amend(&original);
+    let json = serde_json::to_value(&amended).unwrap();
+    assert_eq!(
+        json["amended"],
+        json!({ "uuid": original.uuid.to_string() })
+    );
+
+    let reparsed: Vcon = serde_json::from_value(json).unwrap();
+    assert_eq!(reparsed.amended_reference(), amended.amended_reference());
+}
+
+#[test]
+fn amendment_dropping_critical_extensions_should_fail() {
+    let mut original = email_thread();
+    original.extensions = Some(vec!["contact_center".to_string()]);
+    original.critical = original.extensions.clone();
+    let mut amended = Vcon::amend(&original);
+    amended.critical = None;
+
+    let store = HashMap::from([(original.uuid.clone(), original.clone())]);
+    assert!(matches!(
+        amended.verify_amendments(&store),
+        Err(VconError::InvalidAmendment {
+            field: "critical",
+            ..
+        })
+    ));
+
+    // the prior version must be the one referenced
+    let mut other = original.clone();
+    other.uuid = Vcon::amend(&original).uuid;
+    let store = HashMap::from([(original.uuid.clone(), other)]);
+    assert!(matches!(
+        Vcon::amend(&original).verify_amendments(&store),
+        Err(VconError::InvalidAmendment {
+            field: "amended",
+            ..
+        })
+    ));
+}
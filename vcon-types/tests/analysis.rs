@@ -0,0 +1,81 @@
+mod common;
+
+use serde_json::json;
+use vcon_types::{
+    deepgram::{Deepgram, PrerecordedResponse},
+    Analysis, AnalysisDecoder, AnalysisRegistry, Content, VconResult,
+};
+
+fn deepgram_analysis() -> Analysis {
+    common::call().analysis.unwrap().remove(0)
+}
+
+fn sentiment() -> Analysis {
+    serde_json::from_value(json!({
+        "type": "sentiment",
+        "dialog": 0,
+        "mimetype": "application/json",
+        "vendor": "acme",
+        "encoding": "json",
+        "body": "{\"score\":0.8}"
+    }))
+    .unwrap()
+}
+
+struct Sentiment;
+
+impl AnalysisDecoder for Sentiment {
+    const TYPE: &'static str = "sentiment";
+    type Output = f64;
+
+    fn decode(body: &[u8]) -> VconResult<f64> {
+        let body = serde_json::from_slice::<serde_json::Value>(body).unwrap();
+        Ok(body["score"].as_f64().unwrap())
+    }
+}
+
+#[test]
+fn deepgram_should_decode() {
+    let analysis = deepgram_analysis();
+    assert!(analysis.is::<Deepgram>());
+    let response = analysis.decode::<Deepgram>().unwrap();
+    assert_eq!(response.metadata.channels, 1);
+
+    let decoded = AnalysisRegistry::default()
+        .decode(&analysis)
+        .unwrap()
+        .unwrap();
+    let decoded = decoded.downcast::<PrerecordedResponse>().unwrap();
+    assert_eq!(*decoded, response);
+}
+
+#[test]
+fn any_analysis_should_deserialize() {
+    let analysis = sentiment();
+    assert!(!analysis.is::<Deepgram>());
+    assert!(analysis.decode::<Deepgram>().is_err());
+    assert!(AnalysisRegistry::default()
+        .decode(&analysis)
+        .unwrap()
+        .is_none());
+
+    let mut registry = AnalysisRegistry::default();
+    registry.register::<Sentiment>();
+    let score = registry.decode(&analysis).unwrap().unwrap();
+    assert_eq!(*score.downcast::<f64>().unwrap(), 0.8);
+}
+
+#[test]
+fn url_referenced_analysis_should_deserialize() {
+    let analysis = serde_json::from_value::<Analysis>(json!({
+        "type": "summary",
+        "dialog": 0,
+        "mimetype": "text/plain",
+        "url": "https://example.com/summary.txt",
+        "alg": "SHA-512",
+        "signature": "GLy6IPaIUM1GqzZqfIPZlWjaDsNgNvZM0iCONNThnH0a75fhUM6cYzLZ5GynSURREvZwmOh54-2lRRieyj82UQ"
+    }))
+    .unwrap();
+    assert!(matches!(analysis.content, Content::UrlReferenced(_)));
+    assert!(analysis.content.inline_bytes().is_none());
+}
@@ -0,0 +1,72 @@
+use base64::Engine as _;
+use vcon_types::{Base64Body, Content, Dialog, InlineContent, Vcon, VconStore};
+
+const INLINE_RECORDING: &str =
+    include_str!("../examples/json/two-party-call-with-inline-recording.json");
+
+fn encoded_body() -> String {
+    let json: serde_json::Value = serde_json::from_str(INLINE_RECORDING).unwrap();
+    json["dialog"][0]["body"].as_str().unwrap().to_string()
+}
+
+fn content(vcon: &Vcon) -> &Content {
+    match &vcon.dialog.as_ref().unwrap()[0].dialog {
+        Dialog::Recording { content, .. } => content,
+        _ => unreachable!(),
+    }
+}
+
+struct NoStore;
+
+impl VconStore for NoStore {
+    fn load(&self, uuid: &vcon_types::Uuid) -> vcon_types::VconResult<Vcon> {
+        Err(vcon_types::VconError::UnresolvedReference(uuid.to_string()))
+    }
+
+    fn fetch(&self, url: &vcon_types::Url) -> vcon_types::VconResult<Vec<u8>> {
+        Err(vcon_types::VconError::UnresolvedReference(url.to_string()))
+    }
+}
+
+#[test]
+fn inline_body_should_be_decoded_on_demand() {
+    let vcon: Vcon = serde_json::from_str(INLINE_RECORDING).unwrap();
+    let Content::Inline(InlineContent::BinaryBase64Url(body)) = content(&vcon) else {
+        panic!("body should be base64url");
+    };
+    // kept encoded
+    assert_eq!(body.as_str(), encoded_body());
+
+    let decoded = InlineContent::B64.decode(encoded_body()).unwrap();
+    assert_eq!(body.decoded_len(), decoded.len());
+    assert_eq!(body.decode(), decoded);
+    assert!(decoded.starts_with(b"RIFF"));
+    let mut written = vec![];
+    content(&vcon).write_to(&NoStore, &mut written).unwrap();
+    assert_eq!(written, decoded);
+
+    // serialized back as is
+    let serialized = serde_json::to_value(&vcon).unwrap();
+    assert_eq!(serialized["dialog"][0]["body"], encoded_body());
+}
+
+#[test]
+fn base64_body_should_be_checked() {
+    let large = (0..100_000u32)
+        .flat_map(u32::to_le_bytes)
+        .collect::<Vec<_>>();
+    let body = Base64Body::encode(&large);
+    assert_eq!(
+        Base64Body::from_encoded(body.as_str().to_string()).unwrap(),
+        body
+    );
+    assert_eq!(body.decode(), large);
+
+    assert!(Base64Body::from_encoded("not base64!").is_err());
+    // padding is not allowed
+    assert!(Base64Body::from_encoded("YWJjZA==").is_err());
+
+    let mut json: serde_json::Value = serde_json::from_str(INLINE_RECORDING).unwrap();
+    json["dialog"][0]["body"] = "a+b/".into();
+    assert!(serde_json::from_value::<Vcon>(json).is_err());
+}
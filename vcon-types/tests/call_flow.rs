@@ -0,0 +1,86 @@
+use serde_json::json;
+use vcon_types::{FlowEdgeKind, TransferKind, Vcon, VconError};
+
+fn text(start: &str, parties: [u32; 2]) -> serde_json::Value {
+    json!({
+        "type": "text",
+        "start": start,
+        "parties": parties,
+        "encoding": "none",
+        "body": "..."
+    })
+}
+
+fn transfer(consultation: Option<u32>) -> Vcon {
+    let mut transfer = json!({
+        "type": "transfer",
+        "start": "2022-09-23T23:30:00Z",
+        "transferee": 0,
+        "transferor": 1,
+        "transfer_target": 2,
+        "original": 0,
+        "target_dialog": 2
+    });
+    if let Some(consultation) = consultation {
+        transfer["consultation"] = json!(consultation);
+    }
+    serde_json::from_value(json!({
+        "vcon": "0.0.1",
+        "uuid": "018b4f72-e3fb-8770-b9a2-279e0d16bc46",
+        "parties": [{ "name": "Customer" }, { "name": "Alice" }, { "tel": "+33612345678" }],
+        "dialog": [
+            text("2022-09-23T23:24:59Z", [0, 1]),
+            text("2022-09-23T23:26:00Z", [1, 2]),
+            text("2022-09-23T23:31:00Z", [0, 2]),
+            transfer
+        ]
+    }))
+    .unwrap()
+}
+
+#[test]
+fn attended_transfer_should_export() {
+    let flow = transfer(Some(1)).call_flow().unwrap();
+    assert_eq!(flow.nodes.len(), 3);
+    assert_eq!(flow.nodes[2].parties, vec!["Customer", "+33612345678"]);
+    let kinds = flow
+        .edges
+        .iter()
+        .map(|e| (e.from, e.to, e.kind))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        vec![
+            (0, 1, FlowEdgeKind::Consultation),
+            (0, 2, FlowEdgeKind::Transfer(TransferKind::Attended))
+        ]
+    );
+
+    let dot = flow.to_dot();
+    assert!(dot.starts_with("digraph call_flow {"));
+    assert!(dot.contains("d0 -> d2 [label=\"attended transfer by Alice\"];"));
+    assert!(dot.contains("d0 -> d1 [label=\"consultation by Alice\", style=dashed];"));
+
+    let mermaid = flow.to_mermaid();
+    assert!(mermaid.starts_with("flowchart LR"));
+    assert!(mermaid.contains("d0[\"0: text (Customer, Alice)\"]"));
+    assert!(mermaid.contains("d0 -->|\"attended transfer by Alice\"| d2"));
+}
+
+#[test]
+fn blind_transfer_should_build() {
+    let flow = transfer(None).call_flow().unwrap();
+    assert_eq!(flow.edges.len(), 1);
+    assert_eq!(
+        flow.edges[0].kind,
+        FlowEdgeKind::Transfer(TransferKind::Blind)
+    );
+}
+
+#[test]
+fn transfer_to_transfer_should_fail() {
+    assert!(matches!(
+        transfer(Some(3)).call_flow(),
+        Err(VconError::InvalidTransfer { dialog: 3, .. })
+    ));
+}
@@ -0,0 +1,167 @@
+mod common;
+
+use serde_json::json;
+use vcon_types::{Analysis, Transcript, TranscriptSegment, Vcon, VconError};
+
+fn transcribed() -> (Vcon, Transcript) {
+    let vcon = common::call();
+    let transcript = Transcript::from_analysis(&vcon.analysis.as_ref().unwrap()[0])
+        .unwrap()
+        .unwrap();
+    (vcon, transcript)
+}
+
+#[test]
+fn webvtt_should_export() {
+    let (vcon, transcript) = transcribed();
+    let vtt = transcript.to_webvtt(vcon.parties.as_deref().unwrap());
+    assert!(vtt.starts_with("WEBVTT\n\n1\n00:00:01.200 --> 00:00:01.700\n<v Alice>Hello.</v>\n"));
+    assert_eq!(vtt.matches(" --> ").count(), transcript.segments.len());
+}
+
+#[test]
+fn srt_should_export() {
+    let (vcon, transcript) = transcribed();
+    let srt = transcript.to_srt(vcon.parties.as_deref().unwrap());
+    assert!(srt.starts_with("1\n00:00:01,200 --> 00:00:01,700\nAlice: Hello.\n\n2\n"));
+}
+
+#[test]
+fn script_should_merge_turns() {
+    let (_, transcript) = transcribed();
+    let script = transcript.to_script(&[]);
+    assert_eq!(script.lines().count(), 1);
+    assert!(script.starts_with("Speaker 0: Hello. This is example.com."));
+}
+
+#[test]
+fn webvtt_should_import() {
+    let (vcon, _) = transcribed();
+    let parties = vcon.parties.as_deref().unwrap();
+    let vtt = "WEBVTT\nLanguage: en\n\nNOTE some comment\n\n1\n00:01.000 --> 00:03.000 align:start\n<v.loud Alice>Hello &amp; welcome</v> <v Bob>Hi</v>\n\n00:00:04.000 --> 00:00:05.000\n<i>Unknown</i> voice\n";
+    let transcript = Transcript::from_webvtt(vtt, parties).unwrap();
+    assert_eq!(transcript.language.as_deref(), Some("en"));
+    assert_eq!(transcript.duration, Some(5.0));
+    assert_eq!(transcript.segments.len(), 3);
+    assert_eq!(transcript.segments[0].text, "Hello & welcome");
+    assert_eq!(transcript.segments[0].speaker, Some(0));
+    assert_eq!(transcript.segments[0].words.len(), 3);
+    assert_eq!(transcript.segments[0].start, 1.0);
+    assert_eq!(transcript.segments[1].speaker, Some(1));
+    assert!(transcript.segments[1].end <= 3.0);
+    assert_eq!(transcript.segments[2].speaker, None);
+    assert_eq!(transcript.segments[2].text, "Unknown voice");
+
+    let analysis = transcript.to_analysis(0).unwrap();
+    assert_eq!(
+        Transcript::from_analysis(&analysis).unwrap(),
+        Some(transcript)
+    );
+}
+
+#[test]
+fn srt_should_round_trip() {
+    let (vcon, transcript) = transcribed();
+    let parties = vcon.parties.as_deref().unwrap();
+    let imported = Transcript::from_srt(&transcript.to_srt(parties), parties).unwrap();
+    assert_eq!(imported.segments.len(), transcript.segments.len());
+    assert_eq!(imported.text(), transcript.text());
+    assert!(imported.segments.iter().all(|s| s.speaker == Some(0)));
+
+    assert!(matches!(
+        Transcript::from_srt("1\n00:00:01 --> 00:00:02\nHi\n", parties),
+        Err(vcon_types::VconError::InvalidCaptions { line: 2, .. })
+    ));
+    assert!(Transcript::from_webvtt("1\n00:01.000 --> 00:02.000\nHi\n", parties).is_err());
+}
+
+#[test]
+fn hours_beyond_two_digits_should_round_trip() {
+    let parties = common::call().parties.unwrap();
+    let transcript = Transcript {
+        segments: vec![TranscriptSegment {
+            text: "Still there?".to_string(),
+            start: 360_000.25,
+            end: 360_001.0,
+            speaker: Some(1),
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+    let srt = transcript.to_srt(&parties);
+    assert!(srt.contains("100:00:00,250 --> 100:00:01,000\nBob: Still there?"));
+    let imported = Transcript::from_srt(&srt, &parties).unwrap();
+    assert_eq!(imported.segments[0].start, 360_000.25);
+    assert_eq!(imported.segments[0].speaker, Some(1));
+    assert_eq!(imported.duration, Some(360_001.0));
+}
+
+#[test]
+fn out_of_range_timestamps_should_be_invalid() {
+    let huge = Transcript {
+        segments: vec![TranscriptSegment {
+            text: "Hi".to_string(),
+            start: 1e30,
+            end: f64::INFINITY,
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+    assert!(huge.to_webvtt(&[]).contains(" --> "));
+
+    for timing in [
+        "99999999999999999999:00.000 --> 00:01.000",
+        "00:01.000 --> 307445734561825861:00:00.000",
+        "00:01.000 --> 00:02.0000000000000000000001",
+    ] {
+        let vtt = format!("WEBVTT\n\n{timing}\nHi\n");
+        assert!(
+            matches!(
+                Transcript::from_webvtt(&vtt, &[]),
+                Err(VconError::InvalidCaptions { line: 3, .. })
+            ),
+            "{timing}"
+        );
+    }
+}
+
+#[test]
+fn invalid_utf8_lines_should_stay_in_their_cue() {
+    let srt =
+        b"1\n00:00:01,000 --> 00:00:02,000\nHi \xff\xfe\n\n2\n00:00:03,000 --> 00:00:04,000\nBye\n";
+    let transcript = Transcript::from_srt(&String::from_utf8_lossy(srt), &[]).unwrap();
+    assert_eq!(transcript.segments.len(), 2);
+    assert_eq!(transcript.segments[0].text, "Hi \u{fffd}\u{fffd}");
+    assert_eq!(transcript.segments[1].text, "Bye");
+    assert!(transcript
+        .to_webvtt(&[])
+        .contains("\nHi \u{fffd}\u{fffd}\n"));
+}
+
+#[test]
+fn url_referenced_transcripts_should_be_captioned_once_fetched() {
+    use base64::Engine as _;
+    use sha2::Digest as _;
+
+    let mut vcon = common::call_json();
+    let body = vcon["analysis"][0]["body"].take().to_string();
+    let signature = base64::prelude::BASE64_URL_SAFE_NO_PAD.encode(sha2::Sha512::digest(&body));
+    let analysis = vcon["analysis"][0].as_object_mut().unwrap();
+    analysis.remove("body");
+    analysis.remove("encoding");
+    analysis.insert(
+        "url".to_string(),
+        json!("https://example.com/transcript.json"),
+    );
+    analysis.insert("alg".to_string(), json!("SHA-512"));
+    analysis.insert("signature".to_string(), json!(signature));
+    let analysis: Analysis = serde_json::from_value(vcon["analysis"][0].take()).unwrap();
+
+    assert_eq!(Transcript::from_analysis(&analysis).unwrap(), None);
+    let (vcon, inline) = transcribed();
+    let fetched = Transcript::from_analysis_with(&analysis, &common::Remote(body.into_bytes()))
+        .unwrap()
+        .unwrap();
+    let parties = vcon.parties.as_deref().unwrap();
+    assert_eq!(fetched.to_webvtt(parties), inline.to_webvtt(parties));
+}
@@ -0,0 +1,29 @@
+//! Fixtures shared by the integration tests, each using some of them
+#![allow(dead_code)]
+
+use vcon_types::{Url, Uuid, Vcon, VconError, VconResult, VconStore};
+
+/// Two party call whose recording is transcribed by a Deepgram analysis
+pub const CALL: &str = include_str!("../../examples/json/two-party-call-with-analysis.json");
+
+pub fn call() -> Vcon {
+    serde_json::from_str(CALL).unwrap()
+}
+
+/// [call] as JSON, to be altered before being parsed
+pub fn call_json() -> serde_json::Value {
+    serde_json::from_str(CALL).unwrap()
+}
+
+/// Store fetching the same bytes whatever the url, and loading no vCon
+pub struct Remote(pub Vec<u8>);
+
+impl VconStore for Remote {
+    fn load(&self, uuid: &Uuid) -> VconResult<Vcon> {
+        Err(VconError::UnresolvedReference(uuid.to_string()))
+    }
+
+    fn fetch(&self, _: &Url) -> VconResult<Vec<u8>> {
+        Ok(self.0.clone())
+    }
+}
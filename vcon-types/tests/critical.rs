@@ -0,0 +1,54 @@
+use serde_json::json;
+use vcon_types::{ParseOptions, Vcon, VconError};
+
+const CURRENT_DRAFT: &str = include_str!("../examples/json/current-draft-fields.json");
+
+fn supported(extensions: &[&str]) -> std::collections::BTreeSet<String> {
+    extensions.iter().map(|e| e.to_string()).collect()
+}
+
+#[test]
+fn critical_extensions_should_be_supported() {
+    let mut vcon: Vcon = serde_json::from_str(CURRENT_DRAFT).unwrap();
+    vcon.check_critical(&supported(&["contact_center"]))
+        .unwrap();
+    // extensions merely used need no support
+    vcon.critical = None;
+    vcon.check_critical(&supported(&[])).unwrap();
+
+    vcon.critical = Some(vec![
+        "contact_center".to_string(),
+        "consent".to_string(),
+        "lawful_intercept".to_string(),
+    ]);
+    let error = vcon
+        .check_critical(&supported(&["contact_center"]))
+        .unwrap_err();
+    assert!(matches!(
+        &error,
+        VconError::UnsupportedCriticalExtensions(unsupported)
+            if unsupported == &["consent", "lawful_intercept"]
+    ));
+    assert_eq!(
+        error.to_string(),
+        "Unsupported critical extensions: consent, lawful_intercept"
+    );
+}
+
+#[test]
+fn parsing_should_reject_unsupported_critical_extensions() {
+    let mut json: serde_json::Value = serde_json::from_str(CURRENT_DRAFT).unwrap();
+    json.as_object_mut().unwrap().remove("critical");
+    json["must_support"] = json!(["consent"]);
+    let json = serde_json::to_vec(&json).unwrap();
+
+    assert!(matches!(
+        Vcon::parse_json(&json, &ParseOptions::default()),
+        Err(VconError::UnsupportedCriticalExtensions(unsupported)) if unsupported == ["consent"]
+    ));
+    let options = ParseOptions {
+        supported_extensions: supported(&["consent"]),
+        ..Default::default()
+    };
+    Vcon::parse_json(&json, &options).unwrap();
+}
@@ -0,0 +1,89 @@
+use serde_json::json;
+use vcon_types::{Dialog, Vcon};
+
+const CURRENT_DRAFT: &str = include_str!("../examples/json/current-draft-fields.json");
+const EMAIL_THREAD: &str = include_str!("../examples/json/email-thread-text.json");
+
+#[test]
+fn current_draft_fields_should_be_parsed() {
+    let vcon: Vcon = serde_json::from_str(CURRENT_DRAFT).unwrap();
+    assert_eq!(
+        vcon.extensions.as_deref(),
+        Some(&["contact_center".to_string()][..])
+    );
+    assert_eq!(vcon.critical, vcon.extensions);
+
+    let [alice, bob] = vcon.parties.as_deref().unwrap() else {
+        panic!("two parties expected");
+    };
+    assert_eq!(alice.sip.as_deref(), Some("sip:alice@example.com"));
+    assert_eq!(alice.did.as_deref(), Some("did:example:123456789abcdefghi"));
+    assert_eq!(alice.timezone.as_deref(), Some("America/New_York"));
+    let jcard = serde_json::to_value(alice.jcard.as_ref().unwrap()).unwrap();
+    assert_eq!(jcard[0], "vcard");
+    assert_eq!(
+        bob.contact_list.as_deref(),
+        Some("https://example.com/contact-lists/support")
+    );
+
+    let [call, email] = vcon.dialog.as_deref().unwrap() else {
+        panic!("two dialogs expected");
+    };
+    assert!(call.session_id.as_deref().unwrap().starts_with("ab30317f"));
+    assert_eq!(call.application.as_deref(), Some("zoom"));
+    let meta = serde_json::to_value(call.meta.as_ref().unwrap()).unwrap();
+    assert_eq!(meta, json!({ "queue": "support" }));
+    assert_eq!(email.message_id.as_deref(), Some("<0a1b2c3d@example.com>"));
+    assert!(matches!(
+        email.dialog,
+        Dialog::Text {
+            originator: Some(1),
+            ..
+        }
+    ));
+    // none of them is taken for an unknown field
+    assert!(vcon.unknown_fields(&Default::default()).is_empty());
+}
+
+#[test]
+fn must_support_should_be_read_as_critical_and_absent_fields_not_written() {
+    let mut json: serde_json::Value = serde_json::from_str(EMAIL_THREAD).unwrap();
+    json["must_support"] = json!(["consent"]);
+    let vcon: Vcon = serde_json::from_value(json.clone()).unwrap();
+    assert_eq!(vcon.critical, Some(vec!["consent".to_string()]));
+
+    let written = serde_json::to_value(&vcon).unwrap();
+    assert_eq!(written["critical"], json!(["consent"]));
+    json.as_object_mut().unwrap().remove("must_support");
+    let written = written.as_object().unwrap();
+    let mut keys = written
+        .keys()
+        .filter(|k| *k != "critical")
+        .collect::<Vec<_>>();
+    keys.sort();
+    let mut expected = json.as_object().unwrap().keys().collect::<Vec<_>>();
+    expected.sort();
+    assert_eq!(keys, expected);
+    for (party, written) in json["parties"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .zip(written["parties"].as_array().unwrap())
+    {
+        assert_eq!(
+            party.as_object().unwrap().len(),
+            written.as_object().unwrap().len()
+        );
+    }
+}
+
+#[test]
+fn redaction_should_clear_new_identity_fields() {
+    let original: Vcon = serde_json::from_str(CURRENT_DRAFT).unwrap();
+    let mut redacted = Vcon::redact(&original, "pii");
+    redacted.redact_party(0).unwrap();
+    let party = &redacted.parties.as_ref().unwrap()[0];
+    assert_eq!((&party.sip, &party.did), (&None, &None));
+    assert!(party.jcard.is_none());
+    assert_eq!(party.timezone.as_deref(), Some("America/New_York"));
+}
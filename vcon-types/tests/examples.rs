@@ -1,12 +1,14 @@
 use assert_json_diff::{CompareMode, FloatCompareMode, NumericMode};
 use vcon_types::Vcon;
 
-const EXAMPLES: [&'static str; 5] = [
+const EXAMPLES: [&'static str; 7] = [
     include_str!("../examples/json/email-thread-text.json"),
     include_str!("../examples/json/email-thread-multipart.json"),
     include_str!("../examples/json/two-party-call-with-analysis.json"),
     include_str!("../examples/json/two-party-call-with-external-reference-recording.json"),
     include_str!("../examples/json/two-party-call-with-inline-recording.json"),
+    include_str!("../examples/json/current-draft-fields.json"),
+    include_str!("../examples/json/group.json"),
 ];
 
 #[test]
@@ -0,0 +1,100 @@
+use serde_json::json;
+use vcon_types::{
+    Extensible, ExtensionRegistry, ParseOptions, UnknownFields, Vcon, VconError, VconExtension,
+};
+
+const EMAIL_THREAD: &str = include_str!("../examples/json/email-thread-text.json");
+
+struct CaseId;
+
+impl VconExtension for CaseId {
+    const NAME: &'static str = "x_case_id";
+    type Value = String;
+}
+
+#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+struct Routing {
+    queue: String,
+    skill: Option<String>,
+}
+
+impl VconExtension for Routing {
+    const NAME: &'static str = "x_routing";
+    type Value = Self;
+}
+
+fn registry() -> ExtensionRegistry {
+    let mut registry = ExtensionRegistry::default();
+    registry.register::<CaseId>().register::<Routing>();
+    registry
+}
+
+#[test]
+fn extensions_should_be_typed_and_keep_unknown_ones() {
+    let mut json: serde_json::Value = serde_json::from_str(EMAIL_THREAD).unwrap();
+    json["x_other"] = json!({ "kept": true });
+    let mut vcon: Vcon = serde_json::from_value(json).unwrap();
+    assert_eq!(vcon.extension::<CaseId>().unwrap(), None);
+
+    vcon.set_extension::<CaseId>(&"C-42".to_string()).unwrap();
+    let routing = Routing {
+        queue: "billing".to_string(),
+        skill: Some("french".to_string()),
+    };
+    let object = &mut vcon.dialog.as_mut().unwrap()[0];
+    object.set_extension::<Routing>(&routing).unwrap();
+
+    let reparsed: Vcon = serde_json::from_str(&serde_json::to_string(&vcon).unwrap()).unwrap();
+    assert_eq!(
+        reparsed.extension::<CaseId>().unwrap().as_deref(),
+        Some("C-42")
+    );
+    let object = &reparsed.dialog.as_ref().unwrap()[0];
+    assert_eq!(object.extension::<Routing>().unwrap(), Some(routing));
+    assert_eq!(reparsed.extension_object, vcon.extension_object);
+
+    let mut vcon = reparsed;
+    assert!(vcon.remove_extension::<CaseId>());
+    assert!(!vcon.remove_extension::<CaseId>());
+    assert_eq!(
+        serde_json::to_value(&vcon).unwrap()["x_other"],
+        json!({ "kept": true })
+    );
+}
+
+#[test]
+fn registered_extensions_should_be_validated() {
+    let mut json: serde_json::Value = serde_json::from_str(EMAIL_THREAD).unwrap();
+    json["x_case_id"] = json!("C-42");
+    json["parties"][1]["x_routing"] = json!({ "skill": "french" });
+    let vcon: Vcon = serde_json::from_value(json).unwrap();
+
+    ExtensionRegistry::default().validate(&vcon).unwrap();
+    let error = registry().validate(&vcon).unwrap_err();
+    assert!(matches!(
+        &error,
+        VconError::InvalidExtension { path, .. } if path == "parties[1].x_routing"
+    ));
+    assert!(matches!(
+        vcon.parties.as_ref().unwrap()[1].extension::<Routing>(),
+        Err(VconError::InvalidExtension { .. })
+    ));
+}
+
+#[test]
+fn strict_parsing_should_accept_registered_extensions() {
+    let mut json: serde_json::Value = serde_json::from_str(EMAIL_THREAD).unwrap();
+    json["x_case_id"] = json!("C-42");
+    let json = serde_json::to_vec(&json).unwrap();
+
+    let mut strict = ParseOptions {
+        unknown_fields: UnknownFields::Reject,
+        ..Default::default()
+    };
+    assert!(matches!(
+        Vcon::parse_json(&json, &strict),
+        Err(VconError::UnknownField(path)) if path == "x_case_id"
+    ));
+    strict.extensions = registry().names();
+    Vcon::parse_json(&json, &strict).unwrap();
+}
@@ -0,0 +1,89 @@
+mod common;
+
+use common::Remote;
+use std::collections::HashMap;
+use vcon_types::{UrlReferencedContent, Uuid, Vcon, VconError, VconReference};
+
+fn vcon(udf: &[u8; 16], group: Vec<VconReference>) -> Vcon {
+    let mut vcon: Vcon =
+        serde_json::from_str(include_str!("../examples/json/email-thread-text.json")).unwrap();
+    vcon.uuid = Uuid::new(*udf);
+    vcon.group = Some(group.into_iter().map(Into::into).collect());
+    vcon
+}
+
+fn by_uuid(vcon: &Vcon) -> VconReference {
+    VconReference::Uuid {
+        uuid: vcon.uuid.clone(),
+    }
+}
+
+#[test]
+fn group_entries_should_follow_the_draft() {
+    let json: serde_json::Value =
+        serde_json::from_str(include_str!("../examples/json/group.json")).unwrap();
+    let vcon: Vcon = serde_json::from_value(json.clone()).unwrap();
+    let group = vcon.group.as_deref().unwrap();
+    assert!(
+        matches!(&group[0].vcon_reference, VconReference::Uuid { uuid } if uuid.to_string() == "01928e10-193e-8231-b9a2-279e0d16bc46")
+    );
+    assert!(
+        matches!(&group[1].vcon_reference, VconReference::Url { vcon_url_referenced } if vcon_url_referenced.url.to_string().ends_with("bc47.json"))
+    );
+
+    let written = serde_json::to_value(&vcon).unwrap();
+    assert_eq!(written["group"], json["group"]);
+}
+
+#[test]
+fn group_should_resolve_recursively() {
+    let leaf = vcon(b"leafleafleafleaf", vec![]);
+    let left = vcon(b"leftleftleftleft", vec![by_uuid(&leaf)]);
+    let right = vcon(b"rightrightrightr", vec![by_uuid(&leaf)]);
+    let root = vcon(b"rootrootrootroot", vec![by_uuid(&left), by_uuid(&right)]);
+    let store =
+        HashMap::from([left.clone(), right.clone(), leaf.clone()].map(|v| (v.uuid.clone(), v)));
+
+    let group = root.resolve_group(&store).unwrap();
+    assert_eq!(group.root(), &root);
+    assert_eq!(group.vcons, vec![root.clone(), left, leaf, right]);
+    assert_eq!(group.links.len(), 4);
+    assert_eq!(group.members(&root.uuid).count(), 2);
+}
+
+#[test]
+fn group_cycle_should_fail() {
+    let mut a = vcon(b"aaaaaaaaaaaaaaaa", vec![]);
+    let b = vcon(b"bbbbbbbbbbbbbbbb", vec![by_uuid(&a)]);
+    a.group = Some(vec![by_uuid(&b).into()]);
+    let store = HashMap::from([a.clone(), b].map(|v| (v.uuid.clone(), v)));
+
+    assert!(matches!(a.resolve_group(&store), Err(VconError::GroupCycle(uuid)) if uuid == a.uuid));
+}
+
+#[test]
+fn url_reference_should_verify_digest() {
+    let member = vcon(b"remoteremoteremo", vec![]);
+    let content = serde_json::to_vec(&member).unwrap();
+
+    use sha2::Digest as _;
+    let digest = sha2::Sha512::digest(&content);
+    use base64::Engine as _;
+    let signature = base64::prelude::BASE64_URL_SAFE_NO_PAD.encode(digest);
+    let reference = VconReference::Url {
+        vcon_url_referenced: UrlReferencedContent {
+            url: "https://example.com/vcon.json".parse().unwrap(),
+            signature: signature.parse().unwrap(),
+        },
+    };
+    let root = vcon(b"rootrootrootroot", vec![reference]);
+
+    let group = root.resolve_group(&Remote(content)).unwrap();
+    assert_eq!(group.vcons[1], member);
+
+    let tampered = Remote(b"{}".to_vec());
+    assert!(matches!(
+        root.resolve_group(&tampered),
+        Err(VconError::DigestMismatch(_))
+    ));
+}
@@ -0,0 +1,211 @@
+mod common;
+
+use common::CALL;
+use serde_json::json;
+use vcon_types::{ParseOptions, Vcon, VconError};
+
+const INLINE_RECORDING: &str =
+    include_str!("../examples/json/two-party-call-with-inline-recording.json");
+
+fn limit<T: std::fmt::Debug>(result: Result<T, VconError>) -> (&'static str, usize, usize) {
+    match result {
+        Err(VconError::LimitExceeded { limit, max, actual }) => (limit, max, actual),
+        other => panic!("limit should be exceeded, got {other:?}"),
+    }
+}
+
+#[test]
+fn examples_should_parse_within_default_limits() {
+    let options = ParseOptions::default();
+    for json in [INLINE_RECORDING, CALL] {
+        let parsed = Vcon::parse_json(json.as_bytes(), &options).unwrap();
+        assert_eq!(parsed, serde_json::from_str::<Vcon>(json).unwrap());
+    }
+}
+
+#[test]
+fn sizes_and_counts_should_be_limited() {
+    let json = INLINE_RECORDING.as_bytes();
+    let options = |f: fn(&mut ParseOptions)| {
+        let mut options = ParseOptions::default();
+        f(&mut options);
+        options
+    };
+
+    let small = options(|o| o.max_size = 1000);
+    assert_eq!(
+        limit(Vcon::parse_json(json, &small)),
+        ("size", 1000, json.len())
+    );
+
+    let body = serde_json::from_str::<serde_json::Value>(INLINE_RECORDING).unwrap()["dialog"][0]
+        ["body"]
+        .as_str()
+        .unwrap()
+        .len();
+    let small_bodies = options(|o| o.max_body_size = 100);
+    assert_eq!(
+        limit(Vcon::parse_json(json, &small_bodies)),
+        ("body size", 100, body)
+    );
+
+    // bodies holding JSON are limited by their serialized size
+    let call: serde_json::Value = serde_json::from_str(CALL).unwrap();
+    let json_body = serde_json::to_string(&call["analysis"][0]["body"])
+        .unwrap()
+        .len();
+    let json_bodies = |max_body_size| ParseOptions {
+        max_body_size,
+        ..Default::default()
+    };
+    Vcon::parse_json(CALL.as_bytes(), &json_bodies(json_body)).unwrap();
+    assert_eq!(
+        limit(Vcon::parse_json(
+            CALL.as_bytes(),
+            &json_bodies(json_body - 1)
+        )),
+        ("body size", json_body - 1, json_body)
+    );
+
+    let single_party = options(|o| o.max_parties = 1);
+    assert_eq!(
+        limit(Vcon::parse_json(json, &single_party)),
+        ("parties", 1, 2)
+    );
+
+    let analysis = options(|o| o.max_analysis = 0);
+    assert_eq!(
+        limit(Vcon::parse_json(CALL.as_bytes(), &analysis)).0,
+        "analysis"
+    );
+}
+
+#[test]
+fn nesting_should_be_limited() {
+    let mut nested = json!("deep");
+    for _ in 0..200 {
+        nested = json!({ "nested": nested });
+    }
+    let mut vcon: serde_json::Value = serde_json::from_str(INLINE_RECORDING).unwrap();
+    vcon["x_extension"] = nested;
+    let json = serde_json::to_vec(&vcon).unwrap();
+
+    assert_eq!(
+        limit(Vcon::parse_json(&json, &ParseOptions::default())),
+        ("depth", 64, 65)
+    );
+    // not a limit but still rejected
+    assert!(matches!(
+        Vcon::parse_json(b"{\"vcon\":", &ParseOptions::default()),
+        Err(VconError::InvalidVcon(_))
+    ));
+}
+
+/// CBOR form of a JSON vCon, base64url bodies being tagged byte strings as with the `cbor` feature
+fn cbor(json: &serde_json::Value) -> Vec<u8> {
+    use ciborium::Value;
+    fn tag_bodies(value: &mut Value) {
+        match value {
+            Value::Map(entries) => {
+                let base64url = entries.iter().any(|(k, v)| {
+                    k.as_text() == Some("encoding") && v.as_text() == Some("base64url")
+                });
+                for (key, value) in entries {
+                    match value {
+                        Value::Text(body) if base64url && key.as_text() == Some("body") => {
+                            let bytes = Value::Bytes(std::mem::take(body).into_bytes());
+                            *value = Value::Tag(vcon_types::BASE_64_URL_TAG, Box::new(bytes));
+                        }
+                        value => tag_bodies(value),
+                    }
+                }
+            }
+            Value::Array(items) => items.iter_mut().for_each(tag_bodies),
+            _ => {}
+        }
+    }
+    let mut value = Value::serialized(json).unwrap();
+    tag_bodies(&mut value);
+    let mut bytes = vec![];
+    ciborium::into_writer(&value, &mut bytes).unwrap();
+    bytes
+}
+
+#[test]
+fn cbor_should_be_transcoded_within_limits() {
+    let json: serde_json::Value = serde_json::from_str(INLINE_RECORDING).unwrap();
+    let cbor = cbor(&json);
+
+    let options = ParseOptions::default();
+    let transcoded = options.transcode_cbor(&cbor).unwrap();
+    assert_eq!(
+        Vcon::parse_json(&transcoded, &options).unwrap(),
+        serde_json::from_value::<Vcon>(json).unwrap()
+    );
+
+    let single_party = ParseOptions {
+        max_parties: 1,
+        ..Default::default()
+    };
+    assert_eq!(limit(single_party.transcode_cbor(&cbor)), ("parties", 1, 2));
+    let small = ParseOptions {
+        max_size: cbor.len(),
+        ..Default::default()
+    };
+    assert_eq!(limit(small.transcode_cbor(&cbor)).0, "size");
+    let shallow = ParseOptions {
+        max_depth: 3,
+        ..Default::default()
+    };
+    // tags nest like arrays do
+    let tags = [[0xc1].repeat(10), vec![0]].concat();
+    assert_eq!(limit(shallow.transcode_cbor(&tags)).0, "depth");
+
+    // untagged byte strings are base64url encoded
+    let bytes = [0xa1, 0x61, b'x', 0x44, b'a', b'b', b'c', b'd'];
+    assert_eq!(
+        options.transcode_cbor(&bytes).unwrap(),
+        br#"{"x":"YWJjZA"}"#
+    );
+    assert!(options.transcode_cbor(&bytes[..7]).is_err());
+    assert!(options
+        .transcode_cbor(&[&bytes[..], &[0]].concat())
+        .is_err());
+}
+
+#[test]
+fn limits_should_apply_to_readers_and_repositories() {
+    let single_party = ParseOptions {
+        max_parties: 1,
+        ..Default::default()
+    };
+    let vcon: serde_json::Value = serde_json::from_str(INLINE_RECORDING).unwrap();
+    let ndjson = format!("{vcon}\n");
+    let mut read = vcon_types::VconReader::new(ndjson.as_bytes())
+        .unwrap()
+        .with_options(single_party.clone());
+    let error = read.next().unwrap().unwrap_err().to_string();
+    assert!(error.contains("parties"), "{error}");
+    assert!(read.next().is_none());
+
+    let root = std::env::temp_dir().join(format!("vcon-limits-{}", std::process::id()));
+    let vcon: Vcon = serde_json::from_str(INLINE_RECORDING).unwrap();
+    let mut repository =
+        vcon_types::VconRepository::open(&root, vcon_types::StorageFormat::Json).unwrap();
+    repository.put(&vcon).unwrap();
+    let opened =
+        vcon_types::VconRepository::open_with(&root, vcon_types::StorageFormat::Json, single_party)
+            .unwrap();
+    assert!(opened.list().is_empty());
+    assert!(matches!(
+        opened.skipped(),
+        [(
+            _,
+            VconError::LimitExceeded {
+                limit: "parties",
+                ..
+            }
+        )]
+    ));
+    std::fs::remove_dir_all(root).unwrap();
+}
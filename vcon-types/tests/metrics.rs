@@ -0,0 +1,98 @@
+mod common;
+
+use vcon_types::{
+    AnalysisRegistry, ConversationMetrics, DialogObject, Transcript, TranscriptSegment,
+    TranscriptWord, Vcon,
+};
+
+fn word(text: &str, start: f64, end: f64, speaker: u32) -> TranscriptWord {
+    TranscriptWord {
+        text: text.to_string(),
+        start,
+        end,
+        speaker: Some(speaker),
+        confidence: None,
+    }
+}
+
+fn segment(words: Vec<TranscriptWord>) -> TranscriptSegment {
+    TranscriptSegment {
+        text: words
+            .iter()
+            .map(|w| w.text.as_str())
+            .collect::<Vec<_>>()
+            .join(" "),
+        start: words[0].start,
+        end: words.last().unwrap().end,
+        speaker: words[0].speaker,
+        confidence: None,
+        words,
+    }
+}
+
+fn recording() -> (Vcon, DialogObject) {
+    let vcon = common::call();
+    let dialog = vcon.dialog.as_ref().unwrap()[0].clone();
+    (vcon, dialog)
+}
+
+#[test]
+fn metrics_should_measure_turn_taking() {
+    let (_, dialog) = recording();
+    let transcript = Transcript {
+        duration: Some(10.0),
+        segments: vec![
+            segment(vec![word("hello", 1.0, 2.0, 0), word("there", 2.0, 3.0, 0)]),
+            // starts while party 0 is still talking
+            segment(vec![word("hi", 2.5, 4.0, 1)]),
+            segment(vec![word("so", 6.0, 7.0, 0)]),
+        ],
+        ..Default::default()
+    };
+    let metrics = ConversationMetrics::new(0, &dialog, &transcript);
+
+    assert_eq!(metrics.turns, 3);
+    assert_eq!(metrics.overlap, 0.5);
+    // speech from 1 to 4 and from 6 to 7
+    assert!((metrics.duration - metrics.silence - 4.0).abs() < 1e-9);
+    let alice = &metrics.parties[&0];
+    assert_eq!((alice.words, alice.turns, alice.interruptions), (3, 2, 0));
+    assert_eq!(alice.talk_time, 3.0);
+    assert_eq!(alice.longest_monologue, 2.0);
+    assert_eq!(alice.words_per_minute, 60.0);
+    let bob = &metrics.parties[&1];
+    assert_eq!((bob.words, bob.turns, bob.interruptions), (1, 1, 1));
+    assert_eq!(bob.talk_time, 1.5);
+}
+
+#[test]
+fn vcon_metrics_should_use_transcripts() {
+    let (vcon, _) = recording();
+    let metrics = vcon.conversation_metrics().unwrap();
+    assert_eq!(metrics.len(), 1);
+    let metrics = &metrics[0];
+    assert_eq!(metrics.dialog, 0);
+    // the recording is diarized as a single speaker
+    assert_eq!(metrics.turns, 1);
+    assert_eq!(metrics.parties.len(), 2);
+    assert!(metrics.parties[&0].talk_time > 0.0);
+    assert_eq!(metrics.parties[&1], Default::default());
+    assert!(metrics.silence + metrics.parties[&0].talk_time <= metrics.duration + 1e-9);
+}
+
+#[test]
+fn metrics_should_round_trip_as_analysis() {
+    let (vcon, _) = recording();
+    let metrics = vcon.conversation_metrics().unwrap().remove(0);
+    let analysis = metrics.to_analysis().unwrap();
+    assert_eq!(analysis.typ, "conversation-metrics");
+    assert_eq!(analysis.decode::<ConversationMetrics>().unwrap(), metrics);
+    let decoded = AnalysisRegistry::default()
+        .decode(&analysis)
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        decoded.downcast_ref::<ConversationMetrics>(),
+        Some(&metrics)
+    );
+}
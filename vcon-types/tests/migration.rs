@@ -0,0 +1,98 @@
+use serde_json::json;
+use vcon_types::{LossyConversion, ParseOptions, UnknownFields, Vcon, VconError, Version};
+
+const CURRENT_DRAFT: &str = include_str!("../examples/json/current-draft-fields.json");
+const EMAIL_THREAD: &str = include_str!("../examples/json/email-thread-text.json");
+
+fn parse(json: &serde_json::Value, options: &ParseOptions) -> Result<Vcon, VconError> {
+    Vcon::parse_json(&serde_json::to_vec(json).unwrap(), options)
+}
+
+#[test]
+fn legacy_spellings_should_be_read_and_versions_checked() {
+    let mut json: serde_json::Value = serde_json::from_str(EMAIL_THREAD).unwrap();
+    json["ammended"] = json!({ "uuid": "01928e10-193e-8231-b9a2-279e0d16bc46" });
+    json["parties"][0]["validataion"] = json!("SSO");
+    json["parties"][0]["civicaddress"] = json!({ "country": "US" });
+
+    let vcon = parse(&json, &ParseOptions::default()).unwrap();
+    assert_eq!(vcon.version.version().unwrap(), Version::V0_0_1);
+    assert!(vcon.amended_reference().is_some());
+    let party = &vcon.parties.as_ref().unwrap()[0];
+    assert_eq!(party.validation.as_deref(), Some("SSO"));
+    assert_eq!(
+        party.civic_address.as_ref().unwrap().country.as_deref(),
+        Some("US")
+    );
+    assert!(vcon.unknown_fields(&Default::default()).is_empty());
+
+    // written with the spellings of the latest draft
+    let written = serde_json::to_value(&vcon).unwrap();
+    assert!(written.get("amended").is_some() && written.get("ammended").is_none());
+
+    json["vcon"] = json!("9.9.9");
+    assert!(matches!(
+        parse(&json, &ParseOptions::default()),
+        Err(VconError::UnsupportedVersion(version)) if version == "9.9.9"
+    ));
+}
+
+#[test]
+fn strict_parsing_should_reject_fields_of_later_drafts() {
+    let mut json: serde_json::Value = serde_json::from_str(CURRENT_DRAFT).unwrap();
+    let lenient = ParseOptions {
+        supported_extensions: ["contact_center".to_string()].into(),
+        ..Default::default()
+    };
+    let strict = ParseOptions {
+        unknown_fields: UnknownFields::Reject,
+        ..lenient.clone()
+    };
+    parse(&json, &strict).unwrap();
+
+    json["vcon"] = json!("0.0.1");
+    assert!(matches!(
+        parse(&json, &strict),
+        Err(VconError::UnknownField(path)) if path == "extensions"
+    ));
+    parse(&json, &lenient).unwrap();
+}
+
+#[test]
+fn migration_should_report_lossy_conversions() {
+    let mut legacy: Vcon = serde_json::from_str(EMAIL_THREAD).unwrap();
+    let original = legacy.clone();
+    assert_eq!(legacy.migrate(Version::LATEST).unwrap(), []);
+    assert_eq!(legacy.version.as_str(), "0.3.0");
+    legacy.version = original.version.clone();
+    assert_eq!(legacy, original);
+
+    let mut current: Vcon = serde_json::from_str(CURRENT_DRAFT).unwrap();
+    let lossy = current.migrate(Version::V0_0_1).unwrap();
+    let paths = lossy.iter().map(|l| l.path.as_str()).collect::<Vec<_>>();
+    assert_eq!(
+        paths,
+        [
+            "extensions",
+            "critical",
+            "parties[0].sip",
+            "parties[0].did",
+            "parties[0].jCard",
+            "parties[0].timezone",
+            "parties[1].contact_list",
+            "dialog[0].session_id",
+            "dialog[0].application",
+            "dialog[0].meta",
+            "dialog[1].message_id",
+        ]
+    );
+    assert!(matches!(&lossy[0], LossyConversion { reason, .. } if reason.contains("0.0.1")));
+    assert_eq!(current.version.version().unwrap(), Version::V0_0_1);
+    assert!(current.parties.as_ref().unwrap()[0].sip.is_none());
+
+    current.version = "0.2.0".into();
+    assert!(matches!(
+        current.migrate(Version::LATEST),
+        Err(VconError::UnsupportedVersion(_))
+    ));
+}
@@ -0,0 +1,160 @@
+mod common;
+
+use vcon_types::{Compression, ErrorPolicy, ParseOptions, Vcon, VconError, VconReader, VconWriter};
+
+fn vcons() -> Vec<Vcon> {
+    [
+        common::CALL,
+        include_str!("../examples/json/email-thread-text.json"),
+        include_str!("../examples/json/two-party-call-with-external-reference-recording.json"),
+    ]
+    .into_iter()
+    .map(|json| serde_json::from_str(json).unwrap())
+    .collect()
+}
+
+fn round_trip(compression: Compression) {
+    let mut writer = VconWriter::with_compression(vec![], compression).unwrap();
+    for vcon in vcons() {
+        writer.write(&vcon).unwrap();
+    }
+    let written = writer.finish().unwrap();
+    let read = VconReader::new(written.as_slice())
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(read, vcons());
+}
+
+#[test]
+fn ndjson_should_round_trip() {
+    round_trip(Compression::None);
+    #[cfg(feature = "gzip")]
+    round_trip(Compression::Gzip);
+    #[cfg(feature = "zstd")]
+    round_trip(Compression::Zstd);
+}
+
+#[test]
+fn ndjson_should_report_invalid_lines() {
+    let mut writer = VconWriter::new(vec![]);
+    writer.write(&vcons()[0]).unwrap();
+    let mut ndjson = String::from_utf8(writer.finish().unwrap()).unwrap();
+    ndjson.push_str("\n{\"vcon\": \"0.0.1\"}\n");
+    ndjson.push_str(&serde_json::to_string(&vcons()[1]).unwrap());
+
+    let read = VconReader::new(ndjson.as_bytes())
+        .unwrap()
+        .collect::<Vec<_>>();
+    assert_eq!(read.len(), 3);
+    assert!(matches!(
+        read[1],
+        Err(VconError::InvalidLine { line: 3, .. })
+    ));
+    assert_eq!(read[2].as_ref().unwrap(), &vcons()[1]);
+
+    let mut reader = VconReader::new(ndjson.as_bytes())
+        .unwrap()
+        .on_error(ErrorPolicy::Abort);
+    assert!(reader.next().unwrap().is_ok());
+    assert!(reader.next().unwrap().is_err());
+    assert!(reader.next().is_none());
+    assert_eq!(reader.line(), 3);
+}
+
+#[test]
+fn ndjson_should_carry_on_after_invalid_utf8() {
+    let mut ndjson = b"{\"vcon\": \"\xff\"}\n".to_vec();
+    ndjson.extend(serde_json::to_vec(&vcons()[0]).unwrap());
+
+    let read = VconReader::new(ndjson.as_slice())
+        .unwrap()
+        .collect::<Vec<_>>();
+    assert_eq!(read.len(), 2);
+    assert!(matches!(
+        read[0],
+        Err(VconError::InvalidLine { line: 1, .. })
+    ));
+    assert_eq!(read[1].as_ref().unwrap(), &vcons()[0]);
+}
+
+/// A line of exactly `max_size` bytes, one longer, then the first again
+fn with_long_line() -> (Vec<u8>, ParseOptions) {
+    let line = serde_json::to_vec(&vcons()[0]).unwrap();
+    let mut ndjson = line.clone();
+    ndjson.push(b'\n');
+    ndjson.extend(std::iter::repeat_n(b' ', 1024 * 1024));
+    ndjson.extend(&line);
+    ndjson.push(b'\n');
+    ndjson.extend(&line);
+    let options = ParseOptions {
+        max_size: line.len(),
+        ..Default::default()
+    };
+    (ndjson, options)
+}
+
+#[test]
+fn ndjson_should_skip_lines_longer_than_max_size() {
+    let (ndjson, options) = with_long_line();
+    let read = VconReader::new(ndjson.as_slice())
+        .unwrap()
+        .with_options(options)
+        .collect::<Vec<_>>();
+    assert_eq!(read.len(), 3);
+    assert_eq!(read[0].as_ref().unwrap(), &vcons()[0]);
+    assert!(matches!(
+        &read[1],
+        Err(VconError::InvalidLine { line: 2, reason }) if reason.contains("size")
+    ));
+    assert_eq!(read[2].as_ref().unwrap(), &vcons()[0]);
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn ndjson_should_stream_asynchronously() {
+    use vcon_types::{AsyncVconReader, AsyncVconWriter};
+
+    let mut compressions = vec![Compression::None];
+    #[cfg(feature = "gzip")]
+    compressions.push(Compression::Gzip);
+    #[cfg(feature = "zstd")]
+    compressions.push(Compression::Zstd);
+
+    for compression in compressions {
+        let mut writer = AsyncVconWriter::with_compression(vec![], compression);
+        for vcon in vcons() {
+            writer.write(&vcon).await.unwrap();
+        }
+        let written = writer.finish().await.unwrap();
+        let mut reader = AsyncVconReader::new(written.as_slice()).await.unwrap();
+        let mut read = vec![];
+        while let Some(vcon) = reader.next().await {
+            read.push(vcon.unwrap());
+        }
+        assert_eq!(read, vcons());
+    }
+
+    let mut ndjson = b"\xff\xfe\n".to_vec();
+    ndjson.extend(serde_json::to_vec(&vcons()[0]).unwrap());
+    let mut reader = AsyncVconReader::new(ndjson.as_slice()).await.unwrap();
+    assert!(matches!(
+        reader.next().await,
+        Some(Err(VconError::InvalidLine { line: 1, .. }))
+    ));
+    assert_eq!(reader.next().await.unwrap().unwrap(), vcons()[0]);
+    assert!(reader.next().await.is_none());
+
+    let (ndjson, options) = with_long_line();
+    let mut reader = AsyncVconReader::new(ndjson.as_slice())
+        .await
+        .unwrap()
+        .with_options(options);
+    assert!(reader.next().await.unwrap().is_ok());
+    assert!(matches!(
+        reader.next().await,
+        Some(Err(VconError::InvalidLine { line: 2, .. }))
+    ));
+    assert!(reader.next().await.unwrap().is_ok());
+    assert!(reader.next().await.is_none());
+}
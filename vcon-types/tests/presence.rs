@@ -0,0 +1,64 @@
+use serde_json::json;
+use vcon_types::{DialogObject, Event, VconError};
+
+fn dialog(party_history: serde_json::Value) -> DialogObject {
+    serde_json::from_value(json!({
+        "type": "text",
+        "start": "2022-09-23T10:00:00Z",
+        "duration": 600,
+        "parties": [0, 1],
+        "encoding": "none",
+        "body": "...",
+        "party_history": party_history
+    }))
+    .unwrap()
+}
+
+fn event(party: u32, event: &str, time: &str) -> serde_json::Value {
+    json!({ "party": party, "event": event, "time": format!("2022-09-23T10:{time}Z") })
+}
+
+#[test]
+fn presence_should_compute_hold_time() {
+    let dialog = dialog(json!([
+        event(1, "hold", "01:00"),
+        event(1, "unhold", "03:00"),
+        event(2, "join", "02:00"),
+        event(2, "mute", "04:00"),
+        event(2, "drop", "05:00"),
+    ]));
+    let presence = dialog.presence().unwrap();
+
+    assert_eq!(presence[&0].time_present(), time::Duration::minutes(10));
+    assert_eq!(presence[&1].time_present(), time::Duration::minutes(10));
+    assert_eq!(presence[&1].time_on_hold(), time::Duration::minutes(2));
+    assert_eq!(presence[&2].time_present(), time::Duration::minutes(3));
+    assert_eq!(presence[&2].time_muted(), time::Duration::minutes(1));
+    assert!(presence[&2].on_hold.is_empty());
+}
+
+#[test]
+fn unhold_without_hold_should_fail() {
+    let dialog = dialog(json!([event(0, "unhold", "01:00")]));
+    assert!(matches!(
+        dialog.presence(),
+        Err(VconError::InvalidPartyHistory {
+            party: 0,
+            event: Event::Unhold,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn event_before_start_should_fail() {
+    let mut dialog = dialog(json!([event(0, "hold", "01:00")]));
+    dialog.start = "2022-09-23T10:02:00Z".parse().unwrap();
+    assert!(matches!(
+        dialog.presence(),
+        Err(VconError::InvalidPartyHistory {
+            reason: "event before dialog start",
+            ..
+        })
+    ));
+}
@@ -0,0 +1,130 @@
+use serde_json::json;
+use vcon_types::{
+    Content, StorageFormat, Vcon, VconError, VconReference, VconRepository, VconStore,
+};
+
+fn vcon(uuid: &str, created_at: &str, tel: &str) -> Vcon {
+    serde_json::from_value(json!({
+        "vcon": "0.0.1",
+        "uuid": uuid,
+        "created_at": created_at,
+        "parties": [{ "tel": tel }, { "mailto": "Agent@Example.com" }],
+        "dialog": [{
+            "type": "text",
+            "start": created_at,
+            "parties": [0, 1],
+            "body": "hello, I would like to cancel my subscription",
+            "encoding": "none",
+            "mimetype": "text/plain"
+        }]
+    }))
+    .unwrap()
+}
+
+fn repository(name: &str) -> (std::path::PathBuf, VconRepository) {
+    let root = std::env::temp_dir().join(format!("vcon-repository-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    let repository = VconRepository::open(&root, StorageFormat::Json).unwrap();
+    (root, repository)
+}
+
+const FIRST: &str = "01928e10-193e-8231-b9a2-279e0d16bc46";
+const SECOND: &str = "01928e10-193e-8231-b9a2-279e0d16bc47";
+
+#[test]
+fn repository_should_store_and_index() {
+    let (root, mut repository) = repository("index");
+    let (first, mut second) = (
+        vcon(FIRST, "2023-02-01T00:00:00Z", "+12025550123"),
+        vcon(SECOND, "2023-01-01T00:00:00Z", "+12025550199"),
+    );
+    second.group = Some(vec![VconReference::Uuid {
+        uuid: first.uuid.clone(),
+    }
+    .into()]);
+    repository.put(&first).unwrap();
+    repository.put(&second).unwrap();
+
+    assert_eq!(repository.get(&first.uuid).unwrap(), Some(first.clone()));
+    assert_eq!(repository.list(), [second.uuid.clone(), first.uuid.clone()]);
+    let date = "2023-01-15T00:00:00Z".parse().unwrap();
+    assert_eq!(
+        repository.created_between(Some(&date), None),
+        std::slice::from_ref(&first.uuid)
+    );
+    assert_eq!(repository.with_tel("+12025550199"), [second.uuid.clone()]);
+    assert_eq!(repository.with_mailto("agent@example.com").len(), 2);
+    assert_eq!(
+        repository.groups_containing(&first.uuid),
+        [second.uuid.clone()]
+    );
+
+    // indexes are rebuilt from disk
+    let mut repository = VconRepository::open(&root, StorageFormat::Json).unwrap();
+    assert_eq!(repository.list().len(), 2);
+    assert!(repository.delete(&first.uuid).unwrap());
+    assert!(!repository.delete(&first.uuid).unwrap());
+    assert_eq!(repository.get(&first.uuid).unwrap(), None);
+    assert!(repository.with_tel("+12025550123").is_empty());
+    assert_eq!(repository.with_mailto("agent@example.com"), [second.uuid]);
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn repository_should_externalize_bodies() {
+    let (root, repository) = repository("blobs");
+    let mut repository =
+        repository.externalize_bodies("https://example.com/blobs/".parse().unwrap(), 16);
+    let original = vcon(FIRST, "2023-02-01T00:00:00Z", "+12025550123");
+    repository.put(&original).unwrap();
+
+    let stored = repository.load(&original.uuid).unwrap();
+    let content = match &stored.dialog.as_ref().unwrap()[0].dialog {
+        vcon_types::Dialog::Text { content, .. } => content.clone(),
+        _ => unreachable!(),
+    };
+    let Content::UrlReferenced(referenced) = &content else {
+        panic!("body should be externalized");
+    };
+    assert!(referenced
+        .url
+        .as_str()
+        .starts_with("https://example.com/blobs/"));
+    assert_eq!(
+        content.bytes(&repository).unwrap().as_ref(),
+        b"hello, I would like to cancel my subscription"
+    );
+    assert_eq!(std::fs::read_dir(root.join("blobs")).unwrap().count(), 1);
+    std::fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn repository_should_only_store_what_it_reads_back() {
+    let (root, mut repository) = repository("unreadable");
+    let mut critical = vcon(FIRST, "2023-02-01T00:00:00Z", "+12025550123");
+    critical.critical = Some(vec!["x".to_string()]);
+    assert!(matches!(
+        repository.put(&critical),
+        Err(VconError::UnsupportedCriticalExtensions(unsupported)) if unsupported == ["x"]
+    ));
+    let mut unknown = vcon(FIRST, "2023-02-01T00:00:00Z", "+12025550123");
+    unknown.version = "v1.0".into();
+    assert!(matches!(
+        repository.put(&unknown),
+        Err(VconError::UnsupportedVersion(_))
+    ));
+    assert!(repository.list().is_empty());
+
+    // a file which cannot be read back does not prevent opening the others
+    let readable = vcon(SECOND, "2023-02-02T00:00:00Z", "+12025550199");
+    repository.put(&readable).unwrap();
+    let unreadable = root.join("vcons").join(format!("{FIRST}.json"));
+    std::fs::write(&unreadable, serde_json::to_vec(&critical).unwrap()).unwrap();
+    let repository = VconRepository::open(&root, StorageFormat::Json).unwrap();
+    assert_eq!(repository.list(), [readable.uuid]);
+    assert!(matches!(
+        repository.skipped(),
+        [(path, VconError::UnsupportedCriticalExtensions(_))] if *path == unreadable
+    ));
+    std::fs::remove_dir_all(root).unwrap();
+}
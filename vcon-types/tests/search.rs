@@ -0,0 +1,152 @@
+mod common;
+
+use common::call;
+use serde_json::json;
+use vcon_types::{SearchIndex, SearchQuery, SearchSource, Vcon, VconError};
+
+fn chat() -> Vcon {
+    serde_json::from_value(json!({
+        "vcon": "0.0.1",
+        "uuid": "01928e10-193e-8231-b9a2-279e0d16bc46",
+        "subject": "Billing question",
+        "parties": [
+            { "tel": "+12025550123", "name": "Carol" },
+            { "mailto": "Support@Example.com", "name": "Support" }
+        ],
+        "dialog": [{
+            "type": "text",
+            "start": "2023-01-02T10:00:00Z",
+            "parties": [0, 1],
+            "body": "Why was my card charged twice?",
+            "encoding": "none",
+            "mimetype": "text/plain"
+        }]
+    }))
+    .unwrap()
+}
+
+fn index() -> SearchIndex {
+    let mut index = SearchIndex::default();
+    index.add(&call()).unwrap();
+    index.add(&chat()).unwrap();
+    index
+}
+
+#[test]
+fn search_should_find_phrases_with_timestamps() {
+    let index = index();
+    assert_eq!(index.len(), 2);
+
+    let hits = index.search(&SearchQuery::new("Charged, TWICE"));
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].uuid, chat().uuid);
+    assert_eq!(hits[0].source, SearchSource::Text { dialog: 0 });
+    assert_eq!(hits[0].time, Some("2023-01-02T10:00:00Z".parse().unwrap()));
+    // words must be consecutive
+    assert!(index.search(&SearchQuery::new("card twice")).is_empty());
+
+    let hits = index.search(&SearchQuery::new("example com"));
+    let transcript = hits
+        .iter()
+        .find(|h| matches!(h.source, SearchSource::Transcript { .. }))
+        .unwrap();
+    assert_eq!(transcript.uuid, call().uuid);
+    assert_eq!(transcript.dialog(), Some(0));
+    let (start, end) = (
+        transcript.words[0].start.unwrap(),
+        transcript.words[1].end.unwrap(),
+    );
+    assert!(start > 0.0 && end >= start);
+    let dialog_start = call().dialog.unwrap()[0].start.clone();
+    assert!(transcript.time.as_ref().unwrap() > &dialog_start);
+
+    let hits = index.search(&SearchQuery::new("carol"));
+    assert_eq!(hits[0].source, SearchSource::PartyName { party: 0 });
+}
+
+#[test]
+fn search_should_filter() {
+    let index = index();
+    let query = || SearchQuery::new("billing");
+    assert_eq!(index.search(&query().tel("+12025550123")).len(), 1);
+    assert!(index.search(&query().tel("+33600000000")).is_empty());
+    assert_eq!(
+        index.search(&query().mailto("support@example.com")).len(),
+        1
+    );
+
+    let date = |d: &str| Some(d.parse().unwrap());
+    let in_range = query().between(date("2023-01-01T00:00:00Z"), date("2023-01-03T00:00:00Z"));
+    assert_eq!(index.search(&in_range).len(), 1);
+    let too_late = SearchQuery::new("twice").between(date("2023-01-03T00:00:00Z"), None);
+    assert!(index.search(&too_late).is_empty());
+}
+
+#[test]
+fn index_should_persist_and_update() {
+    let path = std::env::temp_dir().join(format!("vcon-search-{}.json", std::process::id()));
+    index().save(&path).unwrap();
+    let mut index = SearchIndex::open(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(index.search(&SearchQuery::new("twice")).len(), 1);
+
+    let mut chat = chat();
+    chat.subject = Some("Refund".to_string());
+    index.add(&chat).unwrap();
+    assert_eq!(index.len(), 2);
+    assert!(index.search(&SearchQuery::new("billing")).is_empty());
+    assert_eq!(index.search(&SearchQuery::new("refund")).len(), 1);
+
+    assert!(index.remove(&chat.uuid));
+    assert!(index.search(&SearchQuery::new("refund")).is_empty());
+    assert!(SearchIndex::open(&path).unwrap().is_empty());
+}
+
+#[test]
+fn corrupted_index_should_not_open() {
+    let path = std::env::temp_dir().join(format!("vcon-corrupted-{}.json", std::process::id()));
+    index().save(&path).unwrap();
+    let saved: serde_json::Value = serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+    let corrupt = |change: fn(&mut serde_json::Value)| {
+        let mut snapshot = saved.clone();
+        change(&mut snapshot);
+        std::fs::write(&path, serde_json::to_vec(&snapshot).unwrap()).unwrap();
+        SearchIndex::open(&path)
+    };
+
+    assert!(corrupt(|_| ()).is_ok());
+    let corruptions: [fn(&mut serde_json::Value); 5] = [
+        |s| s["postings"]["twice"][0][0] = json!(42),
+        |s| s["postings"]["twice"][0][1] = json!(42),
+        |s| s["postings"]["twice"][0][2] = json!(42),
+        |s| s["documents"][0] = json!(null),
+        |s| s["documents"][1] = s["documents"][0].clone(),
+    ];
+    for change in corruptions {
+        assert!(matches!(
+            corrupt(change),
+            Err(VconError::InvalidSearchIndex(_))
+        ));
+    }
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn reindexing_should_keep_hits() {
+    let mut index = index();
+    let hits = |index: &SearchIndex, phrase| index.search(&SearchQuery::new(phrase));
+    let expected = hits(&index, "name");
+    assert!(!expected.is_empty());
+    for _ in 0..10 {
+        index.add(&chat()).unwrap();
+        index.add(&call()).unwrap();
+    }
+    assert_eq!(index.len(), 2);
+    assert_eq!(hits(&index, "twice").len(), 1);
+    let mut reindexed = hits(&index, "name");
+    // vCons are now indexed the other way round
+    reindexed.sort_by_key(|h| h.uuid.to_string());
+    let mut expected = expected;
+    expected.sort_by_key(|h| h.uuid.to_string());
+    assert_eq!(reindexed, expected);
+}
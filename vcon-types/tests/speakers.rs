@@ -0,0 +1,75 @@
+mod common;
+
+use common::call;
+use vcon_types::{SpeakerMapping, Transcript, TranscriptSegment, Vcon};
+
+fn segment(start: f64, speaker: u32) -> TranscriptSegment {
+    TranscriptSegment {
+        text: "hello".to_string(),
+        start,
+        end: start + 1.0,
+        speaker: Some(speaker),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn mapping_should_follow_appearance_and_originator() {
+    let mut vcon: serde_json::Value = serde_json::to_value(call()).unwrap();
+    // diarization labels the second party to talk as 0
+    let transcript = Transcript {
+        segments: vec![segment(0.0, 3), segment(1.0, 0), segment(2.0, 7)],
+        ..Default::default()
+    };
+
+    let dialog = serde_json::from_value(vcon["dialog"][0].clone()).unwrap();
+    let mapping = SpeakerMapping::infer(&dialog, &transcript);
+    assert_eq!(mapping.iter().collect::<Vec<_>>(), [(0, 1), (3, 0)]);
+
+    vcon["dialog"][0]["originator"] = 1.into();
+    let dialog = serde_json::from_value(vcon["dialog"][0].clone()).unwrap();
+    let mapping = SpeakerMapping::infer(&dialog, &transcript);
+    assert_eq!(mapping.iter().collect::<Vec<_>>(), [(0, 0), (3, 1)]);
+
+    let mut mapped = transcript.clone();
+    mapping.apply(&mut mapped);
+    let speakers = mapped
+        .segments
+        .iter()
+        .map(|s| s.speaker)
+        .collect::<Vec<_>>();
+    assert_eq!(speakers, [Some(1), Some(0), None]);
+}
+
+#[test]
+fn multichannel_should_map_channels_to_parties() {
+    let vcon = call();
+    let transcript = Transcript {
+        channels: Some(2),
+        segments: vec![segment(0.0, 1), segment(1.0, 0)],
+        ..Default::default()
+    };
+    let mapping = SpeakerMapping::infer(&vcon.dialog.as_ref().unwrap()[0], &transcript);
+    assert_eq!(mapping.iter().collect::<Vec<_>>(), [(0, 0), (1, 1)]);
+}
+
+#[test]
+fn persisted_mapping_should_be_used() {
+    let mut vcon = call();
+    let inferred = vcon.transcript(0).unwrap().unwrap();
+    assert!(inferred.segments.iter().all(|s| s.speaker == Some(0)));
+
+    let mut mapping = SpeakerMapping::default();
+    mapping.insert(0, 1);
+    let analysis = &mut vcon.analysis.as_mut().unwrap()[0];
+    analysis.set_speaker_mapping(&mapping).unwrap();
+    assert_eq!(analysis.speaker_mapping().unwrap(), Some(mapping.clone()));
+
+    // survives a round trip
+    let vcon: Vcon = serde_json::from_str(&serde_json::to_string(&vcon).unwrap()).unwrap();
+    let analysis = &vcon.analysis.as_ref().unwrap()[0];
+    assert_eq!(analysis.speaker_mapping().unwrap(), Some(mapping));
+    let mapped = vcon.transcript(0).unwrap().unwrap();
+    assert!(mapped.segments.iter().all(|s| s.speaker == Some(1)));
+    assert_eq!(vcon.conversation_metrics().unwrap()[0].parties[&0].words, 0);
+}
@@ -0,0 +1,241 @@
+#![cfg(feature = "sqlite")]
+
+mod common;
+
+use rusqlite::Connection;
+use serde_json::json;
+use vcon_types::{ParseOptions, SqliteStore, Vcon, VconError};
+
+/// [common::call] in which both parties were put on hold
+fn held_call() -> Vcon {
+    let mut vcon = common::call_json();
+    vcon["dialog"][0]["party_history"] = json!([
+        { "party": 1, "event": "hold", "time": "2022-06-21T17:53:27Z" },
+        { "party": 1, "event": "unhold", "time": "2022-06-21T17:53:57Z" },
+        { "party": 0, "event": "hold", "time": "2022-06-21T17:53:30Z" },
+        { "party": 0, "event": "unhold", "time": "2022-06-21T17:53:31Z" }
+    ]);
+    serde_json::from_value(vcon).unwrap()
+}
+
+fn count(store: &SqliteStore, table: &str) -> u32 {
+    store
+        .connection()
+        .query_row(&format!("SELECT COUNT(*) FROM {table}"), [], |r| r.get(0))
+        .unwrap()
+}
+
+#[test]
+fn sqlite_should_round_trip() {
+    let mut store = SqliteStore::open_in_memory().unwrap();
+    let vcon = held_call();
+    store.save(&vcon).unwrap();
+    // saving again replaces
+    store.save(&vcon).unwrap();
+    assert_eq!(store.load(&vcon.uuid).unwrap(), Some(vcon.clone()));
+
+    assert_eq!(count(&store, "vcons"), 1);
+    assert_eq!(count(&store, "parties"), 2);
+    assert_eq!(count(&store, "party_events"), 4);
+    assert_eq!(count(&store, "analysis"), 1);
+
+    assert!(store.delete(&vcon.uuid).unwrap());
+    assert_eq!(store.load(&vcon.uuid).unwrap(), None);
+    assert_eq!(count(&store, "parties"), 0);
+    assert_eq!(count(&store, "party_intervals"), 0);
+}
+
+#[test]
+fn projection_should_answer_analytics_queries() {
+    let mut store = SqliteStore::open_in_memory().unwrap();
+    let vcon = held_call();
+    store.save(&vcon).unwrap();
+
+    let on_hold_longer_than = |seconds: f64| -> Vec<(String, String)> {
+        let mut statement = store
+            .connection()
+            .prepare(
+                "SELECT i.vcon, p.name FROM party_intervals i
+                 JOIN parties p ON p.vcon = i.vcon AND p.idx = i.party
+                 WHERE i.kind = 'on_hold'
+                 GROUP BY i.vcon, i.party HAVING SUM(i.seconds) > ?1",
+            )
+            .unwrap();
+        statement
+            .query_map([seconds], |r| Ok((r.get(0)?, r.get(1)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap()
+    };
+    assert_eq!(
+        on_hold_longer_than(20.0),
+        [(vcon.uuid.to_string(), "Bob".to_string())]
+    );
+    assert_eq!(on_hold_longer_than(0.5).len(), 2);
+    assert!(on_hold_longer_than(120.0).is_empty());
+}
+
+#[test]
+fn migrations_should_be_recorded() {
+    let path = std::env::temp_dir().join(format!("vcon-sqlite-{}.db", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let vcon = held_call();
+    SqliteStore::open(&path).unwrap().save(&vcon).unwrap();
+
+    let store = SqliteStore::open(&path).unwrap();
+    let applied: usize = store
+        .connection()
+        .pragma_query_value(None, "user_version", |r| r.get(0))
+        .unwrap();
+    assert_eq!(applied, SqliteStore::MIGRATIONS.len());
+    assert_eq!(store.schema_version(), "0.3.0");
+    assert_eq!(store.load(&vcon.uuid).unwrap(), Some(vcon));
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn inconsistent_party_history_should_be_recorded() {
+    let mut store = SqliteStore::open_in_memory().unwrap();
+    let mut vcon = held_call();
+    let history = vcon.dialog.as_mut().unwrap()[0]
+        .party_history
+        .as_mut()
+        .unwrap();
+    history.remove(0);
+    store.save(&vcon).unwrap();
+
+    let error: Option<String> = store
+        .connection()
+        .query_row(
+            "SELECT party_history_error FROM dialogs WHERE idx = 0",
+            [],
+            |r| r.get(0),
+        )
+        .unwrap();
+    assert!(error.unwrap().contains("party 1"));
+    assert_eq!(count(&store, "party_intervals"), 0);
+    assert_eq!(store.load(&vcon.uuid).unwrap(), Some(vcon));
+}
+
+fn current_draft() -> Vcon {
+    serde_json::from_str(include_str!("../examples/json/current-draft-fields.json")).unwrap()
+}
+
+/// Options of a consumer of [current_draft], which marks `contact_center` as critical
+fn contact_center() -> ParseOptions {
+    ParseOptions {
+        supported_extensions: ["contact_center".to_string()].into(),
+        ..Default::default()
+    }
+}
+
+fn current_draft_columns(store: &SqliteStore) -> (String, String, String, String) {
+    store
+        .connection()
+        .query_row(
+            "SELECT v.critical, p.sip, p.timezone, d.application FROM vcons v
+             JOIN parties p ON p.vcon = v.uuid AND p.idx = 0
+             JOIN dialogs d ON d.vcon = v.uuid AND d.idx = 0",
+            [],
+            |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?)),
+        )
+        .unwrap()
+}
+
+fn expected_current_draft_columns() -> (String, String, String, String) {
+    (
+        r#"["contact_center"]"#.to_string(),
+        "sip:alice@example.com".to_string(),
+        "America/New_York".to_string(),
+        "zoom".to_string(),
+    )
+}
+
+#[test]
+fn current_draft_fields_should_be_projected() {
+    let mut store = SqliteStore::open_in_memory()
+        .unwrap()
+        .with_options(contact_center());
+    store.save(&current_draft()).unwrap();
+    assert_eq!(
+        current_draft_columns(&store),
+        expected_current_draft_columns()
+    );
+}
+
+#[test]
+fn migrating_should_project_the_stored_current_draft_fields() {
+    // a store saved by a version of the crate which only knew the first migrations
+    let connection = Connection::open_in_memory().unwrap();
+    for migration in &SqliteStore::MIGRATIONS[..2] {
+        connection.execute_batch(migration.sql).unwrap();
+    }
+    connection.pragma_update(None, "user_version", 2).unwrap();
+    let vcon = current_draft();
+    let uuid = vcon.uuid.to_string();
+    for sql in [
+        "INSERT INTO vcons (uuid, version, document) VALUES (?1, '0.3.0', ?2)",
+        "INSERT INTO parties (vcon, idx) VALUES (?1, 0)",
+        "INSERT INTO dialogs (vcon, idx, type, start) VALUES (?1, 0, 'recording', '')",
+    ] {
+        let mut statement = connection.prepare(sql).unwrap();
+        let document = serde_json::to_string(&vcon).unwrap();
+        let params = [uuid.as_str(), &document];
+        statement
+            .execute(rusqlite::params_from_iter(
+                &params[..statement.parameter_count()],
+            ))
+            .unwrap();
+    }
+
+    let store = SqliteStore::from_connection(connection)
+        .unwrap()
+        .with_options(contact_center());
+    assert_eq!(
+        current_draft_columns(&store),
+        expected_current_draft_columns()
+    );
+    assert_eq!(store.load(&vcon.uuid).unwrap(), Some(vcon));
+}
+
+#[test]
+fn unsupported_versions_should_be_neither_saved_nor_loaded() {
+    let mut store = SqliteStore::open_in_memory().unwrap();
+    let mut vcon = held_call();
+    vcon.version = "9.9.9".into();
+    assert!(matches!(
+        store.save(&vcon),
+        Err(VconError::UnsupportedVersion(v)) if v == "9.9.9"
+    ));
+
+    let mut document = serde_json::to_value(&vcon).unwrap();
+    document["vcon"] = "9.9.9".into();
+    store
+        .connection()
+        .execute(
+            "INSERT INTO vcons (uuid, version, document) VALUES (?1, '9.9.9', ?2)",
+            [vcon.uuid.to_string(), document.to_string()],
+        )
+        .unwrap();
+    assert!(matches!(
+        store.load(&vcon.uuid),
+        Err(VconError::UnsupportedVersion(v)) if v == "9.9.9"
+    ));
+}
+
+#[test]
+fn critical_extensions_should_be_supported_to_be_saved() {
+    let vcon = current_draft();
+    let mut store = SqliteStore::open_in_memory().unwrap();
+    assert!(matches!(
+        store.save(&vcon),
+        Err(VconError::UnsupportedCriticalExtensions(unsupported)) if unsupported == ["contact_center"]
+    ));
+    assert_eq!(count(&store, "vcons"), 0);
+
+    let mut store = SqliteStore::open_in_memory()
+        .unwrap()
+        .with_options(contact_center());
+    store.save(&vcon).unwrap();
+    assert_eq!(store.load(&vcon.uuid).unwrap(), Some(vcon));
+}
@@ -0,0 +1,123 @@
+mod common;
+
+use serde_json::json;
+use vcon_types::{Event, TimelineEntryKind, Transcript, TranscriptSegment, Vcon};
+
+/// [common::call] with a hold and a text dialog
+fn call_with_text() -> Vcon {
+    let mut vcon = common::call_json();
+    vcon["dialog"][0]["party_history"] = json!([
+        { "party": 1, "event": "hold", "time": "2022-06-21T17:53:30Z" },
+        { "party": 1, "event": "unhold", "time": "2022-06-21T17:53:31Z" }
+    ]);
+    vcon["dialog"].as_array_mut().unwrap().push(json!({
+        "type": "text",
+        "start": "2022-06-21T17:53:27Z",
+        "parties": [1, 0],
+        "originator": 1,
+        "body": "are you there?",
+        "encoding": "none",
+        "mimetype": "text/plain"
+    }));
+    serde_json::from_value(vcon).unwrap()
+}
+
+#[test]
+fn timeline_should_be_chronological() {
+    let vcon = call_with_text();
+    let timeline = vcon.timeline().unwrap();
+    assert!(timeline.windows(2).all(|w| w[0].time <= w[1].time));
+
+    assert_eq!(timeline[0].kind, TimelineEntryKind::DialogStart);
+    assert_eq!(timeline[0].time, "2022-06-21T17:53:26Z".parse().unwrap());
+    // the text message comes from the second dialog
+    let message = timeline
+        .iter()
+        .find(|e| matches!(e.kind, TimelineEntryKind::Message { .. }))
+        .unwrap();
+    assert_eq!(message.dialog, 1);
+    assert_eq!(
+        message.kind,
+        TimelineEntryKind::Message {
+            originator: Some(1),
+            text: Some("are you there?".to_string())
+        }
+    );
+    let events = timeline
+        .iter()
+        .filter_map(|e| match e.kind {
+            TimelineEntryKind::PartyEvent { party, event } => Some((party, event)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(events, [(1, Event::Hold), (1, Event::Unhold)]);
+    let last = timeline.last().unwrap();
+    assert_eq!(last.kind, TimelineEntryKind::DialogEnd);
+    assert_eq!(last.dialog, 0);
+    assert_eq!(
+        Some(&last.time),
+        vcon.dialog.as_ref().unwrap()[0].end().as_ref()
+    );
+}
+
+#[test]
+fn utterances_should_be_absolute() {
+    let vcon = call_with_text();
+    let transcript = vcon_types::Transcript::from_analysis(&vcon.analysis.as_ref().unwrap()[0])
+        .unwrap()
+        .unwrap();
+    let utterances = vcon
+        .timeline()
+        .unwrap()
+        .into_iter()
+        .filter_map(|e| match e.kind {
+            TimelineEntryKind::Utterance {
+                party, text, end, ..
+            } => Some((e.time, party, text, end)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(utterances.len(), transcript.segments.len());
+
+    let start = vcon.dialog.as_ref().unwrap()[0].start.clone();
+    let (time, party, text, end) = &utterances[0];
+    assert_eq!(
+        *time,
+        vcon.dialog.as_ref().unwrap()[0]
+            .at(transcript.segments[0].start)
+            .unwrap()
+    );
+    assert!(*time > start && end > time);
+    assert_eq!(*party, Some(0));
+    assert_eq!(text, &transcript.segments[0].text);
+}
+
+#[test]
+fn utterances_out_of_range_should_be_skipped() {
+    let mut vcon = call_with_text();
+    let segment = |start: f64| TranscriptSegment {
+        text: format!("at {start}"),
+        start,
+        end: start + 1.0,
+        ..Default::default()
+    };
+    let transcript = Transcript {
+        segments: vec![segment(1.0), segment(1e20), segment(-1e20)],
+        ..Default::default()
+    };
+    let analysis = vcon.analysis.as_mut().unwrap();
+    analysis.insert(0, transcript.to_analysis(0).unwrap());
+
+    let texts = vcon
+        .timeline()
+        .unwrap()
+        .into_iter()
+        .filter_map(|e| match e.kind {
+            TimelineEntryKind::Utterance {
+                analysis: 0, text, ..
+            } => Some(text),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(texts, ["at 1"]);
+}
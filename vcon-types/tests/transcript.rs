@@ -0,0 +1,169 @@
+mod common;
+
+use common::Remote;
+use serde_json::json;
+use vcon_types::{Analysis, Transcript};
+
+#[test]
+fn deepgram_should_normalize() {
+    let transcript = Transcript::from_analysis(&common::call().analysis.unwrap()[0])
+        .unwrap()
+        .unwrap();
+
+    let first = &transcript.segments[0];
+    assert_eq!(first.text, "Hello.");
+    assert_eq!(first.speaker, Some(0));
+    assert_eq!(first.words.len(), 1);
+    assert_eq!(first.words[0].text, "Hello.");
+    assert!(transcript
+        .segments
+        .windows(2)
+        .all(|s| s[0].start <= s[1].start));
+    assert_eq!(transcript.speakers(), [0].into());
+    assert!(transcript.text().ends_with("Have a nice day."));
+}
+
+#[test]
+fn whisper_should_normalize() {
+    let whisper = json!({
+        "language": "english",
+        "duration": 3.0,
+        "text": "Hello there. Hi.",
+        "segments": [
+            { "id": 0, "start": 0.0, "end": 1.5, "text": " Hello there.", "avg_logprob": 0.0 },
+            { "id": 1, "start": 2.0, "end": 3.0, "text": " Hi.", "avg_logprob": -0.5 }
+        ],
+        "words": [
+            { "word": "Hello", "start": 0.0, "end": 0.5 },
+            { "word": "there.", "start": 0.6, "end": 1.5 },
+            { "word": "Hi.", "start": 2.0, "end": 3.0 }
+        ]
+    });
+    let transcript = Transcript::from_whisper_json(whisper.to_string().as_bytes()).unwrap();
+    assert_eq!(transcript.language.as_deref(), Some("english"));
+    assert_eq!(transcript.segments.len(), 2);
+    assert_eq!(transcript.segments[0].text, "Hello there.");
+    assert_eq!(transcript.segments[0].confidence, Some(1.0));
+    assert_eq!(transcript.segments[0].words.len(), 2);
+    assert_eq!(transcript.segments[1].words[0].text, "Hi.");
+}
+
+#[test]
+fn aws_transcribe_should_normalize() {
+    let aws = json!({
+        "jobName": "job",
+        "status": "COMPLETED",
+        "results": {
+            "language_code": "en-US",
+            "transcripts": [{ "transcript": "Hello, Bob. Hi." }],
+            "speaker_labels": {
+                "speakers": 2,
+                "segments": [
+                    { "start_time": "0.0", "end_time": "1.2", "speaker_label": "spk_0", "items": [] },
+                    { "start_time": "1.5", "end_time": "2.0", "speaker_label": "spk_1", "items": [] }
+                ]
+            },
+            "items": [
+                { "type": "pronunciation", "start_time": "0.0", "end_time": "0.5", "alternatives": [{ "confidence": "0.9", "content": "Hello" }] },
+                { "type": "punctuation", "alternatives": [{ "confidence": "0.0", "content": "," }] },
+                { "type": "pronunciation", "start_time": "0.6", "end_time": "1.2", "alternatives": [{ "confidence": "0.7", "content": "Bob" }] },
+                { "type": "punctuation", "alternatives": [{ "confidence": "0.0", "content": "." }] },
+                { "type": "pronunciation", "start_time": "1.5", "end_time": "2.0", "alternatives": [{ "confidence": "1.0", "content": "Hi" }] },
+                { "type": "punctuation", "alternatives": [{ "confidence": "0.0", "content": "." }] }
+            ]
+        }
+    });
+    let transcript = Transcript::from_aws_transcribe_json(aws.to_string().as_bytes()).unwrap();
+    assert_eq!(transcript.language.as_deref(), Some("en-US"));
+    assert_eq!(transcript.segments.len(), 2);
+    assert_eq!(transcript.segments[0].text, "Hello, Bob.");
+    assert_eq!(transcript.segments[0].speaker, Some(0));
+    assert_eq!(transcript.segments[1].text, "Hi.");
+    assert_eq!(transcript.segments[1].words[0].speaker, Some(1));
+}
+
+#[test]
+fn google_stt_should_normalize() {
+    let google = json!({
+        "results": [
+            {
+                "alternatives": [{ "transcript": "hello Bob hi", "confidence": 0.9 }],
+                "languageCode": "en-us"
+            },
+            {
+                "alternatives": [{
+                    "transcript": "hello Bob hi",
+                    "words": [
+                        { "startTime": "0s", "endTime": "0.500s", "word": "hello", "speakerTag": 1 },
+                        { "startTime": "0.600s", "endTime": "1.200s", "word": "Bob", "speakerTag": 1 },
+                        { "startTime": "1.500s", "endTime": "2s", "word": "hi", "speakerTag": 2 }
+                    ]
+                }]
+            }
+        ]
+    });
+    let transcript = Transcript::from_google_stt_json(google.to_string().as_bytes()).unwrap();
+    assert_eq!(transcript.language.as_deref(), Some("en-us"));
+    assert_eq!(transcript.segments.len(), 2);
+    assert_eq!(transcript.segments[0].text, "hello Bob");
+    assert_eq!(transcript.segments[0].end, 1.2);
+    assert_eq!(transcript.segments[1].speaker, Some(2));
+}
+
+#[test]
+fn normalized_transcript_should_roundtrip_through_analysis() {
+    let transcript = Transcript::from_whisper_json(
+        json!({ "segments": [{ "start": 0.0, "end": 1.0, "text": "Hi" }] })
+            .to_string()
+            .as_bytes(),
+    )
+    .unwrap();
+    let analysis = serde_json::from_value::<Analysis>(json!({
+        "type": "transcript",
+        "dialog": 0,
+        "schema": Transcript::SCHEMA,
+        "encoding": "json",
+        "body": transcript
+    }))
+    .unwrap();
+    assert_eq!(
+        Transcript::from_analysis(&analysis).unwrap(),
+        Some(transcript)
+    );
+}
+
+#[test]
+fn url_referenced_transcripts_should_be_fetched_or_skipped() {
+    let body = json!({ "segments": [{ "start": 0.0, "end": 1.0, "text": " Hi" }] }).to_string();
+    use base64::Engine as _;
+    use sha2::Digest as _;
+    let signature = base64::prelude::BASE64_URL_SAFE_NO_PAD.encode(sha2::Sha512::digest(&body));
+    let analysis = |vendor: &str| {
+        serde_json::from_value::<Analysis>(json!({
+            "type": "transcript",
+            "dialog": 0,
+            "vendor": vendor,
+            "url": "https://example.com/transcript.json",
+            "alg": "SHA-512",
+            "signature": signature
+        }))
+        .unwrap()
+    };
+    let store = Remote(body.into_bytes());
+
+    let whisper = analysis("whisper");
+    assert_eq!(Transcript::from_analysis(&whisper).unwrap(), None);
+    let transcript = Transcript::from_analysis_with(&whisper, &store)
+        .unwrap()
+        .unwrap();
+    assert_eq!(transcript.text(), "Hi");
+
+    // unsupported vendors are skipped without being fetched
+    let failing = Remote(b"not json".to_vec());
+    let unknown = analysis("acme");
+    assert_eq!(Transcript::from_analysis(&unknown).unwrap(), None);
+    assert_eq!(
+        Transcript::from_analysis_with(&unknown, &failing).unwrap(),
+        None
+    );
+}
@@ -0,0 +1,110 @@
+mod common;
+
+use common::CALL;
+use serde_json::json;
+use vcon_types::{ParseOptions, UnknownFields, Vcon, VconError};
+
+const UUID: &str = "01928e10-193e-8231-b9a2-279e0d16bc46";
+
+/// Analysis example with an unknown key on every kind of object
+fn extended() -> serde_json::Value {
+    let mut json: serde_json::Value = serde_json::from_str(CALL).unwrap();
+    json["x_case"] = json!("C-42");
+    json["redacted"] = json!({ "uuid": UUID, "type": "pii", "x_reason": "gdpr" });
+    json["group"] = json!([{ "uuid": UUID, "x_role": "transfer" }]);
+    json["parties"][0]["x_skill"] = json!("billing");
+    json["parties"][0]["civic_address"] = json!({ "country": "US", "x_zone": 3 });
+    json["dialog"][0]["x_queue"] = json!("support");
+    json["dialog"][0]["party_history"] =
+        json!([{ "party": 0, "event": "join", "time": "2022-06-21T17:53:26Z", "x_seat": 7 }]);
+    json["analysis"][0]["x_reviewed"] = json!(true);
+    json
+}
+
+#[test]
+fn unknown_fields_should_be_preserved_on_every_object() {
+    let json = extended();
+    let vcon: Vcon = serde_json::from_value(json.clone()).unwrap();
+
+    let object = &vcon.dialog.as_ref().unwrap()[0];
+    assert_eq!(
+        object.extension_object,
+        serde_json::from_value(json!({ "x_queue": "support" })).unwrap()
+    );
+
+    // no key is duplicated nor lost
+    let serialized = serde_json::to_string(&vcon).unwrap();
+    assert_eq!(serialized.matches(r#""encoding":"#).count(), 1);
+    let reparsed: serde_json::Value = serde_json::from_str(&serialized).unwrap();
+    let keys = |v: &serde_json::Value| v.as_object().unwrap().keys().cloned().collect::<Vec<_>>();
+    assert_eq!(keys(&reparsed), keys(&json));
+    for (pointer, key) in [
+        ("/redacted", "x_reason"),
+        ("/group/0", "x_role"),
+        ("/parties/0", "x_skill"),
+        ("/parties/0/civic_address", "x_zone"),
+        ("/dialog/0", "x_queue"),
+        ("/dialog/0/party_history/0", "x_seat"),
+        ("/analysis/0", "x_reviewed"),
+    ] {
+        assert_eq!(
+            reparsed.pointer(pointer).unwrap()[key],
+            json.pointer(pointer).unwrap()[key]
+        );
+    }
+    assert_eq!(
+        reparsed.pointer("/analysis/0/body"),
+        json.pointer("/analysis/0/body")
+    );
+}
+
+#[test]
+fn strict_parsing_should_report_the_path_of_unknown_fields() {
+    let json = serde_json::to_vec(&extended()).unwrap();
+    let vcon = Vcon::parse_json(&json, &ParseOptions::default()).unwrap();
+    assert_eq!(
+        vcon.unknown_fields(&Default::default()),
+        [
+            "x_case",
+            "redacted.x_reason",
+            "group[0].x_role",
+            "parties[0].x_skill",
+            "parties[0].civic_address.x_zone",
+            "dialog[0].x_queue",
+            "dialog[0].party_history[0].x_seat",
+            "analysis[0].x_reviewed",
+        ]
+    );
+
+    let strict = ParseOptions {
+        unknown_fields: UnknownFields::Reject,
+        ..Default::default()
+    };
+    assert!(matches!(
+        Vcon::parse_json(&json, &strict),
+        Err(VconError::UnknownField(path)) if path == "x_case"
+    ));
+    // spec only documents are accepted
+    Vcon::parse_json(CALL.as_bytes(), &strict).unwrap();
+}
+
+#[test]
+fn strict_parsing_should_accept_registered_extensions() {
+    let json = serde_json::to_vec(&extended()).unwrap();
+    let mut strict = ParseOptions {
+        unknown_fields: UnknownFields::Reject,
+        extensions: [
+            "x_case", "x_reason", "x_role", "x_skill", "x_zone", "x_queue", "x_seat",
+        ]
+        .map(String::from)
+        .into(),
+        ..Default::default()
+    };
+    assert!(matches!(
+        Vcon::parse_json(&json, &strict),
+        Err(VconError::UnknownField(path)) if path == "analysis[0].x_reviewed"
+    ));
+
+    strict.extensions.insert("x_reviewed".to_string());
+    Vcon::parse_json(&json, &strict).unwrap();
+}
@@ -0,0 +1,99 @@
+mod common;
+
+use common::call_json;
+use serde_json::json;
+use vcon_types::{Content, Dialog, InlineContent, Vcon, VconError};
+
+#[test]
+fn validate_should_check_indexes() {
+    let vcon: Vcon = serde_json::from_value(call_json()).unwrap();
+    vcon.validate().unwrap();
+
+    let mut invalid = call_json();
+    invalid["analysis"][0]["dialog"] = json!(3);
+    let vcon: Vcon = serde_json::from_value(invalid).unwrap();
+    assert!(matches!(
+        vcon.validate(),
+        Err(VconError::UnknownIndex {
+            target: "dialog",
+            index: 3,
+            ..
+        })
+    ));
+
+    let mut invalid = call_json();
+    invalid["dialog"][0]["originator"] = json!(2);
+    let vcon: Vcon = serde_json::from_value(invalid).unwrap();
+    let error = vcon.validate().unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "dialog[0].originator references unknown party 2"
+    );
+}
+
+#[test]
+fn validate_should_reject_out_of_range_durations() {
+    for (start, duration) in [
+        ("2022-06-21T17:53:26Z", json!(-1.5)),
+        ("2022-06-21T17:53:26Z", json!(1e30)),
+        ("9999-12-31T23:00:00Z", json!(u32::MAX)),
+    ] {
+        let mut invalid = call_json();
+        invalid["dialog"][0]["start"] = json!(start);
+        invalid["dialog"][0]["duration"] = duration;
+        let vcon: Vcon = serde_json::from_value(invalid).unwrap();
+        assert_eq!(vcon.dialog.as_ref().unwrap()[0].end(), None);
+        assert!(vcon.presence().is_ok());
+        assert!(matches!(
+            vcon.validate(),
+            Err(VconError::InvalidVcon(reason)) if reason.starts_with("dialog[0].duration")
+        ));
+    }
+}
+
+#[test]
+fn redact_should_remove_data() {
+    let original: Vcon = serde_json::from_value(call_json()).unwrap();
+    let mut redacted = Vcon::redact(&original, "PII Masked");
+    redacted.redact_party(1).unwrap();
+    redacted.redact_dialog(0).unwrap();
+    assert!(redacted.redact_party(2).is_err());
+
+    assert_ne!(redacted.uuid, original.uuid);
+    assert!(redacted.updated_at.is_some());
+    let party = &redacted.parties.as_ref().unwrap()[1];
+    assert_eq!((&party.name, &party.tel), (&None, &None));
+    assert_eq!(
+        redacted.parties.as_ref().unwrap()[0],
+        original.parties.as_ref().unwrap()[0]
+    );
+    let Dialog::Recording { content, .. } = &redacted.dialog.as_ref().unwrap()[0].dialog else {
+        unreachable!()
+    };
+    assert_eq!(
+        content,
+        &Content::Inline(InlineContent::TextNone(String::new()))
+    );
+    assert_eq!(redacted.analysis, Some(vec![]));
+    redacted.validate().unwrap();
+}
+
+#[test]
+fn redact_attachment_should_move_the_next_ones_down() {
+    let mut original = call_json();
+    original["attachments"] = json!(["contract", "invoice"]
+        .map(|typ| json!({ "type": typ, "start": "2022-06-21T17:53:26Z", "party": 0, "encoding": "none", "body": typ })));
+    let mut redacted = Vcon::redact(&serde_json::from_value(original).unwrap(), "PII Masked");
+
+    redacted.redact_attachment(0).unwrap();
+    let attachments = redacted.attachments.as_ref().unwrap();
+    assert_eq!(attachments.len(), 1);
+    assert_eq!(attachments[0].typ, "invoice");
+    assert!(matches!(
+        redacted.redact_attachment(1),
+        Err(VconError::UnknownIndex { index: 1, .. })
+    ));
+    redacted.redact_attachment(0).unwrap();
+    assert_eq!(redacted.attachments, None);
+    redacted.validate().unwrap();
+}